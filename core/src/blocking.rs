@@ -0,0 +1,491 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    spanned::Spanned,
+    visit_mut::{self, VisitMut},
+};
+
+use crate::{
+    params::{ConvertMode, IdentCase, MacroParameters},
+    visit_ext::{VisitMutExt, Visitor},
+    visitor_async::{search_future_output_in_type, AsyncAwaitVisitor},
+};
+
+const INNER_NAME: &str = "inner";
+const ARG_PREFIX: &str = "__maybe_async_cfg_arg";
+const RUNTIME_FN_NAME: &str = "__maybe_async_cfg_runtime";
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Executor {
+    Tokio,
+    Futures,
+    AsyncStd,
+}
+
+impl Executor {
+    pub fn from_str<S: AsRef<str>>(s: S) -> Option<Self> {
+        match s.as_ref() {
+            "tokio" => Some(Self::Tokio),
+            "futures" => Some(Self::Futures),
+            "async-std" | "async_std" => Some(Self::AsyncStd),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Self::Tokio => "tokio",
+            Self::Futures => "futures",
+            Self::AsyncStd => "async-std",
+        }
+    }
+}
+
+// The tokio runtime is shared by all the blocking functions of the crate: it is defined once at the
+// crate root by `blocking_runtime!()` (see `runtime_fn`).
+fn block_on(params: &MacroParameters, fut: TokenStream2) -> TokenStream2 {
+    if let Some(path) = params.block_on_get() {
+        return quote!(#path(#fut));
+    }
+
+    match params.executor_get() {
+        Some(Executor::Tokio) => {
+            let runtime = syn::Ident::new(RUNTIME_FN_NAME, Span::call_site());
+            quote!(crate::#runtime().block_on(#fut))
+        }
+        Some(Executor::AsyncStd) => quote!(::async_std::task::block_on(#fut)),
+        Some(Executor::Futures) | None => quote!(::futures::executor::block_on(#fut)),
+    }
+}
+
+// The runtime is created on the first call and is never dropped, so the tasks spawned by the
+// futures keep running after the calls return. It is a multi-thread runtime with one worker, since
+// only such a runtime drives its tasks and I/O resources outside of `block_on`.
+pub fn runtime_fn() -> TokenStream2 {
+    let runtime = syn::Ident::new(RUNTIME_FN_NAME, Span::call_site());
+    quote!(
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        fn #runtime() -> &'static ::tokio::runtime::Runtime {
+            static RUNTIME: ::std::sync::OnceLock<::tokio::runtime::Runtime> = ::std::sync::OnceLock::new();
+            RUNTIME.get_or_init(|| {
+                ::tokio::runtime::Builder::new_multi_thread()
+                    .worker_threads(1)
+                    .enable_all()
+                    .build()
+                    .expect("Failed to build tokio runtime")
+            })
+        }
+    )
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Replaces the name of the type being wrapped with `Self`
+struct SelfNameVisitor<'a> {
+    name: &'a str,
+}
+
+impl<'a> VisitMut for SelfNameVisitor<'a> {
+    fn visit_type_path_mut(&mut self, node: &mut syn::TypePath) {
+        if node.qself.is_none() && node.path.is_ident(self.name) {
+            node.path = syn::parse_quote!(Self);
+            return;
+        }
+        visit_mut::visit_type_path_mut(self, node);
+    }
+}
+
+fn is_self_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path.is_ident("Self"),
+        _ => false,
+    }
+}
+
+fn contains_self_type(ty: &syn::Type) -> bool {
+    struct Search(bool);
+    impl VisitMut for Search {
+        fn visit_path_mut(&mut self, node: &mut syn::Path) {
+            if node.segments.first().is_some_and(|s| s.ident == "Self") {
+                self.0 = true;
+            }
+            visit_mut::visit_path_mut(self, node);
+        }
+    }
+
+    let mut search = Search(false);
+    search.visit_type_mut(&mut ty.clone());
+    search.0
+}
+
+// `Option<Self>`, `Result<Self, E>`
+fn is_wrapped_self_type(ty: &syn::Type) -> bool {
+    if let syn::Type::Path(syn::TypePath { qself: None, path }) = ty {
+        if let Some(last) = path.segments.last() {
+            if last.ident == "Option" || last.ident == "Result" {
+                if let syn::PathArguments::AngleBracketed(args) = &last.arguments {
+                    let mut types = args.args.iter().filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    });
+                    if let Some(first) = types.next() {
+                        return is_self_type(first) && !types.any(contains_self_type);
+                    }
+                }
+            }
+        }
+    }
+
+    false
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+struct BlockingConverter<'p> {
+    params: &'p mut MacroParameters,
+    target: MacroParameters,
+    errors: Option<syn::Error>,
+}
+
+impl<'p> BlockingConverter<'p> {
+    fn self_name_set(&mut self, name: &syn::Ident, case: IdentCase) {
+        self.params.original_self_name_set(name.to_string(), case);
+        self.target.original_self_name_set(name.to_string(), case);
+    }
+
+    fn rename(&self, ident: &syn::Ident) -> syn::Ident {
+        self.params
+            .ident_rename(ident, ConvertMode::IntoBlocking)
+            .unwrap_or_else(|| ident.clone())
+    }
+
+    fn rename_target(&self, ident: &syn::Ident) -> syn::Ident {
+        self.target
+            .ident_rename(ident, ConvertMode::IntoAsync)
+            .unwrap_or_else(|| ident.clone())
+    }
+
+    fn emit_error(&mut self, err: syn::Error) {
+        match &mut self.errors {
+            Some(prev) => prev.combine(err),
+            None => self.errors = Some(err),
+        }
+    }
+
+    fn process_attrs(&mut self, attrs: &mut Vec<syn::Attribute>) {
+        let mut visitor = Visitor::new(AsyncAwaitVisitor::new(self.params, ConvertMode::IntoBlocking));
+        if let Err(err) = VisitMutExt::process_attrs(&mut visitor, attrs) {
+            self.emit_error(err);
+        }
+    }
+
+    // Visits the node as in the target version
+    fn visit_target(&mut self, visit: impl FnOnce(&mut Visitor<AsyncAwaitVisitor<'_>>)) {
+        let mut visitor = Visitor::new(AsyncAwaitVisitor::new(&mut self.target, ConvertMode::IntoAsync));
+        visit(&mut visitor);
+        if let Some(err) = visitor.take_error() {
+            self.emit_error(err);
+        }
+    }
+
+    // `Foo<T>` -> `FooAsync<T>` (as in the target version)
+    fn target_type(&mut self, ty: &syn::Type) -> syn::Type {
+        let mut ty = ty.clone();
+        self.visit_target(|visitor| visitor.visit_type_mut(&mut ty));
+        ty
+    }
+
+    // A type from `idents`, which is a wrapper in the blocking version
+    fn is_wrapped_type(&self, ty: &syn::Type) -> bool {
+        match ty {
+            syn::Type::Path(syn::TypePath { qself: None, path }) => match path.segments.last() {
+                Some(last) => self.params.ident_rename(&last.ident, ConvertMode::IntoBlocking).is_some(),
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    // `Foo<T>` -> `FooBlocking<T>`
+    fn wrapper_type(&mut self, ty: &syn::Type) -> syn::Type {
+        let mut wrapper = self.target_type(ty);
+        if let (syn::Type::Path(wrapper), syn::Type::Path(original)) = (&mut wrapper, ty) {
+            if let (Some(last), Some(original)) = (
+                wrapper.path.segments.last_mut(),
+                original.path.segments.last(),
+            ) {
+                last.ident = self.rename(&original.ident);
+            }
+        }
+        wrapper
+    }
+
+    fn convert_wrapper(
+        &mut self,
+        attrs: &mut Vec<syn::Attribute>,
+        vis: &syn::Visibility,
+        ident: &syn::Ident,
+        generics: &syn::Generics,
+    ) -> Vec<syn::Item> {
+        self.self_name_set(ident, IdentCase::Camel);
+        self.process_attrs(attrs);
+
+        let wrapper = self.rename(ident);
+        let target = self.rename_target(ident);
+
+        let mut generics = generics.clone();
+        self.visit_target(|visitor| visitor.visit_generics_mut(&mut generics));
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let inner = syn::Ident::new(INNER_NAME, Span::call_site());
+
+        let item: syn::Item = syn::parse_quote!(
+            #(#attrs)*
+            #vis struct #wrapper #impl_generics #where_clause {
+                #inner: #target #ty_generics,
+            }
+        );
+        let from: syn::Item = syn::parse_quote!(
+            impl #impl_generics ::core::convert::From<#target #ty_generics> for #wrapper #ty_generics #where_clause {
+                fn from(#inner: #target #ty_generics) -> Self {
+                    Self { #inner }
+                }
+            }
+        );
+
+        vec![item, from]
+    }
+
+    // Converts the signature and returns the arguments to pass to the async version
+    fn convert_sig(
+        &mut self,
+        sig: &mut syn::Signature,
+        self_name: Option<&str>,
+    ) -> syn::Result<(Vec<TokenStream2>, bool)> {
+        let output = match &sig.output {
+            syn::ReturnType::Type(_, ty) => search_future_output_in_type(ty),
+            syn::ReturnType::Default => None,
+        };
+        let is_async = sig.asyncness.is_some() || output.is_some();
+        sig.asyncness = None;
+        if let (Some(output), syn::ReturnType::Type(_, ty)) = (output, &mut sig.output) {
+            **ty = output;
+        }
+
+        if let Some(name) = self_name {
+            SelfNameVisitor { name }.visit_signature_mut(sig);
+        }
+
+        sig.ident = self.rename(&sig.ident);
+        self.visit_target(|visitor| visitor.visit_generics_mut(&mut sig.generics));
+
+        let inner = syn::Ident::new(INNER_NAME, Span::call_site());
+        let mut args = vec![];
+        for (i, input) in sig.inputs.iter_mut().enumerate() {
+            let pat_type = match input {
+                syn::FnArg::Receiver(_) => continue,
+                syn::FnArg::Typed(pat_type) => pat_type,
+            };
+
+            let name = match &*pat_type.pat {
+                syn::Pat::Ident(pat) if pat.subpat.is_none() && pat.by_ref.is_none() => pat.ident.clone(),
+                _ => format_ident!("{}{}", ARG_PREFIX, i),
+            };
+            *pat_type.pat = syn::parse_quote!(#name);
+
+            let arg = match &*pat_type.ty {
+                ty if is_self_type(ty) => quote!(#name.#inner),
+                syn::Type::Reference(r) if is_self_type(&r.elem) => match r.mutability {
+                    Some(_) => quote!(&mut #name.#inner),
+                    None => quote!(&#name.#inner),
+                },
+                ty if self.is_wrapped_type(ty) => {
+                    *pat_type.ty = self.wrapper_type(ty);
+                    quote!(#name.#inner)
+                }
+                syn::Type::Reference(r) if self.is_wrapped_type(&r.elem) => {
+                    let mut r = r.clone();
+                    *r.elem = self.wrapper_type(&r.elem);
+                    let arg = match r.mutability {
+                        Some(_) => quote!(&mut #name.#inner),
+                        None => quote!(&#name.#inner),
+                    };
+                    *pat_type.ty = syn::Type::Reference(r);
+                    arg
+                }
+                ty if contains_self_type(ty) => {
+                    return Err(syn::Error::new(
+                        ty.span(),
+                        "Unsupported use of `Self` in the blocking version",
+                    ))
+                }
+                ty => {
+                    *pat_type.ty = self.target_type(ty);
+                    quote!(#name)
+                }
+            };
+            args.push(arg);
+        }
+
+        if let syn::ReturnType::Type(_, ty) = &mut sig.output {
+            if !contains_self_type(ty) {
+                **ty = self.target_type(ty);
+            } else if !is_self_type(ty) && !is_wrapped_self_type(ty) {
+                return Err(syn::Error::new(
+                    ty.span(),
+                    "Unsupported use of `Self` in the blocking version",
+                ));
+            }
+        }
+
+        Ok((args, is_async))
+    }
+
+    fn make_body(&self, sig: &syn::Signature, call: TokenStream2, is_async: bool) -> syn::Block {
+        let inner = syn::Ident::new(INNER_NAME, Span::call_site());
+        let call = if is_async { block_on(self.params, call) } else { call };
+
+        match &sig.output {
+            syn::ReturnType::Type(_, ty) if is_self_type(ty) => {
+                syn::parse_quote!({ Self { #inner: #call } })
+            }
+            syn::ReturnType::Type(_, ty) if is_wrapped_self_type(ty) => {
+                syn::parse_quote!({ #call.map(|#inner| Self { #inner }) })
+            }
+            _ => syn::parse_quote!({ #call }),
+        }
+    }
+
+    fn turbofish(generics: &syn::Generics) -> TokenStream2 {
+        let params = generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                syn::GenericParam::Type(param) => Some(&param.ident),
+                syn::GenericParam::Const(param) => Some(&param.ident),
+                syn::GenericParam::Lifetime(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        if params.is_empty() {
+            quote!()
+        } else {
+            quote!(::<#(#params),*>)
+        }
+    }
+
+    fn convert_impl(&mut self, item: &mut syn::ItemImpl) -> syn::Result<()> {
+        if item.trait_.is_some() {
+            return Err(syn::Error::new(
+                item.span(),
+                "Blocking version can be generated for inherent impls only",
+            ));
+        }
+
+        let self_name = match &*item.self_ty {
+            syn::Type::Path(syn::TypePath { path, .. }) => match path.segments.last() {
+                Some(last) => last.ident.clone(),
+                None => return Err(syn::Error::new(item.self_ty.span(), "Expected type")),
+            },
+            _ => return Err(syn::Error::new(item.self_ty.span(), "Expected type")),
+        };
+        self.self_name_set(&self_name, IdentCase::Camel);
+        self.process_attrs(&mut item.attrs);
+
+        let target = self.target_type(&item.self_ty);
+        *item.self_ty = self.wrapper_type(&item.self_ty);
+        self.visit_target(|visitor| visitor.visit_generics_mut(&mut item.generics));
+
+        let self_name = self_name.to_string();
+        let inner = syn::Ident::new(INNER_NAME, Span::call_site());
+
+        for impl_item in &mut item.items {
+            match impl_item {
+                syn::ImplItem::Method(method) => {
+                    self.process_attrs(&mut method.attrs);
+
+                    let target_ident = self.rename_target(&method.sig.ident);
+                    let has_receiver = method.sig.receiver().is_some();
+                    let (args, is_async) = self.convert_sig(&mut method.sig, Some(&self_name))?;
+                    let turbofish = Self::turbofish(&method.sig.generics);
+
+                    let call = if has_receiver {
+                        quote!(self.#inner.#target_ident #turbofish (#(#args),*))
+                    } else {
+                        quote!(<#target>::#target_ident #turbofish (#(#args),*))
+                    };
+
+                    method.block = self.make_body(&method.sig, call, is_async);
+                }
+                syn::ImplItem::Const(item_const) => {
+                    self.process_attrs(&mut item_const.attrs);
+
+                    let ident = &item_const.ident;
+                    item_const.ty = self.target_type(&item_const.ty);
+                    item_const.expr = syn::parse_quote!(<#target>::#ident);
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        impl_item.span(),
+                        "Only methods and constants are supported in the blocking version",
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn convert_fn(&mut self, item: &mut syn::ItemFn) -> syn::Result<()> {
+        self.self_name_set(&item.sig.ident, IdentCase::Snake);
+        self.process_attrs(&mut item.attrs);
+
+        let target_ident = self.rename_target(&item.sig.ident);
+        let (args, is_async) = self.convert_sig(&mut item.sig, None)?;
+        let turbofish = Self::turbofish(&item.sig.generics);
+
+        let call = quote!(#target_ident #turbofish (#(#args),*));
+        *item.block = self.make_body(&item.sig, call, is_async);
+
+        Ok(())
+    }
+}
+
+// Generates the blocking version of the items: a struct (or enum) becomes a wrapper around its
+// async version, and methods and functions delegate to the async version, blocking on the
+// returned futures.
+pub fn convert(params: &mut MacroParameters, file: &mut syn::File) -> syn::Result<()> {
+    let target = params.blocking_target();
+    let mut converter = BlockingConverter { params, target, errors: None };
+
+    let mut items = vec![];
+    for item in file.items.drain(..) {
+        let res = match item {
+            syn::Item::Struct(mut item) => {
+                items.extend(converter.convert_wrapper(&mut item.attrs, &item.vis, &item.ident, &item.generics));
+                Ok(())
+            }
+            syn::Item::Enum(mut item) => {
+                items.extend(converter.convert_wrapper(&mut item.attrs, &item.vis, &item.ident, &item.generics));
+                Ok(())
+            }
+            syn::Item::Impl(mut item) => converter.convert_impl(&mut item).map(|_| items.push(syn::Item::Impl(item))),
+            syn::Item::Fn(mut item) => converter.convert_fn(&mut item).map(|_| items.push(syn::Item::Fn(item))),
+            item => Err(syn::Error::new(
+                item.span(),
+                "Blocking version can be generated for struct, enum, impl and fn items only",
+            )),
+        };
+
+        res?;
+    }
+
+    file.items = items;
+
+    match converter.errors {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
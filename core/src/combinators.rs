@@ -0,0 +1,493 @@
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream, Parser},
+    punctuated::Punctuated,
+    token::Comma,
+    visit_mut::VisitMut,
+};
+
+const VALUE_NAME: &str = "__maybe_async_cfg_value";
+const SYNC_ARM_MARKER: &str = "sync";
+
+// The functions of `futures::future` which are converted
+const FUTURES_FNS: &[&str] = &[
+    "join_all", "try_join_all", "join", "join3", "join4", "join5", "try_join", "try_join3",
+    "try_join4", "try_join5",
+];
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// `name`, `tokio::name`, `futures::name`
+fn is_runtime_macro(path: &syn::Path, names: &[&str]) -> bool {
+    let last = match path.segments.last() {
+        Some(last) => last,
+        None => return false,
+    };
+    if !names.iter().any(|name| last.ident == name) {
+        return false;
+    }
+
+    match path.segments.len() {
+        1 => true,
+        2 => {
+            let first = &path.segments[0].ident;
+            first == "tokio" || first == "futures"
+        }
+        _ => false,
+    }
+}
+
+pub fn is_select_macro(path: &syn::Path) -> bool {
+    is_runtime_macro(path, &["select", "select_biased"])
+}
+
+pub fn is_join_macro(path: &syn::Path) -> bool {
+    is_runtime_macro(path, &["join", "try_join"])
+}
+
+fn is_combinator_macro(path: &syn::Path) -> bool {
+    is_join_macro(path) || is_select_macro(path)
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// (a, b, c)
+fn make_tuple(exprs: Vec<syn::Expr>) -> syn::Expr {
+    syn::parse_quote!( (#(#exprs,)*) )
+}
+
+// Evaluates the results one by one and stops at the first error:
+//
+// ```ignore
+// match a {
+//     Ok(__maybe_async_cfg_value0) => match b {
+//         Ok(__maybe_async_cfg_value1) => Ok((__maybe_async_cfg_value0, __maybe_async_cfg_value1)),
+//         Err(err) => Err(err),
+//     },
+//     Err(err) => Err(err),
+// }
+// ```
+fn make_try_tuple(exprs: Vec<syn::Expr>) -> syn::Expr {
+    let names: Vec<syn::Ident> = (0..exprs.len())
+        .map(|i| syn::Ident::new(&format!("{}{}", VALUE_NAME, i), Span::call_site()))
+        .collect();
+
+    let mut result: syn::Expr = syn::parse_quote!( ::core::result::Result::Ok( (#(#names,)*) ) );
+    for (expr, name) in exprs.into_iter().zip(names).rev() {
+        result = syn::parse_quote!(
+            match #expr {
+                ::core::result::Result::Ok(#name) => #result,
+                ::core::result::Result::Err(err) => ::core::result::Result::Err(err),
+            }
+        );
+    }
+
+    result
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+struct SelectArm {
+    attrs: Vec<syn::Attribute>,
+    sync: bool,
+    // `else`, `complete` or `default`
+    keyword: Option<TokenTree>,
+    binding: Option<(syn::Pat, syn::Expr)>,
+    precondition: Option<syn::Expr>,
+    body: syn::Expr,
+}
+
+struct SelectArms {
+    biased: bool,
+    arms: Vec<SelectArm>,
+}
+
+fn is_fallback_arm(input: ParseStream) -> bool {
+    let fork = input.fork();
+    let is_keyword = if fork.peek(syn::Token![else]) {
+        fork.parse::<syn::Token![else]>().is_ok()
+    } else {
+        match fork.parse::<syn::Ident>() {
+            Ok(ident) => ident == "complete" || ident == "default",
+            Err(_) => false,
+        }
+    };
+    is_keyword && fork.peek(syn::Token![=>])
+}
+
+impl Parse for SelectArms {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // `biased;`
+        let mut biased = false;
+        let fork = input.fork();
+        if let Ok(ident) = fork.parse::<syn::Ident>() {
+            if ident == "biased" && fork.peek(syn::Token![;]) {
+                input.parse::<syn::Ident>()?;
+                input.parse::<syn::Token![;]>()?;
+                biased = true;
+            }
+        }
+
+        let mut arms = vec![];
+        while !input.is_empty() {
+            let mut attrs = input.call(syn::Attribute::parse_outer)?;
+            let len = attrs.len();
+            attrs.retain(|attr| !attr.path.is_ident(SYNC_ARM_MARKER));
+            let sync = attrs.len() != len;
+
+            let mut keyword = None;
+            let mut binding = None;
+            let mut precondition = None;
+            if is_fallback_arm(input) {
+                keyword = Some(input.parse::<TokenTree>()?);
+            } else {
+                let pat: syn::Pat = input.parse()?;
+                input.parse::<syn::Token![=]>()?;
+                let fut: syn::Expr = input.parse()?;
+                // `, if cond`
+                if input.peek(syn::Token![,]) && input.peek2(syn::Token![if]) {
+                    input.parse::<syn::Token![,]>()?;
+                    input.parse::<syn::Token![if]>()?;
+                    precondition = Some(input.parse::<syn::Expr>()?);
+                }
+                binding = Some((pat, fut));
+            };
+
+            input.parse::<syn::Token![=>]>()?;
+            let body: syn::Expr = input.parse()?;
+            if !input.is_empty() {
+                match &body {
+                    syn::Expr::Block(_) => {
+                        input.parse::<Option<syn::Token![,]>>()?;
+                    }
+                    _ => {
+                        input.parse::<syn::Token![,]>()?;
+                    }
+                }
+            }
+
+            arms.push(SelectArm {
+                attrs,
+                sync,
+                keyword,
+                binding,
+                precondition,
+                body,
+            });
+        }
+
+        Ok(Self { biased, arms })
+    }
+}
+
+impl ToTokens for SelectArms {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        if self.biased {
+            tokens.extend(quote!(biased;));
+        }
+
+        for arm in &self.arms {
+            let attrs = &arm.attrs;
+            let body = &arm.body;
+            match (&arm.keyword, &arm.binding) {
+                (Some(keyword), _) => tokens.extend(quote!(#(#attrs)* #keyword => #body,)),
+                (None, Some((pat, fut))) => {
+                    let precondition = arm.precondition.as_ref().map(|cond| quote!(, if #cond));
+                    tokens.extend(quote!(#(#attrs)* #pat = #fut #precondition => #body,));
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+    }
+}
+
+// Whether the pattern always matches. Only the patterns that are irrefutable by their syntax are
+// recognized: `Foo { a, b }` can be a struct or an enum variant, so it is considered refutable.
+fn is_irrefutable(pat: &syn::Pat) -> bool {
+    match pat {
+        // `None` is parsed as an ident too
+        syn::Pat::Ident(pat) if pat.ident.to_string().starts_with(char::is_uppercase) => false,
+        syn::Pat::Ident(pat) => match &pat.subpat {
+            Some((_, subpat)) => is_irrefutable(subpat),
+            None => true,
+        },
+        syn::Pat::Wild(_) | syn::Pat::Rest(_) => true,
+        syn::Pat::Tuple(pat) => pat.elems.iter().all(is_irrefutable),
+        syn::Pat::Reference(pat) => is_irrefutable(&pat.pat),
+        syn::Pat::Box(pat) => is_irrefutable(&pat.pat),
+        syn::Pat::Type(pat) => is_irrefutable(&pat.pat),
+        _ => false,
+    }
+}
+
+// Only the arm marked with `#[sync]` is kept in the sync version:
+//
+// `#[sync] v = rx.recv() => body` -> `match rx.recv() { v => body }`
+//
+// `#[sync] else => body` -> `body`
+//
+// If the pattern of the marked arm can fail to match, the `else` arm is used in this case:
+//
+// `#[sync] Some(v) = rx.recv() => body, else => other` -> `match rx.recv() { Some(v) => body, _ => other }`
+fn convert_select_macro(mac: &syn::Macro) -> syn::Result<Option<syn::Expr>> {
+    let SelectArms { arms, .. } = match parse_select_arms(mac)? {
+        Some(select) => select,
+        None => return Ok(None),
+    };
+
+    let fallback = arms
+        .iter()
+        .find(|arm| matches!(&arm.keyword, Some(TokenTree::Ident(ident)) if ident == "else"))
+        .map(|arm| arm.body.clone());
+
+    let mut marked = arms.into_iter().filter(|arm| arm.sync);
+    let arm = match (marked.next(), marked.next()) {
+        (Some(arm), None) => arm,
+        (None, _) => {
+            return Err(syn::Error::new_spanned(
+                &mac.path,
+                "`select!` can not be converted into sync code: mark the arm to be used in the \
+                sync version with `#[sync]`",
+            ))
+        }
+        (Some(_), Some(_)) => {
+            return Err(syn::Error::new_spanned(
+                &mac.path,
+                "Only one arm of `select!` can be marked with `#[sync]`",
+            ))
+        }
+    };
+
+    let body = arm.body;
+    Ok(Some(match (arm.binding, fallback) {
+        (Some((pat, fut)), _) if is_irrefutable(&pat) => syn::parse_quote!(
+            match #fut {
+                #pat => #body,
+            }
+        ),
+        (Some((pat, fut)), Some(fallback)) => syn::parse_quote!(
+            match #fut {
+                #pat => #body,
+                #[allow(unreachable_patterns)]
+                _ => #fallback,
+            }
+        ),
+        (Some((pat, _)), None) => {
+            return Err(syn::Error::new_spanned(
+                pat,
+                "The pattern of the `#[sync]` arm of `select!` may not match: add an `else` arm \
+                to be used in this case in the sync version",
+            ))
+        }
+        (None, _) => syn::parse_quote!({ #body }),
+    }))
+}
+
+// A bare `select!` may be a macro of the user which is not ours to parse, so its tokens are left
+// intact if they are not the arms of `select!`
+fn parse_select_arms(mac: &syn::Macro) -> syn::Result<Option<SelectArms>> {
+    match syn::parse2(mac.tokens.clone()) {
+        Ok(select) => Ok(Some(select)),
+        Err(_) if mac.path.segments.len() == 1 => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+// Visits the expressions of `select!` arms in the async version; `#[sync]` markers are removed
+pub fn visit_select_macro(mac: &mut syn::Macro, mut visit: impl FnMut(&mut syn::Expr)) -> syn::Result<()> {
+    let mut select = match parse_select_arms(mac)? {
+        Some(select) => select,
+        None => return Ok(()),
+    };
+
+    for arm in &mut select.arms {
+        if let Some((_, fut)) = &mut arm.binding {
+            visit(fut);
+        }
+        if let Some(precondition) = &mut arm.precondition {
+            visit(precondition);
+        }
+        visit(&mut arm.body);
+    }
+
+    mac.tokens = select.into_token_stream();
+
+    Ok(())
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// `join!(a, b)` -> `(a, b)`, `try_join!(a, b)` -> `Ok((a?, b?))` (evaluated sequentially),
+// `select!` -> the arm marked with `#[sync]`
+fn convert_combinator_macro(mac: &syn::Macro) -> syn::Result<Option<syn::Expr>> {
+    if is_select_macro(&mac.path) {
+        return convert_select_macro(mac);
+    }
+
+    let try_join = if is_runtime_macro(&mac.path, &["join"]) {
+        false
+    } else if is_runtime_macro(&mac.path, &["try_join"]) {
+        true
+    } else {
+        return Ok(None);
+    };
+
+    let args = Punctuated::<syn::Expr, Comma>::parse_terminated.parse2(mac.tokens.clone())?;
+    let exprs = args.into_iter().collect();
+
+    Ok(Some(if try_join {
+        make_try_tuple(exprs)
+    } else {
+        make_tuple(exprs)
+    }))
+}
+
+// `futures::future::name`, `futures_util::future::name`, `future::name` (with `future` imported
+// from `futures`) or just `name` if it is imported from `futures::future` (maybe renamed)
+fn futures_fn_name(path: &syn::Path, imported: &[(String, String)]) -> Option<(String, bool)> {
+    let names: Vec<String> = path.segments.iter().map(|segment| segment.ident.to_string()).collect();
+    match names.as_slice() {
+        [name] if path.leading_colon.is_none() => imported
+            .iter()
+            .find(|(local, _)| local == name)
+            .map(|(_, name)| (name.clone(), false)),
+        [module, name] if module == "future" && path.leading_colon.is_none() => Some((name.clone(), true)),
+        [krate, module, name] if (krate == "futures" || krate == "futures_util") && module == "future" => {
+            Some((name.clone(), true))
+        }
+        _ => None,
+    }
+}
+
+// `join_all(iter)` -> `iter.into_iter().collect::<Vec<_>>()`,
+// `try_join_all(iter)` -> `iter.into_iter().collect::<Result<Vec<_>, _>>()`,
+// `future::join(a, b)` -> `(a, b)`, `future::try_join(a, b)` -> `Ok((a?, b?))`, etc.
+fn convert_combinator_call(call: &syn::ExprCall, imported: &[(String, String)]) -> Option<syn::Expr> {
+    let path = match &*call.func {
+        syn::Expr::Path(syn::ExprPath { qself: None, path, .. }) => path,
+        _ => return None,
+    };
+
+    let (name, in_future_mod) = futures_fn_name(path, imported)?;
+    let args = &call.args;
+
+    match name.as_str() {
+        "join_all" if args.len() == 1 => Some(syn::parse_quote!(
+            ::core::iter::IntoIterator::into_iter(#args).collect::<::std::vec::Vec<_>>()
+        )),
+        "try_join_all" if args.len() == 1 => Some(syn::parse_quote!(
+            ::core::iter::IntoIterator::into_iter(#args)
+                .collect::<::core::result::Result<::std::vec::Vec<_>, _>>()
+        )),
+        "join" | "join3" | "join4" | "join5" if in_future_mod => {
+            Some(make_tuple(args.iter().cloned().collect()))
+        }
+        "try_join" | "try_join3" | "try_join4" | "try_join5" if in_future_mod => {
+            Some(make_try_tuple(args.iter().cloned().collect()))
+        }
+        _ => None,
+    }
+}
+
+pub fn convert_combinators(expr: &mut syn::Expr, imported: &[(String, String)]) -> syn::Result<()> {
+    let new_expr = match expr {
+        syn::Expr::Macro(expr) => convert_combinator_macro(&expr.mac)?,
+        syn::Expr::Call(call) => convert_combinator_call(call, imported),
+        _ => None,
+    };
+
+    if let Some(new_expr) = new_expr {
+        *expr = new_expr;
+    }
+
+    Ok(())
+}
+
+// `tokio::select! { ... }` and `tokio::join!(...);` in statement position are turned into
+// expressions, so they will be converted by the expression visitor.
+pub fn convert_combinator_stmts(block: &mut syn::Block) {
+    let mut stmts = Vec::with_capacity(block.stmts.len());
+
+    for stmt in block.stmts.drain(..) {
+        match stmt {
+            syn::Stmt::Item(syn::Item::Macro(item))
+                if item.ident.is_none() && is_combinator_macro(&item.mac.path) =>
+            {
+                let expr = syn::Expr::Macro(syn::ExprMacro {
+                    attrs: item.attrs,
+                    mac: item.mac,
+                });
+                stmts.push(match item.semi_token {
+                    Some(semi) => syn::Stmt::Semi(expr, semi),
+                    None => syn::Stmt::Expr(expr),
+                });
+            }
+            stmt => stmts.push(stmt),
+        }
+    }
+
+    block.stmts = stmts;
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// The functions imported from `futures::future` (or `futures_util::future`), with their local
+// names. Only these bare names are converted, so the own functions of the user named `join_all`
+// etc. are left intact
+pub fn imported_futures_fns(file: &mut syn::File) -> Vec<(String, String)> {
+    let mut imports = FuturesImports { names: vec![] };
+    imports.visit_file_mut(file);
+    imports.names
+}
+
+struct FuturesImports {
+    // (local name, function name)
+    names: Vec<(String, String)>,
+}
+
+impl FuturesImports {
+    fn add(&mut self, local: String, name: String) {
+        if FUTURES_FNS.contains(&name.as_str()) && !self.names.iter().any(|(l, _)| *l == local) {
+            self.names.push((local, name));
+        }
+    }
+
+    // `use futures::future::{join_all, try_join_all as all, *};`
+    fn collect(&mut self, tree: &syn::UseTree, prefix: &mut Vec<String>) {
+        let is_future_mod = |prefix: &[String]| {
+            matches!(prefix, [krate, module] if (krate == "futures" || krate == "futures_util") && module == "future")
+        };
+
+        match tree {
+            syn::UseTree::Path(path) => {
+                prefix.push(path.ident.to_string());
+                self.collect(&path.tree, prefix);
+                prefix.pop();
+            }
+            syn::UseTree::Name(name) if is_future_mod(prefix) => {
+                self.add(name.ident.to_string(), name.ident.to_string());
+            }
+            syn::UseTree::Rename(rename) if is_future_mod(prefix) => {
+                self.add(rename.rename.to_string(), rename.ident.to_string());
+            }
+            syn::UseTree::Glob(_) if is_future_mod(prefix) => {
+                for name in FUTURES_FNS {
+                    self.add(name.to_string(), name.to_string());
+                }
+            }
+            syn::UseTree::Group(group) => {
+                for item in &group.items {
+                    self.collect(item, prefix);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl VisitMut for FuturesImports {
+    fn visit_item_use_mut(&mut self, node: &mut syn::ItemUse) {
+        self.collect(&node.tree, &mut vec![]);
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTime,
+};
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse::Parser, punctuated::Punctuated, token::Comma, NestedMeta};
+
+use crate::{CONFIG_ENV_NAME, CONFIG_FILE_NAME};
+
+// A parsed config file with its modification time
+type CachedConfig = (Option<SystemTime>, Rc<toml::Value>);
+
+thread_local! {
+    // profiles being loaded right now, to detect `profile = "..."` cycles
+    static LOADING: RefCell<Vec<String>> = const { RefCell::new(vec![]) };
+    // every `maybe` invocation using a profile needs the file, so it is parsed once
+    static CONFIGS: RefCell<HashMap<PathBuf, CachedConfig>> = RefCell::new(HashMap::new());
+}
+
+fn is_workspace_root(dir: &Path) -> bool {
+    std::fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|manifest| manifest.parse::<toml::Value>().ok())
+        .map(|manifest| manifest.get("workspace").is_some())
+        .unwrap_or(false)
+}
+
+// `$MAYBE_ASYNC_CFG_CONFIG` (relative to the crate root), or the nearest `maybe-async-cfg.toml`
+// in the crate root or one of its parents up to the workspace root (so the workspace root config
+// is shared by its members)
+fn config_path() -> Option<PathBuf> {
+    let root = std::env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from)?;
+
+    if let Some(path) = std::env::var_os(CONFIG_ENV_NAME) {
+        return Some(root.join(path));
+    }
+
+    // the parents are searched only if the crate is a member of a workspace
+    let workspace = root.ancestors().find(|dir| is_workspace_root(dir)).unwrap_or(&root);
+
+    root.ancestors()
+        .take_while(|dir| dir.starts_with(workspace))
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+fn read_config(path: &Path) -> syn::Result<Rc<toml::Value>> {
+    let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    let cached = CONFIGS.with(|configs| {
+        configs
+            .borrow()
+            .get(path)
+            .filter(|(time, _)| modified.is_some() && *time == modified)
+            .map(|(_, config)| config.clone())
+    });
+    if let Some(config) = cached {
+        return Ok(config);
+    }
+
+    let content = std::fs::read_to_string(path).map_err(|err| {
+        syn::Error::new(
+            Span::call_site(),
+            format!("Can not read `{}`: {}", path.display(), err),
+        )
+    })?;
+
+    let config = content.parse::<toml::Value>().map_err(|err| {
+        syn::Error::new(
+            Span::call_site(),
+            format!("Can not parse `{}`: {}", path.display(), err),
+        )
+    })?;
+
+    let config = Rc::new(config);
+    CONFIGS.with(|configs| configs.borrow_mut().insert(path.to_path_buf(), (modified, config.clone())));
+    Ok(config)
+}
+
+// Profiles are the strings with `maybe` parameters:
+//
+// ```toml
+// [profiles]
+// client = 'idents(Client, send(fn)), sync(feature = "use_sync"), async(feature = "use_async")'
+// ```
+fn profile_args(name: &str, span: Span) -> syn::Result<(Punctuated<NestedMeta, Comma>, PathBuf)> {
+    let path = config_path().ok_or_else(|| {
+        syn::Error::new(
+            span,
+            format!("Profile `{}` requested, but `{}` is not found", name, CONFIG_FILE_NAME),
+        )
+    })?;
+    let config = read_config(&path)?;
+
+    let profile = config
+        .get("profiles")
+        .and_then(|profiles| profiles.get(name))
+        .ok_or_else(|| {
+            syn::Error::new(
+                span,
+                format!("Profile `{}` is not found in `{}`", name, path.display()),
+            )
+        })?;
+
+    let args = profile.as_str().ok_or_else(|| {
+        syn::Error::new(
+            span,
+            format!("Profile `{}` in `{}` must be a string", name, path.display()),
+        )
+    })?;
+
+    let tokens = args.parse::<proc_macro2::TokenStream>().map_err(|err| {
+        syn::Error::new(span, format!("Profile `{}`: {}", name, err))
+    })?;
+
+    let args = Punctuated::<NestedMeta, Comma>::parse_terminated
+        .parse2(tokens)
+        .map_err(|err| syn::Error::new(span, format!("Profile `{}`: {}", name, err)))?;
+
+    Ok((args, path))
+}
+
+// Returns the parsed profile and the path of the config file it was loaded from
+pub fn load_profile<T>(
+    name: &str,
+    span: Span,
+    parse: impl FnOnce(&Punctuated<NestedMeta, Comma>) -> syn::Result<T>,
+) -> syn::Result<(T, PathBuf)> {
+    let is_loading = LOADING.with(|loading| loading.borrow().iter().any(|n| n == name));
+    if is_loading {
+        return Err(syn::Error::new(
+            span,
+            format!("Profile `{}` refers to itself", name),
+        ));
+    }
+
+    let (args, path) = profile_args(name, span)?;
+
+    LOADING.with(|loading| loading.borrow_mut().push(name.to_string()));
+    let result = parse(&args);
+    LOADING.with(|loading| loading.borrow_mut().pop());
+
+    Ok((result?, path))
+}
+
+// Cargo does not know that the expansion depends on the config file; including its bytes makes
+// the file a dependency of the crate, so editing it triggers a rebuild
+pub fn track_file(path: &Path) -> TokenStream2 {
+    let path = path.to_string_lossy();
+    quote!(const _: &[u8] = include_bytes!(#path);)
+}
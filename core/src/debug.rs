@@ -1,14 +1,13 @@
 #[cfg(feature="debug")]
 pub mod inner {
 
-    use proc_macro::TokenStream;
     use proc_macro2::TokenStream as TokenStream2;
     
     use crate::params::MacroParameters;
     
     ////////////////////////////////////////////////////////////////////////////////////////////////
     
-    pub fn dump_maybe(args: &TokenStream, input: &TokenStream) {
+    pub fn dump_maybe(args: &TokenStream2, input: &TokenStream2) {
         println!("--vvv------------------------------------------");
         println!("maybe:");
         println!("args:");
@@ -19,7 +18,7 @@ pub mod inner {
         println!("");
     }
 
-    pub fn dump_tokens(name: &str, ts: &TokenStream) {
+    pub fn dump_tokens(name: &str, ts: &TokenStream2) {
         println!("--vvv------------------------------------------");
         println!("{}:", name);
         println!("{}", ts);
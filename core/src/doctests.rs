@@ -0,0 +1,1214 @@
+use std::ops::Range;
+
+use proc_macro2::{Delimiter, Spacing, TokenStream as TokenStream2, TokenTree};
+use pulldown_cmark::{BrokenLink, CodeBlockKind, CowStr, Event, LinkType, Options, Parser, Tag};
+use quote::quote;
+use syn::visit_mut::{self, VisitMut};
+
+use crate::paths::path_to_string;
+
+fn as_lang_tokens(string: &str) -> impl Iterator<Item = &str> {
+    // Pandoc, which Rust once used for generating documentation,
+    // expects lang strings to be surrounded by `{}` and for each token
+    // to be proceeded by a `.`. Since some of these lang strings are still
+    // loose in the wild, we strip a pair of surrounding `{}` from the lang
+    // string and a leading `.` from each token.
+
+    let string = string.trim();
+
+    let first = string.chars().next();
+    let last = string.chars().last();
+
+    let string = if first == Some('{') && last == Some('}') {
+        &string[1..string.len() - 1]
+    } else {
+        string
+    };
+
+    split_outside_parens(string)
+        .into_iter()
+        .map(|token| token.strip_prefix('.').unwrap_or(token))
+}
+
+// Splits by commas and whitespaces, except the ones inside parentheses: `only_if(sync, blocking)`
+// is a single token
+fn split_outside_parens(string: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (i, c) in string.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' | ' ' | '\t' if depth == 0 => {
+                tokens.push(string[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    tokens.push(string[start..].trim());
+
+    tokens.retain(|token| !token.is_empty());
+    tokens
+}
+
+// `name(a, b)` -> ["a", "b"]
+fn our_token_args(token: &str, name: &str) -> Option<Vec<String>> {
+    let args = token.strip_prefix(name)?.strip_prefix('(')?.strip_suffix(')')?;
+    Some(
+        split_outside_parens(args)
+            .into_iter()
+            .map(|arg| arg.trim_matches('"').to_string())
+            .collect(),
+    )
+}
+
+// Tokens which do not prevent a code block from being a rust doctest
+fn is_rustdoc_token(token: &str) -> bool {
+    matches!(
+        token,
+        "rust" | "ignore" | "should_panic" | "no_run" | "compile_fail" | "test_harness" | "standalone_crate"
+    ) || token.starts_with("edition")
+        || token.starts_with("ignore-")
+}
+
+// The lang string of a fenced code block without our tokens
+struct Lang {
+    // `only_if(key, ...)` and `remove_if(key, ...)`: the keys and whether the block is removed in
+    // these versions
+    conditions: Vec<(Vec<String>, bool)>,
+    // `flags_if(key, flag, ...)`
+    flags: Vec<(String, Vec<String>)>,
+    new_lang: String,
+    rust: bool,
+}
+
+fn parse_lang(lang: &str) -> Lang {
+    let mut conditions = vec![];
+    let mut flags = vec![];
+    let mut tokens: Vec<&str> = vec![];
+
+    for token in as_lang_tokens(lang) {
+        if let Some(keys) = our_token_args(token, "only_if") {
+            conditions.push((keys, false));
+        } else if let Some(keys) = our_token_args(token, "remove_if") {
+            conditions.push((keys, true));
+        } else if let Some(mut args) = our_token_args(token, "flags_if") {
+            if !args.is_empty() {
+                let key = args.remove(0);
+                flags.push((key, args));
+            }
+        } else {
+            tokens.push(token);
+        }
+    }
+
+    let rust = tokens.iter().all(|token| is_rustdoc_token(token)) || tokens.contains(&"rust");
+
+    Lang { conditions, flags, new_lang: tokens.join(", "), rust }
+}
+
+fn paste_code(new_lang: &str, code: &str, indent: Option<&str>) -> String {
+    let mut res = String::new();
+
+    res.push_str("\n``` ");
+    res.push_str(new_lang);
+    res.push_str("\n");
+    res.push_str(code);
+    res.push_str("```");
+
+    if let Some(indent) = indent {
+        let mut first = true;
+        res = res.lines().map(|s| {
+            if first {
+                first = false;
+                return s.to_string()
+            };
+
+            let mut res = String::new();
+            res.push('\n');
+            res.push_str(indent);
+            res.push_str(s);
+
+            res
+        }).collect();        
+    }
+
+    res
+}
+
+fn get_indent_from_content(content: &str) -> Option<String> {
+    let content = content.as_bytes();
+    let mut len = content.len();
+
+    if !(content[len-1] == b'`' && content[len-2] == b'`' && content[len-3] == b'`') {
+        return None;
+    }
+
+    len -= 3;
+
+    let mut rres: Vec<u8> = vec![];
+
+    while len >= 1 {
+        let b = content[len-1];
+
+        if b.is_ascii_whitespace() && !(b == b'\n' || b == b'\r') {
+            rres.push(b);
+            len -= 1;
+        } else {
+            break
+        }
+    }
+
+    let res: String = rres.into_iter().rev().map(|b| b as char).collect();
+    
+    Some(res)
+}
+
+// A fenced code block passed to the processor of `process_doctests`
+pub struct CodeBlock<'a> {
+    lang: &'a Lang,
+    pub code: &'a str,
+}
+
+impl<'a> CodeBlock<'a> {
+    // The block is a doctest
+    pub fn is_rust(&self) -> bool {
+        self.lang.rust
+    }
+
+    // The block may be not valid Rust code: rustdoc does not compile it, or expects it to fail
+    pub fn may_be_invalid(&self, key: Option<&str>) -> bool {
+        self.lang_get(key)
+            .split(',')
+            .map(str::trim)
+            .any(|token| token == "ignore" || token.starts_with("ignore-") || token == "compile_fail")
+    }
+
+    // The block has `only_if`, `remove_if` or `flags_if`
+    pub fn has_conditions(&self) -> bool {
+        !self.lang.conditions.is_empty() || !self.lang.flags.is_empty()
+    }
+
+    pub fn is_kept(&self, key: &str) -> bool {
+        self.lang
+            .conditions
+            .iter()
+            .all(|(keys, remove)| keys.iter().any(|k| k == key) != *remove)
+    }
+
+    // The lang string for the version with the key: without our tokens, but with the flags of
+    // this version
+    pub fn lang_get(&self, key: Option<&str>) -> String {
+        let mut tokens = vec![self.lang.new_lang.clone()];
+        for (flags_key, flags) in &self.lang.flags {
+            if Some(flags_key.as_str()) == key {
+                tokens.extend(flags.iter().cloned());
+            }
+        }
+
+        tokens.retain(|token| !token.is_empty());
+        tokens.join(", ")
+    }
+}
+
+// The processor returns `Some(Some((lang, code)))` to replace the block, `Some(None)` to remove it
+// and `None` to keep it unchanged
+pub fn process_doctests(doc: &str, processor: impl Fn(&CodeBlock) -> Option<Option<(String, String)>>) -> Option<String> {
+    let parser = Parser::new(doc);
+
+    let mut prev_offset = 0usize;
+    let mut level = 0usize;
+    let mut block_lang = None;
+    let mut inside_code = false;
+    let mut code = String::new();
+    let mut has_changes: bool = false;
+
+    let mut new_doc = String::new();
+
+    for (event, ref offset) in parser.into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(ref kind)) => {
+                level += 1;
+
+                if level == 1 {
+                    match match kind {
+                        CodeBlockKind::Fenced(ref lang) => {
+                            Some(parse_lang(lang))
+                        },
+                        CodeBlockKind::Indented => {
+                            None
+                        }
+                    } {
+                        Some(lang) => {
+                            let mut new_start = offset.start;
+                            let mut success = false;
+                            while prev_offset < new_start {
+                                let b = doc.as_bytes()[new_start-1];
+                                if !b.is_ascii_whitespace() {
+                                    break;
+                                }
+
+                                if b == b'\n' {
+                                    new_start -= 1;
+                                    success = true;
+                                    break;
+                                }
+                                new_start -= 1;
+                            }
+                            if !success {
+                                new_start = offset.start;
+                            }
+
+                            new_doc.push_str(&doc[prev_offset..new_start]);
+                            prev_offset = new_start;
+        
+                            block_lang = Some(lang);
+                            code.clear();
+                            inside_code = true;
+                        },
+                        None => {},
+                    };
+                }
+            },
+            Event::End(Tag::CodeBlock(_)) => {
+                if let (1, true, Some(lang)) = (level, inside_code, &block_lang) {
+                    let content = &doc[prev_offset..offset.end];
+                    prev_offset = offset.end;
+
+                    let block = CodeBlock {
+                        lang,
+                        code: code.as_str(),
+                    };
+
+                    match processor(&block) {
+                        Some(Some((new_lang, new_code))) => {
+                            let indent = get_indent_from_content(content);
+                            let new_code = paste_code(new_lang.as_str(), new_code.as_str(), indent.as_ref().map(|s| s.as_str()));
+                            new_doc.push_str(new_code.as_str());
+                            has_changes = true;
+                        },
+                        Some(None) => {
+                            has_changes = true;
+                        },
+                        None => {
+                            new_doc.push_str(content);
+                        }
+                    }
+
+                    inside_code = false;
+                };
+                level -= 1;
+            },
+            Event::Text(ref text) if inside_code => {
+                code.push_str(&text);
+            },
+            _ => {}
+        }
+    };
+
+    if has_changes {
+        if prev_offset < doc.len() {
+            new_doc.push_str(&doc[prev_offset..doc.len()]);
+        }
+
+        Some(new_doc)
+    } else {
+        None
+    }
+}
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CodeKind {
+    Block,
+    Inline,
+}
+
+// The code blocks and the inline code of the markdown, sorted by position
+fn code_ranges(doc: &str) -> Vec<(Range<usize>, CodeKind)> {
+    let mut ranges = vec![];
+    let mut level = 0usize;
+
+    for (event, range) in Parser::new(doc).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => {
+                if level == 0 {
+                    ranges.push((range, CodeKind::Block));
+                }
+                level += 1;
+            }
+            Event::End(Tag::CodeBlock(_)) => level -= 1,
+            Event::Code(_) => ranges.push((range, CodeKind::Inline)),
+            _ => {}
+        }
+    }
+
+    ranges
+}
+
+// Searches for the pattern starting at `from`, skipping the code
+fn find_outside_code(doc: &str, mut from: usize, pattern: &str, ranges: &[(Range<usize>, CodeKind)]) -> Option<usize> {
+    loop {
+        let found = from + doc[from..].find(pattern)?;
+        match ranges.iter().find(|(range, _)| range.contains(&found)) {
+            Some((range, _)) => from = range.end,
+            None => return Some(found),
+        }
+    }
+}
+
+// `{{sync: ... | async, blocking: ... | _: ...}}`: the alternative for the version with the key
+// (`_` is the default one), or `None` if this is not a list of alternatives
+fn choose_alternative<'d>(
+    doc: &'d str,
+    inner: Range<usize>,
+    key: &str,
+    ranges: &[(Range<usize>, CodeKind)],
+) -> Option<&'d str> {
+    let mut alternatives = vec![];
+    let mut start = inner.start;
+    while let Some(bar) = find_outside_code(&doc[..inner.end], start, "|", ranges) {
+        alternatives.push(&doc[start..bar]);
+        start = bar + 1;
+    }
+    alternatives.push(&doc[start..inner.end]);
+
+    let mut chosen = None;
+    let mut default = None;
+    for alternative in alternatives {
+        let (keys, text) = alternative.split_once(':')?;
+        let keys = split_outside_parens(keys);
+        if keys.is_empty() {
+            return None;
+        }
+        if chosen.is_none() && keys.contains(&key) {
+            chosen = Some(text.trim());
+        }
+        if keys.contains(&"_") {
+            default = Some(text.trim());
+        }
+    }
+
+    Some(chosen.or(default).unwrap_or(""))
+}
+
+fn is_line_start(doc: &str, pos: usize) -> bool {
+    doc[..pos].chars().rev().take_while(|c| *c != '\n').all(char::is_whitespace)
+}
+
+// The length of the whitespaces and the newline after the marker, if nothing else follows it on
+// the line
+fn rest_of_line_len(doc: &str, pos: usize) -> Option<usize> {
+    let rest = &doc[pos..];
+    let spaces = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    match rest[spaces..].chars().next() {
+        Some('\n') => Some(spaces + 1),
+        None => Some(spaces),
+        Some(_) => None,
+    }
+}
+
+// Processes the markers in the text of the documentation (code blocks stay unchanged):
+//
+// - `<!-- only_if(key, ...) -->...<!-- end -->` (and `remove_if`) keeps the text in the listed
+//   versions only (removes it from them);
+// - `{{sync: ... | async: ...}}` is replaced with the alternative for this version;
+// - `{Self}` is replaced with the name of the item in this version, including the inline code,
+//   so ``[`{Self}`]`` is a link to the right item.
+pub fn process_prose(doc: &str, key: &str, self_name: Option<&str>) -> Option<String> {
+    if !(doc.contains("<!--") || doc.contains("{{") || doc.contains("{Self}")) {
+        return None;
+    }
+
+    let ranges = code_ranges(doc);
+    let replace_self = |text: &str| match self_name {
+        Some(name) => text.replace("{Self}", name),
+        None => text.to_string(),
+    };
+
+    let mut conditions: Vec<(Vec<String>, bool)> = vec![];
+    let mut result = String::new();
+    let mut changed = false;
+    let mut pos = 0;
+
+    while pos < doc.len() {
+        let rest = &doc[pos..];
+        let kept = conditions
+            .iter()
+            .all(|(keys, remove)| keys.iter().any(|k| k == key) != *remove);
+
+        if let Some((range, kind)) = ranges.iter().find(|(range, _)| range.start == pos) {
+            let code = &doc[range.clone()];
+            if kept && *kind == CodeKind::Inline {
+                let new_code = replace_self(code);
+                changed |= new_code != code;
+                result.push_str(&new_code);
+            } else if kept {
+                result.push_str(code);
+            } else {
+                changed = true;
+            }
+            pos = range.end.max(pos + 1);
+            continue;
+        }
+
+        if rest.starts_with("<!--") {
+            if let Some(end) = rest.find("-->") {
+                let body = rest[4..end].trim();
+                let condition = our_token_args(body, "only_if")
+                    .map(|keys| (keys, false))
+                    .or_else(|| our_token_args(body, "remove_if").map(|keys| (keys, true)));
+
+                if condition.is_some() || (body == "end" && !conditions.is_empty()) {
+                    match condition {
+                        Some(condition) => conditions.push(condition),
+                        None => {
+                            conditions.pop();
+                        }
+                    }
+
+                    let line_start = is_line_start(doc, pos);
+                    pos += end + 3;
+
+                    // the marker on its own line is removed with the line
+                    if let (true, Some(len)) = (line_start, rest_of_line_len(doc, pos)) {
+                        while result.ends_with([' ', '\t']) {
+                            result.pop();
+                        }
+                        pos += len;
+                    }
+
+                    changed = true;
+                    continue;
+                }
+            }
+        }
+
+        if rest.starts_with("{{") {
+            if let Some(end) = find_outside_code(doc, pos + 2, "}}", &ranges) {
+                if let Some(text) = choose_alternative(doc, pos + 2..end, key, &ranges) {
+                    if kept {
+                        result.push_str(&replace_self(text));
+                    }
+                    changed = true;
+                    pos = end + 2;
+                    continue;
+                }
+            }
+        }
+
+        if let (true, Some(name)) = (rest.starts_with("{Self}"), self_name) {
+            if kept {
+                result.push_str(name);
+            }
+            changed = true;
+            pos += "{Self}".len();
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        if kept {
+            result.push(c);
+        } else {
+            changed = true;
+        }
+        pos += c.len_utf8();
+    }
+
+    if changed {
+        Some(result)
+    } else {
+        None
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(is_ident_start) && chars.all(is_ident_continue)
+}
+
+// Renames the idents of the intra-doc link target, if it is a path:
+//
+// `Client::get()` -> `ClientSync::get()`, `struct@Client` -> `struct@ClientSync`
+fn rename_link_path(target: &str, rename: &impl Fn(&str) -> Option<String>) -> Option<String> {
+    let (disambiguator, path) = match target.split_once('@') {
+        Some((disambiguator, path)) if is_ident(disambiguator) => (&target[..disambiguator.len() + 1], path),
+        _ => ("", target),
+    };
+    let (path, suffix) = match path.strip_suffix("()").or_else(|| path.strip_suffix('!')) {
+        Some(stripped) => (stripped, &path[stripped.len()..]),
+        None => (path, ""),
+    };
+    let (leading_colon, path) = match path.strip_prefix("::") {
+        Some(path) => ("::", path),
+        None => ("", path),
+    };
+
+    let segments: Vec<&str> = path.split("::").collect();
+    if !segments.iter().all(|segment| is_ident(segment)) {
+        return None;
+    }
+
+    let mut changed = false;
+    let segments: Vec<String> = segments
+        .into_iter()
+        .map(|segment| match rename(segment) {
+            Some(new_segment) => {
+                changed = true;
+                new_segment
+            }
+            None => segment.to_string(),
+        })
+        .collect();
+
+    if changed {
+        Some(format!("{}{}{}{}", disambiguator, leading_colon, segments.join("::"), suffix))
+    } else {
+        None
+    }
+}
+
+fn strip_backticks(s: &str) -> &str {
+    match s.strip_prefix('`').and_then(|s| s.strip_suffix('`')) {
+        Some(inner) => inner,
+        None => s,
+    }
+}
+
+// The same for the link text or label, which may be an inline code: `` `Client` ``
+fn rename_link_text(text: &str, rename: &impl Fn(&str) -> Option<String>) -> Option<String> {
+    let inner = strip_backticks(text);
+    let new_inner = rename_link_path(inner, rename)?;
+    let start = (text.len() - inner.len()) / 2;
+    Some(format!("{}{}{}", &text[..start], new_inner, &text[start + inner.len()..]))
+}
+
+// The position of `]` closing the `[` at the start of the string, skipping the inline code
+fn closing_bracket(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '`' => {
+                let mut ticks = 1;
+                while chars.next_if(|(_, c)| *c == '`').is_some() {
+                    ticks += 1;
+                }
+                let closing = "`".repeat(ticks);
+                let end = s[i + ticks..].find(&closing)? + i + 2 * ticks;
+                while chars.next_if(|(j, _)| *j < end).is_some() {}
+            }
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+// `[text](dest "title")`, `[text][label]`, `[text][]` or `[text]`: the target is renamed, and the
+// text too if it repeats the target
+fn rename_link(source: &str, link_type: LinkType, rename: &impl Fn(&str) -> Option<String>) -> Option<String> {
+    let text_end = closing_bracket(source)?;
+    let text = &source[1..text_end];
+    let rest = &source[text_end + 1..];
+
+    let (target, new_rest) = match link_type {
+        LinkType::Inline => {
+            let inner = rest.strip_prefix('(')?.strip_suffix(')')?;
+            let trimmed = inner.trim_start();
+            let dest = &trimmed[..trimmed.find(char::is_whitespace).unwrap_or(trimmed.len())];
+            let new_rest = rename_link_path(dest, rename).map(|new_dest| {
+                let start = inner.len() - trimmed.len();
+                format!("({}{}{})", &inner[..start], new_dest, &trimmed[dest.len()..])
+            });
+            (dest, new_rest)
+        }
+        LinkType::Reference | LinkType::ReferenceUnknown => {
+            let label = rest.strip_prefix('[')?.strip_suffix(']')?;
+            (label, rename_link_text(label, rename).map(|new_label| format!("[{}]", new_label)))
+        }
+        LinkType::Collapsed
+        | LinkType::CollapsedUnknown
+        | LinkType::Shortcut
+        | LinkType::ShortcutUnknown => (text, None),
+        LinkType::Autolink | LinkType::Email => return None,
+    };
+
+    // `[Client](crate::Client)` repeats the target too
+    let (text_path, target_path) = (strip_backticks(text), strip_backticks(target));
+    let new_text = if text_path == target_path || target_path.ends_with(&format!("::{}", text_path)) {
+        rename_link_text(text, rename)
+    } else {
+        None
+    };
+
+    if new_text.is_none() && new_rest.is_none() {
+        return None;
+    }
+
+    Some(format!(
+        "[{}]{}",
+        new_text.as_deref().unwrap_or(text),
+        new_rest.as_deref().unwrap_or(rest)
+    ))
+}
+
+// `[label]: dest "title"`
+fn rename_link_definition(source: &str, rename: &impl Fn(&str) -> Option<String>) -> Option<String> {
+    let label_end = closing_bracket(source)?;
+    let label = &source[1..label_end];
+    let rest = source[label_end + 1..].strip_prefix(':')?;
+    let trimmed = rest.trim_start();
+    let dest = &trimmed[..trimmed.find(char::is_whitespace).unwrap_or(trimmed.len())];
+
+    let new_label = rename_link_text(label, rename);
+    let new_dest = rename_link_path(dest, rename);
+    if new_label.is_none() && new_dest.is_none() {
+        return None;
+    }
+
+    Some(format!(
+        "[{}]:{}{}{}",
+        new_label.as_deref().unwrap_or(label),
+        &rest[..rest.len() - trimmed.len()],
+        new_dest.as_deref().unwrap_or(dest),
+        &trimmed[dest.len()..]
+    ))
+}
+
+// Renames the items in the intra-doc links: ``[`Client::get`]`` -> ``[`ClientSync::get`]``,
+// `[fetch](fetch)` -> `[fetch_sync](fetch_sync)`, `[client]: Client` -> `[client]: ClientSync`
+pub fn rename_links(doc: &str, rename: impl Fn(&str) -> Option<String>) -> Option<String> {
+    // links to the items are not defined in the markdown
+    fn resolve<'a>(link: BrokenLink<'a>) -> Option<(CowStr<'a>, CowStr<'a>)> {
+        Some((link.reference, "".into()))
+    }
+    let mut callback = resolve;
+    let parser = Parser::new_with_broken_link_callback(doc, Options::empty(), Some(&mut callback));
+    let mut iter = parser.into_offset_iter();
+
+    let mut edits: Vec<(Range<usize>, String)> = vec![];
+    for (event, range) in iter.by_ref() {
+        if let Event::Start(Tag::Link(link_type, _, _)) = event {
+            if let Some(new_link) = rename_link(&doc[range.clone()], link_type, &rename) {
+                edits.push((range, new_link));
+            }
+        }
+    }
+
+    for (_, definition) in iter.reference_definitions().iter() {
+        let span = definition.span.clone();
+        let source = doc[span.clone()].trim_end();
+        if let Some(new_definition) = rename_link_definition(source, &rename) {
+            edits.push((span.start..span.start + source.len(), new_definition));
+        }
+    }
+
+    if edits.is_empty() {
+        return None;
+    }
+
+    edits.sort_by_key(|(range, _)| range.start);
+
+    let mut result = String::new();
+    let mut pos = 0;
+    for (range, new_text) in edits {
+        if range.start < pos {
+            continue;
+        }
+        result.push_str(&doc[pos..range.start]);
+        result.push_str(&new_text);
+        pos = range.end;
+    }
+    result.push_str(&doc[pos..]);
+
+    Some(result)
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    // identifiers, literals and single punctuation characters, as in `proc_macro2`
+    Token,
+    // `/// ...`, `//! ...`, `/** ... */` and `/*! ... */`, which are attributes for the parser
+    Doc,
+    // whitespaces, comments and the `# ` markers of the hidden lines
+    Trivia,
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn take_while(s: &str, f: impl Fn(char) -> bool) -> usize {
+    s.char_indices().find(|(_, c)| !f(*c)).map(|(i, _)| i).unwrap_or(s.len())
+}
+
+// `"..."` with escapes; `s` starts with the opening quote
+fn string_len(s: &str) -> usize {
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return i + 1,
+            _ => escaped = false,
+        }
+    }
+    s.len()
+}
+
+// `r#"..."#`; `s` starts with the first `#` or the opening quote
+fn raw_string_len(s: &str) -> Option<usize> {
+    let hashes = take_while(s, |c| c == '#');
+    if !s[hashes..].starts_with('"') {
+        return None;
+    }
+    let end = format!("\"{}", "#".repeat(hashes));
+    let len = s[hashes + 1..].find(&end).map(|i| hashes + 1 + i + end.len()).unwrap_or(s.len());
+    Some(len)
+}
+
+// `'a'`, `'\n'` or the lifetime `'a`; `s` starts with the quote
+fn quote_len(s: &str) -> usize {
+    let mut chars = s.char_indices().skip(1);
+    match chars.next() {
+        Some((_, '\\')) => s.get(3..).and_then(|rest| rest.find('\'')).map(|i| i + 4).unwrap_or(s.len()),
+        Some((_, c)) => match chars.next() {
+            Some((i, '\'')) => i + 1,
+            _ => 1 + c.len_utf8() + take_while(&s[1 + c.len_utf8()..], is_ident_continue),
+        },
+        None => 1,
+    }
+}
+
+// `1`, `0x1F_u8`, `1.5`, `2.0e-3f64`, but not `1..2` or `x.0.1`
+fn number_len(s: &str) -> usize {
+    let mut len = take_while(s, is_ident_continue);
+    let is_hex = s.starts_with("0x") || s.starts_with("0b") || s.starts_with("0o");
+    let rest = &s[len..];
+    if !is_hex && rest.starts_with('.') && rest[1..].starts_with(|c: char| c.is_ascii_digit()) {
+        len += 1 + take_while(&rest[1..], is_ident_continue);
+    }
+    let rest = &s[len..];
+    if !is_hex && s[..len].ends_with(['e', 'E']) && rest.starts_with(['+', '-']) {
+        len += 1 + take_while(&rest[1..], is_ident_continue);
+    }
+    len
+}
+
+// `/* ... */`, may be nested
+fn block_comment_len(s: &str) -> usize {
+    let mut depth = 0;
+    let mut i = 0;
+    while i < s.len() {
+        if s[i..].starts_with("/*") {
+            depth += 1;
+            i += 2;
+        } else if s[i..].starts_with("*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += s[i..].chars().next().map(char::len_utf8).unwrap_or(1);
+        }
+    }
+    s.len()
+}
+
+fn is_doc_comment(comment: &str) -> bool {
+    (comment.starts_with("///") && !comment.starts_with("////"))
+        || comment.starts_with("//!")
+        || (comment.starts_with("/**") && !comment.starts_with("/***") && comment != "/**/")
+        || comment.starts_with("/*!")
+}
+
+fn tokenize(code: &str) -> Vec<(TokenKind, &str)> {
+    let mut tokens = vec![];
+    let mut pos = 0;
+    let mut line_start = true;
+
+    while pos < code.len() {
+        let rest = &code[pos..];
+        let c = rest.chars().next().unwrap();
+
+        let (kind, len) = if c == '\n' {
+            (TokenKind::Trivia, 1)
+        } else if c.is_whitespace() {
+            (TokenKind::Trivia, take_while(rest, |c| c != '\n' && c.is_whitespace()))
+        } else if line_start && (rest == "#" || rest.starts_with("#\n") || rest.starts_with("# ")) {
+            (TokenKind::Trivia, if rest.starts_with("# ") { 2 } else { 1 })
+        } else if rest.starts_with("//") || rest.starts_with("/*") {
+            let len = if rest.starts_with("//") { take_while(rest, |c| c != '\n') } else { block_comment_len(rest) };
+            let kind = if is_doc_comment(&rest[..len]) { TokenKind::Doc } else { TokenKind::Trivia };
+            (kind, len)
+        } else if c == '"' {
+            (TokenKind::Token, string_len(rest))
+        } else if c == '\'' {
+            (TokenKind::Token, quote_len(rest))
+        } else if c.is_ascii_digit() {
+            (TokenKind::Token, number_len(rest))
+        } else if is_ident_start(c) {
+            let len = take_while(rest, is_ident_continue);
+            let after = &rest[len..];
+            match &rest[..len] {
+                "r" | "br" | "cr" if after.starts_with('"') || after.starts_with("#\"") || after.starts_with("##") => {
+                    (TokenKind::Token, len + raw_string_len(after).unwrap_or(0))
+                }
+                // raw identifier
+                "r" if after.starts_with('#') => {
+                    (TokenKind::Token, len + 1 + take_while(&after[1..], is_ident_continue))
+                }
+                "b" | "c" if after.starts_with('"') => (TokenKind::Token, len + string_len(after)),
+                "b" if after.starts_with('\'') => (TokenKind::Token, len + quote_len(after)),
+                _ => (TokenKind::Token, len),
+            }
+        } else {
+            (TokenKind::Token, c.len_utf8())
+        };
+
+        let len = len.max(1).min(rest.len());
+        let text = &rest[..len];
+        line_start = text.ends_with('\n') || (line_start && text.chars().all(|c| c.is_whitespace() && c != '\n'));
+        tokens.push((kind, text));
+        pos += len;
+    }
+
+    tokens
+}
+
+// The doc comments are compared with the `doc` attributes by their text
+fn doc_key(inner: bool, text: &str) -> String {
+    format!("{}doc{:?}", if inner { "#!" } else { "#" }, text)
+}
+
+fn source_key(kind: TokenKind, text: &str) -> String {
+    match kind {
+        TokenKind::Doc => {
+            let inner = text.starts_with("//!") || text.starts_with("/*!");
+            let content = if text.starts_with("//") { &text[3..] } else { &text[3..text.len() - 2] };
+            doc_key(inner, content)
+        }
+        TokenKind::Token | TokenKind::Trivia => text.to_string(),
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// A token of the converted code: the punctuation is split into single characters and the groups
+// into their delimiters, like the source is
+struct Leaf {
+    key: String,
+    text: String,
+    joint: bool,
+}
+
+impl Leaf {
+    fn new(text: String) -> Self {
+        Self { key: text.clone(), text, joint: false }
+    }
+}
+
+// `#[doc = "..."]` or `#![doc = "..."]` starting at `pos`, and the number of its token trees
+fn doc_attr(trees: &[TokenTree], pos: usize) -> Option<(Leaf, usize)> {
+    let inner = matches!(trees.get(pos + 1), Some(TokenTree::Punct(punct)) if punct.as_char() == '!');
+    let len = if inner { 3 } else { 2 };
+    let group = match trees.get(pos + len - 1) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => group,
+        _ => return None,
+    };
+
+    let text = match syn::parse2::<syn::MetaNameValue>(group.stream()).ok()? {
+        syn::MetaNameValue { path, lit: syn::Lit::Str(text), .. } if path.is_ident("doc") => text.value(),
+        _ => return None,
+    };
+
+    let tokens = trees[pos..pos + len].iter().cloned().collect::<TokenStream2>();
+    let leaf = Leaf {
+        key: doc_key(inner, &text),
+        text: tokens.to_string(),
+        joint: false,
+    };
+    Some((leaf, len))
+}
+
+fn flatten(stream: TokenStream2, leaves: &mut Vec<Leaf>) {
+    let trees = stream.into_iter().collect::<Vec<_>>();
+    let mut pos = 0;
+
+    while pos < trees.len() {
+        match &trees[pos] {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                if !open.is_empty() {
+                    leaves.push(Leaf::new(open.to_string()));
+                }
+                flatten(group.stream(), leaves);
+                if !close.is_empty() {
+                    leaves.push(Leaf::new(close.to_string()));
+                }
+            }
+            TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                if let Some((leaf, len)) = doc_attr(&trees, pos) {
+                    leaves.push(leaf);
+                    pos += len;
+                    continue;
+                }
+                leaves.push(Leaf::new("#".to_string()));
+            }
+            // the lifetime is a single token of the source
+            TokenTree::Punct(punct) if punct.as_char() == '\'' => match trees.get(pos + 1) {
+                Some(TokenTree::Ident(ident)) => {
+                    leaves.push(Leaf::new(format!("'{}", ident)));
+                    pos += 2;
+                    continue;
+                }
+                _ => leaves.push(Leaf::new("'".to_string())),
+            },
+            TokenTree::Punct(punct) => leaves.push(Leaf {
+                joint: punct.spacing() == Spacing::Joint,
+                ..Leaf::new(punct.as_char().to_string())
+            }),
+            TokenTree::Ident(ident) => leaves.push(Leaf::new(ident.to_string())),
+            TokenTree::Literal(literal) => leaves.push(Leaf::new(literal.to_string())),
+        }
+        pos += 1;
+    }
+}
+
+fn is_word(text: &str) -> bool {
+    text.starts_with(|c: char| is_ident_continue(c) || c == '"' || c == '\'')
+        && text.ends_with(|c: char| is_ident_continue(c) || c == '"' || c == '\'')
+}
+
+// A space, if the tokens would be glued into one otherwise
+fn separator(prev: Option<&str>, next: Option<&str>) -> &'static str {
+    match (prev, next) {
+        (Some(prev), Some(next)) if is_word(prev) && is_word(next) => " ",
+        _ => "",
+    }
+}
+
+// The inserted tokens are formatted roughly like rustfmt does
+fn join_leaves(leaves: &[&Leaf]) -> String {
+    let mut result = String::new();
+
+    for (i, leaf) in leaves.iter().enumerate() {
+        if i > 0 {
+            let prev = leaves[i - 1];
+            let path_sep = prev.text == ":" && i > 1 && leaves[i - 2].text == ":" && leaves[i - 2].joint;
+            let glued = prev.joint
+                || path_sep
+                || matches!(prev.text.as_str(), "(" | "[" | "." | "#" | "&")
+                || matches!(leaf.text.as_str(), ")" | "]" | "." | "," | ";" | "?" | ":")
+                || (matches!(leaf.text.as_str(), "(" | "[" | "!") && is_word(&prev.text))
+                || (leaf.text == "(" && matches!(prev.text.as_str(), "!" | ">" | ")"));
+            if !glued {
+                result.push(' ');
+            }
+        }
+        result.push_str(&leaf.text);
+    }
+
+    result
+}
+
+// The tokens to keep in the source (the longest common subsequence with the converted code) and
+// the converted tokens to insert before each source token (or at the end)
+fn diff(source: &[String], converted: &[Leaf]) -> (Vec<bool>, Vec<Vec<usize>>) {
+    let (n, m) = (source.len(), converted.len());
+    let mut kept = vec![false; n];
+    let mut inserted = vec![vec![]; n + 1];
+
+    let prefix = (0..n.min(m)).take_while(|&i| source[i] == converted[i].key).count();
+    let suffix = (0..(n - prefix).min(m - prefix))
+        .take_while(|&i| source[n - 1 - i] == converted[m - 1 - i].key)
+        .count();
+    for i in (0..prefix).chain(n - suffix..n) {
+        kept[i] = true;
+    }
+
+    let (source, converted) = (&source[prefix..n - suffix], &converted[prefix..m - suffix]);
+    let (n, m) = (source.len(), converted.len());
+
+    // lcs[i * (m + 1) + j] is the length of the common subsequence of `source[i..]` and
+    // `converted[j..]`
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * (m + 1) + j] = if source[i] == converted[j].key {
+                lcs[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && source[i] == converted[j].key {
+            kept[prefix + i] = true;
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lcs[(i + 1) * (m + 1) + j] >= lcs[i * (m + 1) + j + 1]) {
+            i += 1;
+        } else {
+            inserted[prefix + i].push(prefix + j);
+            j += 1;
+        }
+    }
+
+    (kept, inserted)
+}
+
+// The wrapper making the doctest a valid item, like rustdoc does
+const DOCTEST_FN: &str = "__maybe_async_cfg_doctest";
+
+// The code of the doctest is parsed as the body of a function, and converted by `convert` like the
+// item itself. Then the changes are applied to the source token by token, so the formatting, the
+// comments and the hidden lines of the unchanged code are kept: the removed tokens are dropped
+// together with the whitespaces around them, and the new ones are inserted. Fails if the code can
+// not be parsed (e.g. it is not complete) or converted.
+pub fn convert_doctest(code: &str, convert: impl FnOnce(&mut syn::ItemFn) -> syn::Result<()>) -> syn::Result<String> {
+    let tokens = tokenize(code);
+
+    let is_marker = |(kind, text): &(TokenKind, &str)| *kind == TokenKind::Trivia && text.starts_with('#');
+    let visible = tokens
+        .iter()
+        .map(|token| if is_marker(token) { "" } else { token.1 })
+        .collect::<String>();
+    let mut item: syn::ItemFn = syn::parse_str(&format!("fn {}() {{\n{}\n}}", DOCTEST_FN, visible))?;
+
+    convert(&mut item)?;
+
+    let mut converted = vec![];
+    let inner_attrs = item.attrs.iter().filter(|attr| matches!(attr.style, syn::AttrStyle::Inner(_)));
+    flatten(quote!(#(#inner_attrs)*), &mut converted);
+    let stmts = &item.block.stmts;
+    flatten(quote!(#(#stmts)*), &mut converted);
+
+    let significant = (0..tokens.len()).filter(|i| tokens[*i].0 != TokenKind::Trivia).collect::<Vec<_>>();
+    let source = significant.iter().map(|i| source_key(tokens[*i].0, tokens[*i].1)).collect::<Vec<_>>();
+    let (kept, inserted) = diff(&source, &converted);
+
+    // the trivia before the significant token `k` (or after the last one)
+    let trivia = |k: usize| -> String {
+        let from = if k == 0 { 0 } else { significant[k - 1] + 1 };
+        let to = significant.get(k).copied().unwrap_or(tokens.len());
+        tokens[from..to].iter().map(|token| token.1).collect()
+    };
+    let text = |k: usize| significant.get(k).map(|i| tokens[*i].1);
+
+    let n = significant.len();
+    let mut result = String::new();
+    let mut prev: Option<String> = None;
+    let mut k = 0;
+
+    while k <= n {
+        // the removed tokens `k..next` and the tokens inserted instead of them
+        let next = (k..n).find(|i| kept[*i]).unwrap_or(n);
+        let new = (k..=next).flat_map(|i| inserted[i].iter().map(|j| &converted[*j])).collect::<Vec<_>>();
+        let (lead, trail) = (trivia(k), trivia(next));
+
+        if new.is_empty() && next == k {
+            result.push_str(&lead);
+        } else if new.is_empty() {
+            let closing = match text(next) {
+                Some(next) => matches!(next, ";" | "," | ")" | "]" | "}" | "?" | "."),
+                None => true,
+            };
+            if closing {
+                result.push_str(&trail);
+            } else {
+                // the whitespaces after the removed tokens are dropped; if the removed tokens span
+                // several lines, the next token gets the indent (and the hidden line marker) of
+                // the last one
+                let inner = (k + 1..next).map(&trivia).collect::<String>();
+                let lead = match inner.rfind('\n') {
+                    Some(pos) => {
+                        let line_end = lead.rfind('\n').map(|p| p + 1).unwrap_or(lead.len());
+                        format!("{}{}", &lead[..line_end], &inner[pos + 1..])
+                    }
+                    None => lead,
+                };
+                if lead.is_empty() {
+                    result.push_str(separator(prev.as_deref(), text(next)));
+                }
+                result.push_str(&lead);
+            }
+        } else {
+            let joined = join_leaves(&new);
+            if next == k {
+                result.push_str(separator(prev.as_deref(), Some(&joined)));
+                result.push_str(&joined);
+                if lead.is_empty() {
+                    result.push_str(separator(Some(&joined), text(next)));
+                }
+                result.push_str(&lead);
+            } else {
+                result.push_str(&lead);
+                if lead.is_empty() {
+                    result.push_str(separator(prev.as_deref(), Some(&joined)));
+                }
+                result.push_str(&joined);
+                if trail.is_empty() {
+                    result.push_str(separator(Some(&joined), text(next)));
+                }
+                result.push_str(&trail);
+            }
+        }
+
+        if let Some(text) = text(next) {
+            result.push_str(text);
+            prev = Some(text.to_string());
+        }
+        k = next + 1;
+    }
+
+    Ok(result)
+}
+
+// `#[tokio::main]` makes no sense on the `main` function of the sync version
+pub fn remove_runtime_attrs(item: &mut syn::ItemFn) {
+    struct RuntimeAttrs;
+
+    impl VisitMut for RuntimeAttrs {
+        fn visit_item_fn_mut(&mut self, node: &mut syn::ItemFn) {
+            node.attrs.retain(|attr| {
+                !RUNTIME_ATTRS.contains(&path_to_string(&attr.path).as_str())
+            });
+            visit_mut::visit_item_fn_mut(self, node);
+        }
+    }
+
+    RuntimeAttrs.visit_item_fn_mut(item);
+}
+
+const RUNTIME_ATTRS: &[&str] = &["tokio::main", "tokio::test", "async_std::main", "async_std::test"];
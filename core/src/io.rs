@@ -0,0 +1,245 @@
+use quote::quote;
+use syn::{
+    punctuated::Punctuated,
+    visit_mut::VisitMut,
+};
+
+use crate::paths::path_to_string;
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// The async I/O traits of tokio and futures, and their `*Ext` companions, become the `std::io` ones
+pub const IO_PATHS: &[(&str, &str)] = &[
+    // tokio
+    ("tokio::io::AsyncRead", "std::io::Read"),
+    ("tokio::io::AsyncReadExt", "std::io::Read"),
+    ("tokio::io::AsyncWrite", "std::io::Write"),
+    ("tokio::io::AsyncWriteExt", "std::io::Write"),
+    ("tokio::io::AsyncBufRead", "std::io::BufRead"),
+    ("tokio::io::AsyncBufReadExt", "std::io::BufRead"),
+    ("tokio::io::AsyncSeek", "std::io::Seek"),
+    ("tokio::io::AsyncSeekExt", "std::io::Seek"),
+    // futures
+    ("futures::io::AsyncRead", "std::io::Read"),
+    ("futures::io::AsyncReadExt", "std::io::Read"),
+    ("futures::io::AsyncWrite", "std::io::Write"),
+    ("futures::io::AsyncWriteExt", "std::io::Write"),
+    ("futures::io::AsyncBufRead", "std::io::BufRead"),
+    ("futures::io::AsyncBufReadExt", "std::io::BufRead"),
+    ("futures::io::AsyncSeek", "std::io::Seek"),
+    ("futures::io::AsyncSeekExt", "std::io::Seek"),
+    ("futures::AsyncRead", "std::io::Read"),
+    ("futures::AsyncReadExt", "std::io::Read"),
+    ("futures::AsyncWrite", "std::io::Write"),
+    ("futures::AsyncWriteExt", "std::io::Write"),
+    ("futures::AsyncBufRead", "std::io::BufRead"),
+    ("futures::AsyncBufReadExt", "std::io::BufRead"),
+    ("futures::AsyncSeek", "std::io::Seek"),
+    ("futures::AsyncSeekExt", "std::io::Seek"),
+    ("futures_util::io::AsyncReadExt", "std::io::Read"),
+    ("futures_util::io::AsyncWriteExt", "std::io::Write"),
+    ("futures_util::io::AsyncBufReadExt", "std::io::BufRead"),
+    ("futures_util::io::AsyncSeekExt", "std::io::Seek"),
+    ("futures_io::AsyncRead", "std::io::Read"),
+    ("futures_io::AsyncWrite", "std::io::Write"),
+    ("futures_io::AsyncBufRead", "std::io::BufRead"),
+    ("futures_io::AsyncSeek", "std::io::Seek"),
+];
+
+// `AsyncRead`, `AsyncReadExt` -> "Read", etc.
+pub fn io_trait_name(name: &str) -> Option<&'static str> {
+    match name {
+        "AsyncRead" | "AsyncReadExt" => Some("Read"),
+        "AsyncWrite" | "AsyncWriteExt" => Some("Write"),
+        "AsyncBufRead" | "AsyncBufReadExt" => Some("BufRead"),
+        "AsyncSeek" | "AsyncSeekExt" => Some("Seek"),
+        _ => None,
+    }
+}
+
+fn is_io_path(path: &str) -> bool {
+    IO_PATHS.iter().any(|(from, _)| *from == path)
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// The names of the traits imported from the paths above. Only these names are converted, so the
+// own traits of the user named `AsyncRead` etc. are left intact
+pub fn imported_io_traits(file: &mut syn::File) -> Vec<String> {
+    let mut imports = IoImports { names: vec![] };
+    imports.visit_file_mut(file);
+    imports.names
+}
+
+struct IoImports {
+    names: Vec<String>,
+}
+
+impl IoImports {
+    fn add(&mut self, name: String) {
+        if io_trait_name(&name).is_some() && !self.names.contains(&name) {
+            self.names.push(name);
+        }
+    }
+
+    // `use tokio::io::{AsyncRead, AsyncWriteExt as AsyncWrite, *};`
+    fn collect(&mut self, tree: &syn::UseTree, prefix: &mut Vec<String>) {
+        match tree {
+            syn::UseTree::Path(path) => {
+                prefix.push(path.ident.to_string());
+                self.collect(&path.tree, prefix);
+                prefix.pop();
+            }
+            syn::UseTree::Name(name) => {
+                let name = name.ident.to_string();
+                if is_io_path(&format!("{}::{}", prefix.join("::"), name)) {
+                    self.add(name);
+                }
+            }
+            syn::UseTree::Rename(rename) => {
+                if is_io_path(&format!("{}::{}", prefix.join("::"), rename.ident)) {
+                    self.add(rename.rename.to_string());
+                }
+            }
+            syn::UseTree::Glob(_) => {
+                let module = format!("{}::", prefix.join("::"));
+                for (from, _) in IO_PATHS {
+                    match from.strip_prefix(&module) {
+                        Some(name) if !name.contains("::") => self.add(name.to_string()),
+                        _ => {}
+                    }
+                }
+            }
+            syn::UseTree::Group(group) => {
+                for item in &group.items {
+                    self.collect(item, prefix);
+                }
+            }
+        }
+    }
+}
+
+impl VisitMut for IoImports {
+    fn visit_item_use_mut(&mut self, node: &mut syn::ItemUse) {
+        self.collect(&node.tree, &mut vec![]);
+    }
+}
+
+// `AsyncReadExt::read_exact(r, buf)` -> `Read::read_exact(r, buf)`
+pub fn convert_io_path(path: &mut syn::Path, imported: &[String]) {
+    if path.leading_colon.is_some() {
+        return;
+    }
+
+    if let Some(first) = path.segments.first_mut() {
+        if !imported.iter().any(|name| first.ident == name) {
+            return;
+        }
+        if let Some(name) = io_trait_name(&first.ident.to_string()) {
+            first.ident = syn::Ident::new(name, first.ident.span());
+        }
+    }
+}
+
+// The paths are converted already, the imported names are restored, and the duplicates are removed:
+//
+// `use {std::io::Read as AsyncRead, std::io::Read as AsyncReadExt};` -> `use std::io::Read;`
+pub fn convert_io_use(tree: &mut syn::UseTree, imported: &[String]) {
+    match tree {
+        syn::UseTree::Path(path) => convert_io_use(&mut path.tree, imported),
+        syn::UseTree::Rename(rename) => {
+            if !imported.iter().any(|name| rename.rename == name) {
+                return;
+            }
+            if io_trait_name(&rename.rename.to_string()).is_some_and(|name| rename.ident == name) {
+                *tree = syn::UseTree::Name(syn::UseName { ident: rename.ident.clone() });
+            }
+        }
+        syn::UseTree::Group(group) => {
+            let mut seen = vec![];
+            let mut items = Punctuated::new();
+            for mut item in std::mem::take(&mut group.items) {
+                convert_io_use(&mut item, imported);
+                let key = quote!(#item).to_string();
+                if !seen.contains(&key) {
+                    seen.push(key);
+                    items.push(item);
+                }
+            }
+            group.items = items;
+            if group.items.len() == 1 {
+                let item = group.items.pop().unwrap().into_value();
+                *tree = item;
+            }
+        }
+        syn::UseTree::Name(_) | syn::UseTree::Glob(_) => {}
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// `AsyncRead` imported from the known paths, or `tokio::io::AsyncRead`
+fn is_io_bound(bound: &syn::TypeParamBound, imported: &[String]) -> bool {
+    match bound {
+        syn::TypeParamBound::Trait(bound) => match bound.path.get_ident() {
+            Some(ident) => imported.iter().any(|name| ident == name),
+            None => is_io_path(&path_to_string(&bound.path)),
+        },
+        syn::TypeParamBound::Lifetime(_) => false,
+    }
+}
+
+fn is_unpin_bound(bound: &syn::TypeParamBound) -> bool {
+    match bound {
+        syn::TypeParamBound::Trait(bound) => {
+            bound.path.segments.last().is_some_and(|last| last.ident == "Unpin")
+        }
+        syn::TypeParamBound::Lifetime(_) => false,
+    }
+}
+
+// `R: AsyncRead + Unpin` -> `R: AsyncRead` (to be converted into `Read` later)
+pub fn remove_io_unpin(bounds: &mut Punctuated<syn::TypeParamBound, syn::Token![+]>, imported: &[String]) {
+    if bounds.iter().any(|bound| is_io_bound(bound, imported)) && bounds.iter().any(is_unpin_bound) {
+        *bounds = std::mem::take(bounds).into_iter().filter(|bound| !is_unpin_bound(bound)).collect();
+    }
+}
+
+pub fn remove_io_unpin_in_generics(generics: &mut syn::Generics, imported: &[String]) {
+    for param in generics.type_params_mut() {
+        remove_io_unpin(&mut param.bounds, imported);
+    }
+
+    if let Some(where_clause) = &mut generics.where_clause {
+        for predicate in where_clause.predicates.iter_mut() {
+            if let syn::WherePredicate::Type(predicate) = predicate {
+                remove_io_unpin(&mut predicate.bounds, imported);
+            }
+        }
+    }
+}
+
+// `&mut (impl AsyncRead + Unpin)` -> `&mut impl AsyncRead`
+pub fn remove_io_unpin_in_type(ty: &mut syn::Type, imported: &[String]) {
+    match ty {
+        syn::Type::ImplTrait(ty) => remove_io_unpin(&mut ty.bounds, imported),
+        syn::Type::TraitObject(ty) => remove_io_unpin(&mut ty.bounds, imported),
+        syn::Type::Paren(paren) => {
+            let single = match &mut *paren.elem {
+                syn::Type::ImplTrait(inner) => {
+                    remove_io_unpin(&mut inner.bounds, imported);
+                    inner.bounds.len() == 1
+                }
+                syn::Type::TraitObject(inner) => {
+                    remove_io_unpin(&mut inner.bounds, imported);
+                    inner.bounds.len() == 1
+                }
+                _ => false,
+            };
+            if single {
+                *ty = (*paren.elem).clone();
+            }
+        }
+        _ => {}
+    }
+}
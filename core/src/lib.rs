@@ -0,0 +1,98 @@
+//! The implementation of the [`maybe-async-cfg`](https://docs.rs/maybe-async-cfg) macros.
+//!
+//! This is not a proc-macro crate, so the convertation can be performed outside of the compiler
+//! (see the `maybe-async-cfg-expand` tool). All the functions work with `proc_macro2` token
+//! streams and return all the errors found during the convertation at once.
+#![deny(missing_docs)]
+
+use proc_macro2::TokenStream as TokenStream2;
+
+mod blocking;
+mod combinators;
+mod config;
+mod io;
+mod macros;
+mod params;
+mod paths;
+mod streams;
+mod utils;
+mod validate;
+mod visit_ext;
+mod visitor_async;
+mod visitor_content;
+
+mod doctests;
+
+mod debug;
+
+const DEFAULT_CRATE_NAME: &'static str = "maybe_async_cfg";
+const MACRO_MAYBE_NAME: &'static str = "maybe";
+const MACRO_ONLY_IF_NAME: &'static str = "only_if";
+const MACRO_REMOVE_IF_NAME: &'static str = "remove_if";
+const MACRO_NOOP_NAME: &'static str = "noop";
+const MACRO_REMOVE_NAME: &'static str = "remove";
+const MACRO_DEFAULT_NAME: &'static str = "default";
+const MACRO_SYNC_IMPL_NAME: &str = "sync_impl";
+const MACRO_ASYNC_IMPL_NAME: &str = "async_impl";
+const MACRO_VERSION_IMPL_NAME: &str = "version_impl";
+const CONFIG_FILE_NAME: &str = "maybe-async-cfg.toml";
+const CONFIG_ENV_NAME: &str = "MAYBE_ASYNC_CFG_CONFIG";
+
+const STANDARD_MACROS: &'static [&'static str] = &[
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "dbg",
+    "debug_assert",
+    "debug_assert_eq",
+    "debug_assert_ne",
+    "eprint",
+    "eprintln",
+    "format",
+    "format_args",
+    "matches",
+    "panic",
+    "print",
+    "println",
+    "todo",
+    "unimplemented",
+    "unreachable",
+    "vec",
+    "write",
+    "writeln",
+];
+
+/// The `maybe` attribute macro: `args` are the attribute arguments, `input` is the item.
+///
+/// Like the compiler does, the first call produces a copy of the item for each version, marked
+/// with the `maybe` attribute again; these attributes are then converted by the next calls.
+pub fn maybe(args: TokenStream2, input: TokenStream2) -> syn::Result<TokenStream2> {
+    macros::maybe(args, input)
+}
+
+/// Expands the `maybe` attribute completely: only the version with the `key` (or all the versions
+/// if `key` is `None`) is produced and converted at once.
+pub fn expand(args: TokenStream2, input: TokenStream2, key: Option<&str>) -> syn::Result<TokenStream2> {
+    macros::expand(args, input, key)
+}
+
+/// The `content` macro: applies the `default` parameters to the `maybe` attributes of the body.
+pub fn content(body: TokenStream2) -> syn::Result<TokenStream2> {
+    macros::content(body)
+}
+
+/// The `noop` attribute macro: the item is left intact.
+pub fn noop(_args: TokenStream2, input: TokenStream2) -> TokenStream2 {
+    input
+}
+
+/// The `remove` attribute macro: the item is removed.
+pub fn remove(_args: TokenStream2, _input: TokenStream2) -> TokenStream2 {
+    TokenStream2::new()
+}
+
+/// The `blocking_runtime` macro: the tokio runtime shared by the blocking versions with
+/// `executor = "tokio"`, to be placed at the crate root.
+pub fn blocking_runtime() -> TokenStream2 {
+    blocking::runtime_fn()
+}
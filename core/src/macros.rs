@@ -0,0 +1,534 @@
+#[allow(unused_imports)]
+use std::iter::FromIterator;
+use std::collections::HashSet;
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::{
+    spanned::Spanned, visit_mut::VisitMut, Ident,
+};
+
+#[allow(unused_imports)]
+use quote::{quote, quote_spanned, ToTokens};
+
+use crate::{
+    MACRO_MAYBE_NAME,
+    blocking,
+    combinators,
+    config,
+    io,
+    streams,
+    validate,
+    params::{ConvertMode, IdentCase, MacroParameters},
+    visit_ext::Visitor,
+    visitor_async::{
+        AsyncAwaitVisitor, remove_asyncness_on_trait, remove_asyncness_on_impl,
+        remove_asyncness_on_fn},
+    visitor_content::ContentVisitor,
+    debug::*
+};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub fn maybe(args: TokenStream2, input: TokenStream2) -> syn::Result<TokenStream2> {
+    dump_maybe!(&args, &input);
+
+    let mut params = MacroParameters::from_tokens(args)?;
+    dump_params!("maybe params", &params);
+
+    let (tracked_input, mut tokens) = track_config_file(&params, input.clone());
+
+    if params.disable_get() {
+        tokens.extend(tracked_input);
+        return Ok(tokens);
+    }
+
+    if let Some(convert_mode) = params.mode_get() {
+        tokens.extend(convert(params, tracked_input, convert_mode)?);
+        return Ok(tokens);
+    }
+
+    check_foreign_mod(&params, &input)?;
+
+    // The unused `idents` records are reported by the invocations converting the versions; the
+    // blocking ones apply no records and the disabled ones are not converted
+    let mut checked = HashSet::new();
+    for version in &mut params.versions {
+        if !version.params.disable_get() && !matches!(version.kind, ConvertMode::IntoBlocking) {
+            version.params.idents_check_once(&mut checked);
+        }
+    }
+
+    for (index, version) in params.versions.iter().enumerate() {
+        match version.kind {
+            ConvertMode::IntoAsync | ConvertMode::IntoSync | ConvertMode::IntoBlocking => {
+                version
+                    .params
+                    .extend_tokenstream2_with_cfg_outer_attrs(&mut tokens)?;
+                let name = params.make_self_path(MACRO_MAYBE_NAME);
+                let args = version.params.to_tokens(Some(version.kind));
+                tokens.extend(quote!(#[#name(#args)]));
+
+                version.params.extend_tokenstream2_with_inner_attrs(&mut tokens)?;
+            }
+        }
+
+        // the config file is tracked once
+        tokens.extend(if index == 0 { tracked_input.clone() } else { input.clone() });
+    }
+
+    dump_tokens!("maybe after", &tokens);
+
+    Ok(tokens)
+}
+
+// The expansion depends on the config file if a profile is used, so the file is included by a
+// `const` item. It is placed next to the items which are allowed only in modules and blocks, and
+// into the body of a function (which may be a method); the items which may be associated ones are
+// not tracked. Returns the input to put the first version of and the tokens to put before it
+fn track_config_file(params: &MacroParameters, input: TokenStream2) -> (TokenStream2, TokenStream2) {
+    let path = match params.config_file_get() {
+        Some(path) => path,
+        None => return (input, TokenStream2::new()),
+    };
+    let track = config::track_file(path);
+
+    match syn::parse2::<syn::Item>(input.clone()) {
+        Ok(syn::Item::Fn(mut item)) => {
+            item.block.stmts.insert(0, syn::parse_quote!(#track));
+            (quote!(#item), TokenStream2::new())
+        }
+        Ok(syn::Item::Const(_))
+        | Ok(syn::Item::Type(_))
+        | Ok(syn::Item::Macro(_))
+        | Ok(syn::Item::Verbatim(_))
+        | Err(_) => (input, TokenStream2::new()),
+        Ok(_) => (input, track),
+    }
+}
+
+// Converts the versions at once (instead of producing the `maybe` attribute for each of them)
+pub fn expand(args: TokenStream2, input: TokenStream2, key: Option<&str>) -> syn::Result<TokenStream2> {
+    let params = MacroParameters::from_tokens(args)?;
+
+    if params.disable_get() {
+        return Ok(input);
+    }
+
+    if let Some(convert_mode) = params.mode_get() {
+        return convert(params, input, convert_mode)
+    }
+
+    check_foreign_mod(&params, &input)?;
+
+    let mut tokens = TokenStream2::new();
+    let mut unused = UnusedIdents::new();
+
+    for version in &params.versions {
+        if key.is_some() && version.params.key_get() != key {
+            continue;
+        }
+
+        version
+            .params
+            .extend_tokenstream2_with_cfg_outer_attrs(&mut tokens)?;
+        version.params.extend_tokenstream2_with_inner_attrs(&mut tokens)?;
+
+        let version_params = MacroParameters::from_tokens(version.params.to_tokens(Some(version.kind)))?;
+        if version_params.disable_get() {
+            tokens.extend(input.clone());
+        } else {
+            tokens.extend(unused.convert(version_params, input.clone(), version.kind)?);
+        }
+    }
+
+    tokens.extend(unused.warnings());
+
+    Ok(tokens)
+}
+
+// The items of an extern block are declared in every version, so with several versions each of
+// them must be renamed (its symbol is kept by `link_name`, see `convert_foreign_mod`)
+fn check_foreign_mod(params: &MacroParameters, input: &TokenStream2) -> syn::Result<()> {
+    let item = match syn::parse2::<syn::ItemForeignMod>(input.clone()) {
+        Ok(item) => item,
+        Err(_) => return Ok(()),
+    };
+
+    if params.versions.len() < 2 {
+        return Ok(());
+    }
+
+    let mut errors: Option<syn::Error> = None;
+    for foreign_item in &item.items {
+        let ident = match foreign_item_ident(foreign_item) {
+            Some(ident) => ident,
+            None => continue,
+        };
+
+        let renamed = params
+            .versions
+            .iter()
+            .all(|version| version.params.ident_rename(ident, version.kind).is_some());
+        if !renamed {
+            let err = syn::Error::new(
+                ident.span(),
+                format!(
+                    "`{}` would be declared in every version; add it to `idents(...)` or keep \
+                    the extern block in one version only",
+                    ident
+                ),
+            );
+            match &mut errors {
+                Some(errors) => errors.combine(err),
+                None => errors = Some(err),
+            }
+        }
+    }
+
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub fn convert(params: MacroParameters, input: TokenStream2, convert_mode: ConvertMode) -> syn::Result<TokenStream2> {
+    let mut unused = UnusedIdents::new();
+    let mut ts = unused.convert(params, input, convert_mode)?;
+    ts.extend(unused.warnings());
+    Ok(ts)
+}
+
+// The `idents` records checked while converting the versions of an item: a record is reported if
+// it is applied by none of the versions it is declared for
+struct UnusedIdents {
+    records: Vec<(String, Span, bool)>,
+}
+
+impl UnusedIdents {
+    fn new() -> Self {
+        Self { records: vec![] }
+    }
+
+    // The blocking version delegates to an async one and applies no records, so it is not checked
+    fn convert(
+        &mut self,
+        mut params: MacroParameters,
+        input: TokenStream2,
+        convert_mode: ConvertMode,
+    ) -> syn::Result<TokenStream2> {
+        let declared = params.idents_unused();
+        let ts = convert_items(&mut params, input, convert_mode)?;
+        if matches!(convert_mode, ConvertMode::IntoBlocking) {
+            return Ok(ts);
+        }
+
+        let unused = params.idents_unused();
+        for (name, span) in declared {
+            let is_unused = unused.iter().any(|(n, _)| *n == name);
+            match self.records.iter_mut().find(|(n, _, _)| *n == name) {
+                Some(record) => record.2 &= is_unused,
+                None => self.records.push((name, span, is_unused)),
+            }
+        }
+
+        Ok(ts)
+    }
+
+    fn warnings(&self) -> TokenStream2 {
+        self.records
+            .iter()
+            .filter(|(_, _, unused)| *unused)
+            .map(|(name, span, _)| unused_ident_warning(name, *span))
+            .collect()
+    }
+}
+
+fn convert_items(params: &mut MacroParameters, input: TokenStream2, convert_mode: ConvertMode) -> syn::Result<TokenStream2> {
+    dump_tokens2!("convert before", &input);
+
+    let mut file: syn::File = syn::parse2(input)?;
+
+    if let ConvertMode::IntoBlocking = convert_mode {
+        blocking::convert(params, &mut file)?;
+
+        let ts = quote!(#file);
+        dump_tokens2!("convert after", &ts);
+        return Ok(ts);
+    }
+
+    if let (ConvertMode::IntoSync, true) = (convert_mode, params.io_get()) {
+        params.io_traits_set(io::imported_io_traits(&mut file));
+    }
+    if let ConvertMode::IntoSync = convert_mode {
+        params.futures_fns_set(combinators::imported_futures_fns(&mut file));
+    }
+    if let ConvertMode::IntoSync = convert_mode {
+        params.stream_traits_set(streams::imported_stream_traits(&mut file));
+    }
+
+    let mut errors: Option<syn::Error> = None;
+
+    for item in &mut file.items {
+        let err = match item {
+            syn::Item::Impl(item) => convert_impl(params, item, convert_mode),
+            syn::Item::Struct(item) => convert_struct(params, item, convert_mode),
+            syn::Item::Enum(item) => convert_enum(params, item, convert_mode),
+            syn::Item::Trait(item) => convert_trait(params, item, convert_mode),
+            syn::Item::Fn(item) => convert_fn(params, item, convert_mode),
+            syn::Item::Use(item) => convert_use(params, item, convert_mode),
+            syn::Item::Mod(item) => convert_mod(params, item, convert_mode),
+            syn::Item::Type(item) => convert_type(params, item, convert_mode),
+            syn::Item::Const(item) => convert_const(params, item, convert_mode),
+            syn::Item::Static(item) => convert_static(params, item, convert_mode),
+            syn::Item::Union(item) => convert_union(params, item, convert_mode),
+            syn::Item::Macro(item) => convert_macro(params, item, convert_mode),
+            syn::Item::ForeignMod(item) => convert_foreign_mod(params, item, convert_mode),
+            _ => {
+                return Err(syn::Error::new(
+                    item.span(),
+                    "Allowed impl, struct, enum, union, trait, fn, mod, use, type, const, static, \
+                    macro or extern items only"
+                ));
+            }
+        };
+
+        if let Some(err) = err {
+            match &mut errors {
+                Some(errors) => errors.combine(err),
+                None => errors = Some(err),
+            }
+        }
+    }
+    // leftovers are expected if the convertation has failed; they are warnings unless `validate`
+    // is specified
+    let mut warnings = TokenStream2::new();
+    if let (ConvertMode::IntoSync, None) = (convert_mode, &errors) {
+        match validate::validate_sync(params, &file) {
+            Some(err) if params.validate_get() => errors = Some(err),
+            Some(err) if !params.allow_async_leftovers_get() => {
+                for err in err {
+                    warnings.extend(deprecated_warning("async_leftover", &err.to_string(), err.span()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let mut ts = quote!(#file);
+    ts.extend(warnings);
+
+    dump_tokens2!("convert after", &ts);
+    Ok(ts)
+}
+
+fn unused_ident_warning(name: &str, span: Span) -> TokenStream2 {
+    let note = format!(
+        "`{}` in `idents(...)` does not match anything; remove it or mark it with `allow_unused`",
+        name
+    );
+    deprecated_warning("unused_ident", &note, span)
+}
+
+// There is no way to emit a warning from a proc macro on stable, so the use of a deprecated
+// constant is produced instead. It is a deliberate workaround: rustc reports it as "use of
+// deprecated constant `_::name`" followed by the note, pointing to the span, and it is controlled
+// by the `deprecated` lint
+fn deprecated_warning(name: &str, note: &str, span: Span) -> TokenStream2 {
+    let ident = Ident::new(name, span);
+
+    quote_spanned! {span=>
+        const _: () = {
+            #[deprecated(note = #note)]
+            #[allow(non_upper_case_globals)]
+            const #ident: () = ();
+            #ident
+        };
+    }
+}
+
+fn convert_impl(params: &mut MacroParameters, item: &mut syn::ItemImpl, convert_mode: ConvertMode) -> Option<syn::Error> {
+    match &mut *item.self_ty {
+        syn::Type::Path(syn::TypePath { path, .. }) => {
+            if let Some(last) = path.segments.last_mut() {
+                params.original_self_name_set(last.ident.to_string(), IdentCase::Camel);
+            }
+        }
+        _ => {}
+    };
+
+    if !params.recursive_asyncness_removal_get() {
+        remove_asyncness_on_impl(
+            item,
+            convert_mode,
+            params.send_get(),
+            params.trait_backend_get(),
+        );
+    }
+
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_impl_mut(item);
+    visitor.take_error()
+}
+
+fn convert_struct(params: &mut MacroParameters, item: &mut syn::ItemStruct, convert_mode: ConvertMode) -> Option<syn::Error> {
+    params.original_self_name_set(item.ident.to_string(), IdentCase::Camel);
+
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_struct_mut(item);
+    visitor.take_error()
+}
+
+fn convert_enum(params: &mut MacroParameters, item: &mut syn::ItemEnum, convert_mode: ConvertMode) -> Option<syn::Error> {
+    params.original_self_name_set(item.ident.to_string(), IdentCase::Camel);
+
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_enum_mut(item);
+    visitor.take_error()
+}
+
+fn convert_trait(params: &mut MacroParameters, item: &mut syn::ItemTrait, convert_mode: ConvertMode) -> Option<syn::Error> {
+    params.original_self_name_set(item.ident.to_string(), IdentCase::Camel);
+
+    if !params.recursive_asyncness_removal_get() {
+        remove_asyncness_on_trait(
+            item,
+            convert_mode,
+            params.send_get(),
+            params.trait_backend_get(),
+        );
+    }
+
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_trait_mut(item);
+    visitor.take_error()
+}
+
+fn convert_fn(params: &mut MacroParameters, item: &mut syn::ItemFn, convert_mode: ConvertMode) -> Option<syn::Error> {
+    params.original_self_name_set(item.sig.ident.to_string(), IdentCase::Snake);
+
+    if !params.recursive_asyncness_removal_get() {
+        remove_asyncness_on_fn(item, convert_mode);
+    }
+
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_fn_mut(item);
+    visitor.take_error()
+}
+
+fn convert_use(params: &mut MacroParameters, item: &mut syn::ItemUse, convert_mode: ConvertMode) -> Option<syn::Error> {
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_use_mut(item);
+    visitor.take_error()
+}
+
+fn convert_mod(params: &mut MacroParameters, item: &mut syn::ItemMod, convert_mode: ConvertMode) -> Option<syn::Error> {
+    params.original_self_name_set(item.ident.to_string(), IdentCase::Snake);
+
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_mod_mut(item);
+    visitor.take_error()
+}
+
+fn convert_type(params: &mut MacroParameters, item: &mut syn::ItemType, convert_mode: ConvertMode) -> Option<syn::Error> {
+    params.original_self_name_set(item.ident.to_string(), IdentCase::Camel);
+
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_type_mut(item);
+    visitor.take_error()
+}
+
+fn convert_const(params: &mut MacroParameters, item: &mut syn::ItemConst, convert_mode: ConvertMode) -> Option<syn::Error> {
+    params.original_self_name_set(item.ident.to_string(), IdentCase::Screaming);
+
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_const_mut(item);
+    visitor.take_error()
+}
+
+fn convert_static(params: &mut MacroParameters, item: &mut syn::ItemStatic, convert_mode: ConvertMode) -> Option<syn::Error> {
+    params.original_self_name_set(item.ident.to_string(), IdentCase::Screaming);
+
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_static_mut(item);
+    visitor.take_error()
+}
+
+fn convert_union(params: &mut MacroParameters, item: &mut syn::ItemUnion, convert_mode: ConvertMode) -> Option<syn::Error> {
+    params.original_self_name_set(item.ident.to_string(), IdentCase::Camel);
+
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_union_mut(item);
+    visitor.take_error()
+}
+
+fn convert_macro(params: &mut MacroParameters, item: &mut syn::ItemMacro, convert_mode: ConvertMode) -> Option<syn::Error> {
+    // `macro_rules! name { ... }`
+    if let Some(ident) = &item.ident {
+        params.original_self_name_set(ident.to_string(), IdentCase::Snake);
+    }
+
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_macro_mut(item);
+    visitor.take_error()
+}
+
+fn convert_foreign_mod(params: &mut MacroParameters, item: &mut syn::ItemForeignMod, convert_mode: ConvertMode) -> Option<syn::Error> {
+    let originals: Vec<Option<Ident>> = item.items.iter().map(foreign_item_ident).map(|i| i.cloned()).collect();
+
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_foreign_mod_mut(item);
+    if let Some(err) = visitor.take_error() {
+        return Some(err);
+    }
+
+    // the renamed items must still be linked to the original symbols
+    for (foreign_item, original) in item.items.iter_mut().zip(originals) {
+        let original = match original {
+            Some(original) if foreign_item_ident(foreign_item) != Some(&original) => original,
+            _ => continue,
+        };
+
+        let attrs = match foreign_item {
+            syn::ForeignItem::Fn(item) => &mut item.attrs,
+            syn::ForeignItem::Static(item) => &mut item.attrs,
+            _ => continue,
+        };
+        if !attrs.iter().any(|attr| attr.path.is_ident("link_name")) {
+            let name = original.to_string();
+            attrs.push(syn::parse_quote!(#[link_name = #name]));
+        }
+    }
+
+    None
+}
+
+fn foreign_item_ident(item: &syn::ForeignItem) -> Option<&Ident> {
+    match item {
+        syn::ForeignItem::Fn(item) => Some(&item.sig.ident),
+        syn::ForeignItem::Static(item) => Some(&item.ident),
+        _ => None,
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub fn content(body: TokenStream2) -> syn::Result<TokenStream2> {
+    dump_tokens!("content before", &body);
+
+    let mut visitor = Visitor::new(ContentVisitor::new());
+    let mut ts = visitor.process(body)?;
+
+    if let Some(path) = visitor.inner.params.config_file_get() {
+        ts.extend(config::track_file(path));
+    }
+
+    dump_tokens!("content after", &ts);
+    Ok(ts)
+}
@@ -0,0 +1,2055 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+#[allow(unused_imports)]
+use std::iter::FromIterator;
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use syn::{
+    parse::Parser, punctuated::Punctuated, spanned::Spanned, token::Comma, Attribute, Ident, Lit,
+    LitStr, Meta, MetaNameValue, NestedMeta, MetaList, 
+};
+
+use crate::{
+    DEFAULT_CRATE_NAME, STANDARD_MACROS,
+    blocking::Executor,
+    config,
+    io::IO_PATHS,
+    paths::{path_to_string, Runtime},
+    utils::*,
+};
+
+const MODE_INTO_ASYNC: &'static str = "__into_async";
+const MODE_INTO_SYNC: &'static str = "__into_sync";
+const MODE_INTO_BLOCKING: &str = "__into_blocking";
+const TARGET_SUFFIX_NAME: &str = "__target_suffix";
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy)]
+#[allow(clippy::enum_variant_names)]
+pub enum ConvertMode {
+    IntoSync,
+    IntoAsync,
+    IntoBlocking,
+}
+
+impl ConvertMode {
+    fn from_str<S: AsRef<str>>(s: S) -> Option<Self> {
+        match s.as_ref() {
+            "sync" => Some(Self::IntoSync),
+            "async" => Some(Self::IntoAsync),
+            "blocking" => Some(Self::IntoBlocking),
+            _ => None,
+        }
+    }
+
+    fn to_str(&self) -> &'static str {
+        match self {
+            Self::IntoSync => "sync",
+            Self::IntoAsync => "async",
+            Self::IntoBlocking => "blocking",
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraitBackend {
+    // `#[async_trait::async_trait]` on traits and impls
+    AsyncTrait,
+    // `async fn` in traits as is, or desugared into `-> impl Future + Send`
+    Native,
+    // `#[trait_variant::make(Send)]` on traits
+    TraitVariant,
+}
+
+impl TraitBackend {
+    fn from_str<S: AsRef<str>>(s: S) -> Option<Self> {
+        match s.as_ref() {
+            "async_trait" | "async-trait" => Some(Self::AsyncTrait),
+            "native" => Some(Self::Native),
+            "trait_variant" | "trait-variant" => Some(Self::TraitVariant),
+            _ => None,
+        }
+    }
+
+    fn to_str(self) -> &'static str {
+        match self {
+            Self::AsyncTrait => "async_trait",
+            Self::Native => "native",
+            Self::TraitVariant => "trait_variant",
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdentCase {
+    // `Foo` -> `FooSync`, `FooAsync`
+    Camel,
+    // `foo` -> `foo_sync`, `foo_async`
+    Snake,
+    // `FOO` -> `FOO_SYNC`, `FOO_ASYNC`
+    Screaming,
+}
+
+// `AsyncStd` -> `async_std`
+fn camel_to_snake(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 4);
+    for (i, c) in s.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 && !res.ends_with('_') {
+                res.push('_');
+            }
+            res.extend(c.to_lowercase());
+        } else {
+            res.push(c);
+        }
+    }
+    res
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone)]
+pub struct IdentRecord {
+    pub case: IdentCase,
+    pub use_mode: bool,
+    pub keep: bool,
+    pub ident_sync: Option<String>,
+    pub ident_async: Option<String>,
+    pub idents: Option<HashMap<String, String>>,
+    pub allow_unused: bool,
+    // the name in `idents(...)`, used to report the record that never matched
+    pub span: Option<Span>,
+}
+
+impl IdentRecord {
+    pub fn new() -> Self {
+        Self {
+            case: IdentCase::Camel,
+            use_mode: false,
+            keep: false,
+            ident_sync: None,
+            ident_async: None,
+            idents: None,
+            allow_unused: false,
+            span: None,
+        }
+    }
+
+    pub fn with_case( case: IdentCase ) -> Self {
+        Self {
+            case,
+            use_mode: false,
+            keep: false,
+            ident_sync: None,
+            ident_async: None,
+            idents: None,
+            allow_unused: false,
+            span: None,
+        }
+    }
+
+    pub fn ident_add_suffix(
+        &self,
+        ident: &Ident,
+        convert_mode: ConvertMode,
+        version_name: Option<&str>,
+        version_suffix: Option<&str>,
+    ) -> Ident {
+        if self.keep {
+            return ident.clone();
+        }
+
+        if let Some(version_name) = version_name {
+            if let Some(idents) = self.idents.as_ref() {
+                if let Some(value) = idents.get(version_name) {
+                    return Ident::new(value, ident.span());
+                }
+            }
+        }
+
+        match convert_mode {
+            ConvertMode::IntoSync => {
+                if let Some(name) = &self.ident_sync {
+                    return Ident::new(&name, ident.span());
+                }
+            }
+            ConvertMode::IntoAsync => {
+                if let Some(name) = &self.ident_async {
+                    return Ident::new(&name, ident.span());
+                }
+            }
+            ConvertMode::IntoBlocking => {}
+        };
+
+        if let Some(suffix) = version_suffix {
+            let suffix = match self.case {
+                IdentCase::Camel => suffix.to_string(),
+                IdentCase::Snake => format!("_{}", camel_to_snake(suffix)),
+                IdentCase::Screaming => format!("_{}", camel_to_snake(suffix).to_uppercase()),
+            };
+            return Ident::new(&format!("{}{}", ident, suffix), ident.span());
+        }
+
+        let suffix = match (self.case, convert_mode) {
+            (IdentCase::Camel, ConvertMode::IntoAsync) => "Async",
+            (IdentCase::Camel, ConvertMode::IntoSync) => "Sync",
+            (IdentCase::Snake, ConvertMode::IntoAsync) => "_async",
+            (IdentCase::Snake, ConvertMode::IntoSync) => "_sync",
+            (IdentCase::Screaming, ConvertMode::IntoAsync) => "_ASYNC",
+            (IdentCase::Screaming, ConvertMode::IntoSync) => "_SYNC",
+            (IdentCase::Camel, ConvertMode::IntoBlocking) => "Blocking",
+            (IdentCase::Snake, ConvertMode::IntoBlocking) => "_blocking",
+            (IdentCase::Screaming, ConvertMode::IntoBlocking) => "_BLOCKING",
+        };
+
+        Ident::new(&format!("{}{}", ident, suffix), ident.span())
+    }
+
+    pub fn to_nestedmeta(&self, name: &str) -> syn::NestedMeta {
+        let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+        
+        match self.case {
+            IdentCase::Camel => {}
+            IdentCase::Snake => {
+                nested.push(syn::NestedMeta::Meta(syn::Meta::Path(make_path("snake"))));
+            }
+            IdentCase::Screaming => {
+                nested.push(syn::NestedMeta::Meta(syn::Meta::Path(make_path("upper"))));
+            }
+        };
+    
+        if self.use_mode {
+            nested.push(syn::NestedMeta::Meta(syn::Meta::Path(make_path("use"))));
+        };
+    
+        if self.keep {
+            nested.push(syn::NestedMeta::Meta(syn::Meta::Path(make_path("keep"))));
+        };
+
+        if self.allow_unused {
+            nested.push(syn::NestedMeta::Meta(syn::Meta::Path(make_path("allow_unused"))));
+        };
+    
+        if let Some(value) = &self.ident_async {
+            if value == name {
+                nested.push(syn::NestedMeta::Meta(syn::Meta::Path(make_path("async"))));
+            } else {
+                nested.push(make_nestedmeta_namevalue("async", value.as_str()));
+            }
+        };
+        if let Some(value) = &self.ident_sync {
+            if value == name {
+                nested.push(syn::NestedMeta::Meta(syn::Meta::Path(make_path("sync"))));
+            } else {
+                nested.push(make_nestedmeta_namevalue("sync", value.as_str()));
+            }
+        };
+    
+        if let Some(idents) = &self.idents {
+            for (key, value) in idents {
+                nested.push(make_nestedmeta_namevalue(key.as_str(), value.as_str()));
+            }
+        };
+    
+        // the span is kept, so the warning points to the name even after the re-serialization
+        let span = self.span.unwrap_or_else(Span::call_site);
+        let path = syn::Path::from(Ident::new(name, span));
+        if nested.is_empty() {
+            syn::NestedMeta::Meta(syn::Meta::Path(path))
+        } else {
+            syn::NestedMeta::Meta(syn::Meta::List(syn::MetaList {
+                path,
+                paren_token: syn::token::Paren(span),
+                nested,
+            }))
+        }
+    }    
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdentPatternKind {
+    Regex,
+    Prefix,
+    Suffix,
+}
+
+impl IdentPatternKind {
+    fn from_str<S: AsRef<str>>(s: S) -> Option<Self> {
+        match s.as_ref() {
+            "regex" => Some(Self::Regex),
+            "prefix" => Some(Self::Prefix),
+            "suffix" => Some(Self::Suffix),
+            _ => None,
+        }
+    }
+
+    fn to_str(self) -> &'static str {
+        match self {
+            Self::Regex => "regex",
+            Self::Prefix => "prefix",
+            Self::Suffix => "suffix",
+        }
+    }
+}
+
+// `Foo` -> Camel, `foo` -> Snake, `FOO` -> Screaming
+fn ident_case_of(s: &str) -> IdentCase {
+    if !s.chars().any(|c| c.is_lowercase()) {
+        IdentCase::Screaming
+    } else if s.starts_with(|c: char| c.is_uppercase()) {
+        IdentCase::Camel
+    } else {
+        IdentCase::Snake
+    }
+}
+
+// Renames all the idents matching the pattern:
+//
+// - `idents(regex = "^(.*)Client$")`: `HttpClient` -> `HttpClientSync`, `HttpClientAsync`;
+//
+// - `idents(prefix = "Maybe")`: `MaybeClient` -> `ClientSync`, `ClientAsync`;
+//
+// - `idents(suffix = "Async", sync = "Blocking")`: `ClientAsync` -> `ClientBlocking`, `ClientAsync`.
+//
+// For `sync = "..."`, `async = "..."` and `<key> = "..."` values of regex patterns, `$1`, `$name`
+// are replaced with the captured groups. Prefix and suffix patterns without `$` in the value just
+// replace the prefix or the suffix.
+#[derive(Debug, Clone)]
+pub struct IdentPattern {
+    kind: IdentPatternKind,
+    pattern: String,
+    regex: regex::Regex,
+    ident_sync: Option<String>,
+    ident_async: Option<String>,
+    idents: HashMap<String, String>,
+}
+
+impl IdentPattern {
+    pub fn new(kind: IdentPatternKind, pattern: String) -> Result<Self, regex::Error> {
+        let source = match kind {
+            IdentPatternKind::Regex => pattern.clone(),
+            IdentPatternKind::Prefix => format!("^{}(.+)$", regex::escape(&pattern)),
+            IdentPatternKind::Suffix => format!("^(.+){}$", regex::escape(&pattern)),
+        };
+
+        Ok(Self {
+            kind,
+            pattern,
+            regex: regex::Regex::new(&source)?,
+            ident_sync: None,
+            ident_async: None,
+            idents: HashMap::new(),
+        })
+    }
+
+    pub fn rename(
+        &self,
+        ident: &Ident,
+        convert_mode: ConvertMode,
+        version_name: Option<&str>,
+        version_suffix: Option<&str>,
+    ) -> Option<Ident> {
+        let name = ident.to_string();
+        let captures = self.regex.captures(&name)?;
+
+        let template = version_name
+            .and_then(|key| self.idents.get(key))
+            .or(match convert_mode {
+                ConvertMode::IntoSync => self.ident_sync.as_ref(),
+                ConvertMode::IntoAsync => self.ident_async.as_ref(),
+                ConvertMode::IntoBlocking => None,
+            });
+
+        // the ident without the prefix or the suffix
+        let base = match self.kind {
+            IdentPatternKind::Regex => name.as_str(),
+            IdentPatternKind::Prefix | IdentPatternKind::Suffix => captures.get(1)?.as_str(),
+        };
+
+        let new_name = match (template, self.kind) {
+            (Some(template), IdentPatternKind::Prefix) if !template.contains('$') => {
+                format!("{}{}", template, base)
+            }
+            (Some(template), IdentPatternKind::Suffix) if !template.contains('$') => {
+                format!("{}{}", base, template)
+            }
+            (Some(template), _) => {
+                let mut new_name = String::new();
+                captures.expand(template, &mut new_name);
+                new_name
+            }
+            (None, _) => {
+                let base = syn::parse_str::<Ident>(base).ok()?;
+                IdentRecord::with_case(ident_case_of(&name))
+                    .ident_add_suffix(&base, convert_mode, version_name, version_suffix)
+                    .to_string()
+            }
+        };
+
+        let mut new_ident = syn::parse_str::<Ident>(&new_name).ok()?;
+        new_ident.set_span(ident.span());
+        Some(new_ident)
+    }
+
+    pub fn to_nestedmeta(&self) -> syn::NestedMeta {
+        let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+
+        nested.push(make_nestedmeta_namevalue(self.kind.to_str(), self.pattern.as_str()));
+        if let Some(value) = &self.ident_sync {
+            nested.push(make_nestedmeta_namevalue("sync", value.as_str()));
+        }
+        if let Some(value) = &self.ident_async {
+            nested.push(make_nestedmeta_namevalue("async", value.as_str()));
+        }
+        for (key, value) in &self.idents {
+            nested.push(make_nestedmeta_namevalue(key.as_str(), value.as_str()));
+        }
+
+        make_nestedmeta_list("idents", nested)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone)]
+pub struct MacroParameterVersion {
+    pub kind: ConvertMode,
+    pub params: MacroParameters,
+}
+
+#[derive(Clone)]
+pub struct MacroParameters {
+    mode: Option<ConvertMode>,
+    disable: bool,
+    key: Option<String>,
+    suffix: Option<String>,
+    self_name: Option<String>,
+    keep_self: bool,
+    // the name of the item before the convertation
+    original_self_name: Option<String>,
+    // settings
+    prefix: Option<String>,
+    idents: HashMap<String, IdentRecord>,
+    ident_patterns: Vec<IdentPattern>,
+    allow_unused_idents: bool,
+    // names of the `idents` records applied during the convertation
+    used_idents: RefCell<HashSet<String>>,
+    send: Option<bool>,
+    trait_backend: Option<TraitBackend>,
+    recursive_asyncness_removal: bool,
+    streams: bool,
+    io: bool,
+    unwrap_locks: bool,
+    convert_doctests: bool,
+    validate: bool,
+    allow_async_leftovers: bool,
+    runtime: Option<Runtime>,
+    paths: HashMap<String, String>,
+    macros: Vec<String>,
+    // blocking
+    executor: Option<Executor>,
+    block_on: Option<String>,
+    target: Option<String>,
+    target_suffix: Option<String>,
+    // groups
+    cfg: Option<Meta>,
+    outer_attrs: Punctuated<NestedMeta, Comma>,
+    inner_attrs: Punctuated<NestedMeta, Comma>,
+    drop_attrs: Vec<String>,
+    replace_features: HashMap<String, String>,
+    // the config file the profile was loaded from
+    config_file: Option<PathBuf>,
+    // the I/O traits imported by the item from the known paths (see `io`)
+    io_traits: Vec<String>,
+    futures_fns: Vec<(String, String)>,
+    stream_traits: Vec<String>,
+    // versions
+    pub versions: Vec<MacroParameterVersion>,
+}
+
+impl std::fmt::Debug for MacroParameters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {    
+        f.debug_struct("MacroParameters")
+           .field("mode", &self.mode)
+           .field("disable", &self.disable)
+           .field("key", &self.key)
+           .field("suffix", &self.suffix)
+           .field("self_name", &self.self_name)
+           .field("prefix", &self.prefix)
+           .field("idents", &self.idents)
+           .field("ident_patterns", &self.ident_patterns)
+           .field("allow_unused_idents", &self.allow_unused_idents)
+           .field("send", &self.send)
+           .field("trait_backend", &self.trait_backend)
+           .field("recursive_asyncness_removal", &self.recursive_asyncness_removal)
+           .field("keep_self", &self.keep_self)
+           .field("streams", &self.streams)
+           .field("io", &self.io)
+           .field("unwrap_locks", &self.unwrap_locks)
+           .field("convert_doctests", &self.convert_doctests)
+           .field("validate", &self.validate)
+           .field("allow_async_leftovers", &self.allow_async_leftovers)
+           .field("runtime", &self.runtime)
+           .field("paths", &self.paths)
+           .field("macros", &self.macros)
+           .field("executor", &self.executor)
+           .field("block_on", &self.block_on)
+           .field("target", &self.target)
+           .field("target_suffix", &self.target_suffix)
+           .field("cfg", &OptionToTokens(self.cfg.as_ref()))
+           .field("outer_attrs", &DebugByDisplay(self.outer_attrs.to_token_stream()))
+           .field("inner_attrs", &DebugByDisplay(self.inner_attrs.to_token_stream()))
+           .field("outer_attrs", &DebugByDisplay(self.outer_attrs.to_token_stream()))
+           .field("drop_attrs", &self.drop_attrs)
+           .field("replace_features", &self.replace_features)
+           .field("config_file", &self.config_file)
+           .field("io_traits", &self.io_traits)
+           .field("futures_fns", &self.futures_fns)
+           .field("stream_traits", &self.stream_traits)
+           .field("versions", &self.versions)
+           .finish()
+        }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+macro_rules! lit_str {
+    ($lit:ident, $obj:expr, $fn:ident, $msg:expr) => {
+        match $lit {
+            syn::Lit::Str(str_val) => $obj.$fn(str_val.value())?,
+            _ => return Err(syn::Error::new_spanned($lit.to_token_stream(), $msg)),
+        }
+    };
+}
+
+macro_rules! lit_meta {
+    ($lit:ident, $meta:expr, $obj:expr, $fn:ident, $msg:expr) => {
+        match $lit {
+            syn::Lit::Str(_) => $obj.$fn($meta)?,
+            _ => return Err(syn::Error::new_spanned($lit.to_token_stream(), $msg)),
+        }
+    };
+}
+
+impl MacroParameters {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        MacroParametersBuilder::new().build().unwrap()
+    }
+
+    fn from_args<'i>(args: impl IntoIterator<Item = &'i NestedMeta>) -> syn::Result<Self> {
+        Self::parse_args(args)?.build()
+    }
+
+    fn parse_args<'i>(args: impl IntoIterator<Item = &'i NestedMeta>) -> syn::Result<MacroParametersBuilder> {
+        let mut builder = MacroParametersBuilder::new();
+
+        for arg in args {
+            match arg {
+                syn::NestedMeta::Meta(meta) => match meta {
+                    syn::Meta::NameValue(syn::MetaNameValue { path, lit, .. }) => {
+                        let name = path
+                            .get_ident()
+                            .ok_or(syn::Error::new_spanned(
+                                path.to_token_stream(),
+                                "Expected name",
+                            ))?
+                            .to_string();
+                        match name.as_str() {
+                            "key" => lit_str!(lit, builder, key, "Expected string literal"),
+                            "suffix" => lit_str!(lit, builder, suffix, "Expected string literal"),
+                            "self" => lit_str!(lit, builder, self_name, "Expected string literal"),
+                            "prefix" => lit_str!(lit, builder, prefix, "Expected string literal"),
+                            "send" => lit_str!(lit, builder, send, "Expected string literal"),
+                            "trait_backend" => lit_str!(lit, builder, trait_backend, "Expected string literal"),
+                            "runtime" => lit_str!(lit, builder, runtime, "Expected string literal"),
+                            "executor" => lit_str!(lit, builder, executor, "Expected string literal"),
+                            "block_on" => lit_str!(lit, builder, block_on, "Expected string literal"),
+                            "target" => lit_str!(lit, builder, target, "Expected string literal"),
+                            TARGET_SUFFIX_NAME => lit_str!(lit, builder, target_suffix, "Expected string literal"),
+                            "feature" => lit_meta!(lit, meta, builder, feature, "Expected string literal"),
+                            "profile" => match lit {
+                                syn::Lit::Str(lit) => builder.profile(lit)?,
+                                _ => {
+                                    return Err(syn::Error::new_spanned(
+                                        lit.to_token_stream(),
+                                        "Expected string literal",
+                                    ))
+                                }
+                            },
+                            _ => {
+                                return Err(syn::Error::new_spanned(
+                                    meta.to_token_stream(),
+                                    format!("Wrong name for name-value pair: {}", &name),
+                                ))
+                            }
+                        }
+                    }
+                    syn::Meta::List(list) => {
+                        let name = list
+                            .path
+                            .get_ident()
+                            .ok_or(syn::Error::new_spanned(
+                                list.path.to_token_stream(),
+                                "Expected name",
+                            ))?
+                            .to_string();
+                        match name.as_str() {
+                            "cfg" => builder.cfg_list(list)?,
+                            "idents" => MacroParametersBuilder::idents(
+                                &mut builder.params.idents,
+                                &mut builder.params.ident_patterns,
+                                &list.nested,
+                            )?,
+                            "any" | "all" | "not" => builder.cfg_meta(meta)?,
+                            "outer" => builder.outer_attrs(&list.nested)?,
+                            "inner" => builder.inner_attrs(&list.nested)?,
+                            "replace_feature" => builder.replace_feature(&list.nested)?,
+                            "drop_attrs" => builder.drop_attrs(&list.nested)?,
+                            "paths" => builder.paths(&list.nested)?,
+                            "macros" => builder.macros(&list.nested)?,
+                            name @ _ => builder.version_or_inner_attr(name, &list.nested, meta)?,
+                        }
+                    }
+                    syn::Meta::Path(path) => {
+                        if let Some(name) = path.get_ident().map(|i| i.to_string()) {
+                            match name.as_str() {
+                                MODE_INTO_ASYNC => builder.mode_into_async()?,
+                                MODE_INTO_SYNC => builder.mode_into_sync()?,
+                                MODE_INTO_BLOCKING => builder.mode_into_blocking()?,
+                                "disable" => builder.disable(),
+                                "keep_self" => builder.keep_self(),
+                                "streams" => builder.streams(),
+                                "io" => builder.io(),
+                                "unwrap_locks" => builder.unwrap_locks(),
+                                "convert_doctests" => builder.convert_doctests(),
+                                "allow_unused_idents" => builder.allow_unused_idents(),
+                                "validate" => builder.validate(),
+                                "allow_async_leftovers" => builder.allow_async_leftovers(),
+                                _ => builder.inner_attr(meta)?,
+                            }
+                        } else {
+                            builder.inner_attr(meta)?    
+                        }
+                    }
+                },
+                syn::NestedMeta::Lit(lit) => {
+                    lit_meta!(lit, lit, builder, inner_attr_str, "Expected string literal")
+                }
+            }
+        }
+
+        Ok(builder)
+    }
+
+    pub fn from_tokens(tokens: TokenStream2) -> syn::Result<Self> {
+        let args = match Punctuated::<NestedMeta, Comma>::parse_terminated.parse2(tokens) {
+            Ok(a) => a,
+            Err(e) => return Err(e),
+        };
+
+        Self::from_args(&args)
+    }
+
+    pub fn from_tokens_in_parens(tokens: TokenStream2) -> syn::Result<Self> {
+        let aip = match syn::parse2::<AttributeArgsInParens>(tokens) {
+            Ok(a) => a,
+            Err(e) => {
+                return Err(e);
+            }
+        };
+
+        Self::from_args(&aip.args)
+    }
+
+    pub fn to_nestedmeta(&self, add_mode: Option<ConvertMode>) -> Punctuated<syn::NestedMeta, syn::token::Comma> {
+        let mut args = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+
+        let mode = if let Some(mode) = add_mode { 
+            Some(mode)
+        } else if let Some(mode) = self.mode {
+            Some(mode)
+        } else {
+            None
+        };
+        
+        if let Some(mode) = mode { 
+            match mode {
+                ConvertMode::IntoAsync => args.push(NestedMeta::Meta(Meta::Path(make_path(MODE_INTO_ASYNC)))),
+                ConvertMode::IntoSync => args.push(NestedMeta::Meta(Meta::Path(make_path(MODE_INTO_SYNC)))),
+                ConvertMode::IntoBlocking => args.push(NestedMeta::Meta(Meta::Path(make_path(MODE_INTO_BLOCKING)))),
+            }
+        }
+
+        if self.disable {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("disable"))));
+        }
+
+        if self.keep_self {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("keep_self"))));
+        }
+
+        if self.streams {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("streams"))));
+        }
+
+        if self.io {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("io"))));
+        }
+
+        if self.unwrap_locks {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("unwrap_locks"))));
+        }
+
+        if self.convert_doctests {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("convert_doctests"))));
+        }
+
+        if self.allow_unused_idents {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("allow_unused_idents"))));
+        }
+
+        if self.validate {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("validate"))));
+        }
+
+        if self.allow_async_leftovers {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("allow_async_leftovers"))));
+        }
+
+        if let Some(key) = &self.key {
+            args.push(make_nestedmeta_namevalue("key", key.as_str()));
+        }
+
+        if let Some(suffix) = &self.suffix {
+            args.push(make_nestedmeta_namevalue("suffix", suffix.as_str()));
+        }
+
+        if let Some(self_name) = &self.self_name {
+            args.push(make_nestedmeta_namevalue("self", self_name.as_str()));
+        }
+
+        if let Some(prefix) = &self.prefix {
+            args.push(make_nestedmeta_namevalue("prefix", prefix.as_str()));
+        }
+
+        if let Some(send) = &self.send {
+            args.push(make_nestedmeta_namevalue(
+                "send",
+                if *send { "Send" } else { "?Send" },
+            ));
+        }
+
+        if let Some(trait_backend) = &self.trait_backend {
+            args.push(make_nestedmeta_namevalue("trait_backend", trait_backend.to_str()));
+        }
+
+        if let Some(runtime) = &self.runtime {
+            args.push(make_nestedmeta_namevalue("runtime", runtime.to_str()));
+        }
+
+        if let Some(executor) = &self.executor {
+            args.push(make_nestedmeta_namevalue("executor", executor.to_str()));
+        }
+
+        if let Some(block_on) = &self.block_on {
+            args.push(make_nestedmeta_namevalue("block_on", block_on.as_str()));
+        }
+
+        if let Some(target) = &self.target {
+            args.push(make_nestedmeta_namevalue("target", target.as_str()));
+        }
+
+        if let Some(target_suffix) = &self.target_suffix {
+            args.push(make_nestedmeta_namevalue(TARGET_SUFFIX_NAME, target_suffix.as_str()));
+        }
+
+        if let Some(cfg) = &self.cfg {
+            let mut nested = Punctuated::new();
+            nested.push(NestedMeta::Meta(cfg.clone()));
+            args.push(make_nestedmeta_list("cfg", nested));
+        }
+
+        if !self.outer_attrs.is_empty() {
+            args.push(make_nestedmeta_list("outer", self.outer_attrs.clone()));
+        }
+
+        if !self.inner_attrs.is_empty() {
+            args.push(make_nestedmeta_list("inner", self.inner_attrs.clone()));
+        }
+
+        if !self.idents.is_empty() {
+            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+            for (name, value) in &self.idents {
+                nested.push(value.to_nestedmeta(name.as_str()));
+            }
+            let arg = make_nestedmeta_list("idents", nested);
+            args.push(arg);
+        }
+
+        for pattern in &self.ident_patterns {
+            args.push(pattern.to_nestedmeta());
+        }
+
+        if !self.drop_attrs.is_empty() {
+            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+            for name in &self.drop_attrs {
+                nested.push(NestedMeta::Meta(Meta::Path(make_path(name.as_str()))));
+            }
+            let arg = make_nestedmeta_list("drop_attrs", nested);
+            args.push(arg);
+        }
+
+        if !self.paths.is_empty() {
+            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+            for (name, value) in &self.paths {
+                nested.push(NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path: syn::parse_str(name).unwrap(),
+                    eq_token: syn::Token![=](Span::call_site()),
+                    lit: Lit::Str(LitStr::new(value.as_str(), Span::call_site())),
+                })));
+            }
+            let arg = make_nestedmeta_list("paths", nested);
+            args.push(arg);
+        }
+
+        if !self.macros.is_empty() {
+            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+            for name in &self.macros {
+                nested.push(NestedMeta::Meta(Meta::Path(syn::parse_str(name).unwrap())));
+            }
+            let arg = make_nestedmeta_list("macros", nested);
+            args.push(arg);
+        }
+
+        if !self.replace_features.is_empty() {
+            for (name, value) in &self.replace_features {
+                let mut inner = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+                inner.push(NestedMeta::Lit(Lit::Str(LitStr::new(
+                    name.as_str(),
+                    Span::call_site(),
+                ))));
+                inner.push(NestedMeta::Lit(Lit::Str(LitStr::new(
+                    value.as_str(),
+                    Span::call_site(),
+                ))));
+                let arg = make_nestedmeta_list("replace_feature", inner);
+                args.push(arg);
+            }
+        }
+
+        for version in &self.versions {
+            let (name, nested) = match version.kind {
+                ConvertMode::IntoSync | ConvertMode::IntoAsync | ConvertMode::IntoBlocking => {
+                    (version.kind.to_str(), version.params.to_nestedmeta(None))
+                }
+            };
+
+            let arg = make_nestedmeta_list(name, nested);
+            args.push(arg);
+        }
+
+        args
+    }
+
+    pub fn extend_tokenstream2_with_cfg_outer_attrs(
+        &self,
+        ts: &mut TokenStream2,
+    ) -> syn::Result<()> {
+        if let Some(cfg_cond) = &self.cfg {
+            let cfg_ts = cfg_cond.into_token_stream();
+            ts.extend(quote!(#[cfg(#cfg_ts)]));
+        };
+
+        for attr in &self.outer_attrs {
+            match attr {
+                NestedMeta::Meta(_) => {
+                    let attr_ts = attr.into_token_stream();
+                    ts.extend(quote!(#[#attr_ts]));
+                }
+                NestedMeta::Lit(syn::Lit::Str(s)) => {
+                    let attr_ts = make_attr_ts_from_str(s.value(), attr.span())?;
+                    ts.extend(attr_ts);
+                }
+                _ => {
+                    unreachable!()
+                }
+            };
+        }
+
+        Ok(())
+    }
+
+    pub fn extend_tokenstream2_with_inner_attrs(&self, ts: &mut TokenStream2) -> syn::Result<()> {
+        for attr in &self.inner_attrs {
+            match attr {
+                NestedMeta::Meta(_) => {
+                    let attr_ts = attr.into_token_stream();
+                    ts.extend(quote!(#[#attr_ts]));
+                }
+                NestedMeta::Lit(syn::Lit::Str(s)) => {
+                    let attr_ts = make_attr_ts_from_str(s.value(), attr.span())?;
+                    ts.extend(attr_ts);
+                }
+                _ => {
+                    unreachable!()
+                }
+            };
+        }
+
+        Ok(())
+    }
+
+    pub fn to_tokens(&self, add_mode: Option<ConvertMode>) -> TokenStream2 {
+        self.to_nestedmeta(add_mode).to_token_stream()
+    }
+
+    pub fn default_ident_record(&self, case: IdentCase) -> IdentRecord {
+        IdentRecord::with_case( case )
+    }
+
+    pub fn apply_parent(child: &mut MacroParameters, parent: &MacroParameters) -> syn::Result<()> {
+        if parent.disable {
+            child.disable = true;
+        }
+
+        if parent.keep_self {
+            child.keep_self = true;
+        }
+
+        if parent.streams {
+            child.streams = true;
+        }
+
+        if parent.io {
+            child.io = true;
+        }
+
+        if parent.unwrap_locks {
+            child.unwrap_locks = true;
+        }
+
+        if parent.convert_doctests {
+            child.convert_doctests = true;
+        }
+
+        if parent.allow_unused_idents {
+            child.allow_unused_idents = true;
+        }
+
+        if parent.validate {
+            child.validate = true;
+        }
+
+        if parent.allow_async_leftovers {
+            child.allow_async_leftovers = true;
+        }
+
+        // the entries of the child take precedence
+        for (name, ir) in &parent.idents {
+            child.idents.entry(name.clone()).or_insert_with(|| ir.clone());
+        }
+
+        if !parent.ident_patterns.is_empty() {
+            child.ident_patterns.extend(parent.ident_patterns.iter().cloned());
+        }
+
+        if child.send.is_none() {
+            child.send = parent.send;
+        }
+
+        if child.trait_backend.is_none() {
+            child.trait_backend = parent.trait_backend;
+        }
+
+        if child.runtime.is_none() {
+            child.runtime = parent.runtime;
+        }
+
+        for (name, value) in &parent.paths {
+            child.paths.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+
+        for name in &parent.macros {
+            if !child.macros.contains(name) {
+                child.macros.push(name.clone());
+            }
+        }
+
+        if !parent.drop_attrs.is_empty() {
+            let mut new_drop_attrs = parent.drop_attrs.clone();
+            new_drop_attrs.extend_from_slice(&child.drop_attrs);
+            child.drop_attrs = new_drop_attrs;
+        }
+
+        for (name, value) in &parent.replace_features {
+            child.replace_features.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+
+        Ok(())
+    }
+
+    pub fn disable_get(&self) -> bool {
+        self.disable
+    }
+
+    pub fn mode_get(&self) -> Option<ConvertMode> {
+        self.mode
+    }
+
+    pub fn key_get<'s>(&'s self) -> Option<&'s str> {
+        self.key.as_ref().map(|s| s.as_str())
+    }
+
+    pub fn suffix_get<'s>(&'s self) -> Option<&'s str> {
+        self.suffix.as_deref()
+    }
+
+    pub fn original_self_name_set<S: AsRef<str>>(&mut self, name: S, case: IdentCase) {
+        self.original_self_name = Some(name.as_ref().to_string());
+
+        if !self.keep_self {
+            if self.idents.get(name.as_ref()).is_none() {
+                let mut ir = self.default_ident_record(case);
+    
+                if let Some(key) = &self.key {
+                    if let Some(self_name) = &self.self_name {
+                        let mut idents = HashMap::new();
+                        idents.insert( key.clone(), self_name.clone() );
+                        ir.idents = Some(idents);
+                    }
+                }
+    
+                self.idents.insert(name.as_ref().to_string(), ir);
+            }
+        }
+    }
+
+    // The name of the item in this version
+    pub fn self_name_get(&self, convert_mode: ConvertMode) -> Option<String> {
+        let name = self.original_self_name.as_ref()?;
+        let ident = syn::parse_str::<Ident>(name).ok()?;
+        let ident = self.ident_rename(&ident, convert_mode).unwrap_or(ident);
+        Some(ident.to_string())
+    }
+
+    pub fn prefix_set(&mut self, prefix: String) {
+        self.prefix = Some(prefix);
+    }
+
+    pub fn prefix_get(&self) -> &str {
+        self.prefix
+            .as_ref()
+            .map(|s| s.as_str())
+            .unwrap_or(DEFAULT_CRATE_NAME)
+    }
+
+    pub fn send_get(&self) -> Option<bool> {
+        self.send
+    }
+
+    pub fn trait_backend_get(&self) -> Option<TraitBackend> {
+        self.trait_backend
+    }
+
+    pub fn recursive_asyncness_removal_get(&self) -> bool {
+        self.recursive_asyncness_removal
+    }
+
+    pub fn recursive_asyncness_removal_set(&mut self, recursive: bool) {
+        self.recursive_asyncness_removal = recursive;
+    }
+
+    pub fn streams_get(&self) -> bool {
+        self.streams
+    }
+
+    pub fn io_get(&self) -> bool {
+        self.io
+    }
+
+    pub fn io_traits_set(&mut self, names: Vec<String>) {
+        self.io_traits = names;
+    }
+
+    pub fn io_traits_get(&self) -> &[String] {
+        &self.io_traits
+    }
+
+    pub fn futures_fns_set(&mut self, names: Vec<(String, String)>) {
+        self.futures_fns = names;
+    }
+
+    pub fn futures_fns_get(&self) -> &[(String, String)] {
+        &self.futures_fns
+    }
+
+    pub fn stream_traits_set(&mut self, names: Vec<String>) {
+        self.stream_traits = names;
+    }
+
+    pub fn stream_traits_get(&self) -> &[String] {
+        &self.stream_traits
+    }
+
+    pub fn unwrap_locks_get(&self) -> bool {
+        self.unwrap_locks
+    }
+
+    pub fn convert_doctests_get(&self) -> bool {
+        self.convert_doctests
+    }
+
+    pub fn validate_get(&self) -> bool {
+        self.validate
+    }
+
+    pub fn allow_async_leftovers_get(&self) -> bool {
+        self.allow_async_leftovers
+    }
+
+    pub fn config_file_get(&self) -> Option<&Path> {
+        self.config_file.as_deref()
+    }
+
+    // Searches for the longest prefix of the path that should be replaced. Paths specified by the
+    // user take precedence over the runtime and `io` ones, which are used only for the sync version.
+    pub fn paths_get(&self, path: &[String], convert_mode: ConvertMode) -> Option<(usize, String)> {
+        let runtime_paths = match (convert_mode, self.runtime) {
+            (ConvertMode::IntoSync, Some(runtime)) => runtime.paths(),
+            _ => &[],
+        };
+        let io_paths = match (convert_mode, self.io) {
+            (ConvertMode::IntoSync, true) => IO_PATHS,
+            _ => &[],
+        };
+
+        if self.paths.is_empty() && runtime_paths.is_empty() && io_paths.is_empty() {
+            return None;
+        }
+
+        for len in (1..=path.len()).rev() {
+            let name = path[..len].join("::");
+            if let Some(value) = self.paths.get(&name) {
+                return Some((len, value.clone()));
+            }
+            if let Some((_, value)) = runtime_paths.iter().chain(io_paths).find(|(key, _)| *key == name) {
+                return Some((len, value.to_string()));
+            }
+        }
+
+        None
+    }
+
+    pub fn executor_get(&self) -> Option<Executor> {
+        self.executor
+    }
+
+    pub fn block_on_get(&self) -> Option<syn::Path> {
+        self.block_on.as_ref().map(|s| syn::parse_str(s).unwrap())
+    }
+
+    // Parameters of the async version which the blocking version delegates to
+    pub fn blocking_target(&self) -> MacroParameters {
+        let mut target = self.clone();
+        target.mode = Some(ConvertMode::IntoAsync);
+        target.key = self.target.clone();
+        target.suffix = self.target_suffix.clone();
+        target
+    }
+
+    pub fn idents_get<'s, S: AsRef<str>>(&'s self, name: S) -> Option<&'s IdentRecord> {
+        let ir = self.idents.get(name.as_ref());
+        if ir.is_some() {
+            self.used_idents.borrow_mut().insert(name.as_ref().to_string());
+        }
+        ir
+    }
+
+    // Marks the records whose names occur in the tokens (the code removed from this version) as
+    // used
+    pub fn idents_mark_used(&self, tokens: TokenStream2) {
+        for tt in tokens {
+            match tt {
+                proc_macro2::TokenTree::Ident(ident) => {
+                    self.idents_get(ident.to_string());
+                }
+                proc_macro2::TokenTree::Group(group) => self.idents_mark_used(group.stream()),
+                _ => {}
+            }
+        }
+    }
+
+    // Records specified in place (not inherited from `content!` or a profile) which were never
+    // applied, with the spans of their names
+    pub fn idents_unused(&self) -> Vec<(String, Span)> {
+        if self.allow_unused_idents {
+            return vec![];
+        }
+
+        let used = self.used_idents.borrow();
+        let mut unused = self
+            .idents
+            .iter()
+            .filter(|(name, ir)| !ir.allow_unused && !used.contains(name.as_str()))
+            .filter_map(|(name, ir)| ir.span.map(|span| (name.clone(), span)))
+            .collect::<Vec<_>>();
+        unused.sort_by(|a, b| a.0.cmp(&b.0));
+        unused
+    }
+
+    // Records inherited by other items are not reported: each item uses only some of them
+    pub fn idents_allow_unused(&mut self) {
+        for ir in self.idents.values_mut() {
+            ir.allow_unused = true;
+        }
+        for version in &mut self.versions {
+            version.params.idents_allow_unused();
+        }
+    }
+
+    // Each version of an item is converted by its own invocation, so a record is checked by the
+    // first version having it only: it is added to `checked` there and allowed to be unused in the
+    // rest
+    pub fn idents_check_once(&mut self, checked: &mut HashSet<String>) {
+        for (name, ir) in &mut self.idents {
+            if !checked.insert(name.clone()) {
+                ir.allow_unused = true;
+            }
+        }
+    }
+
+    // Renames the ident listed in `idents` or matching one of the patterns (listed idents take
+    // precedence)
+    pub fn ident_rename(&self, ident: &Ident, convert_mode: ConvertMode) -> Option<Ident> {
+        if let Some(ir) = self.idents_get(ident.to_string()) {
+            return Some(ir.ident_add_suffix(ident, convert_mode, self.key_get(), self.suffix_get()));
+        }
+
+        self.ident_patterns
+            .iter()
+            .find_map(|pattern| pattern.rename(ident, convert_mode, self.key_get(), self.suffix_get()))
+    }
+
+    pub fn replace_features_is_empty(&self) -> bool {
+        self.replace_features.is_empty()
+    }
+    pub fn replace_features_get<'s, S: AsRef<str>>(&'s self, name: S) -> Option<&'s str> {
+        self.replace_features.get(name.as_ref()).map(|s| s.as_str())
+    }
+
+    pub fn drop_attrs_is_empty(&self) -> bool {
+        self.drop_attrs.is_empty()
+    }
+    pub fn drop_attrs_contains(&self, name: &String) -> bool {
+        self.drop_attrs.contains(name)
+    }
+
+    pub fn is_our_attr(&self, attr: &Attribute) -> Option<String> {
+        if attr.style == syn::AttrStyle::Outer {
+            if let Some((first, last)) = Self::split_two_segments(&attr.path) {
+                if let Some(prefix) = &self.prefix {
+                    if &first == prefix {
+                        return Some(last);
+                    }
+                } else {
+                    return Some(last);
+                }
+            }
+        };
+
+        None
+    }
+
+    // `maybe_async_cfg::name` (or `prefix::name` if the prefix is specified) -> "name". Unlike the
+    // attributes, the macros in bodies must start with the crate name, since `sync_impl!` and the
+    // like are too common names to be recognized by the last segment only
+    pub fn is_our_path(&self, path: &syn::Path) -> Option<String> {
+        match Self::split_two_segments(path) {
+            Some((first, last)) if first == self.prefix_get() => Some(last),
+            _ => None,
+        }
+    }
+
+    // `first::last` -> ("first", "last")
+    fn split_two_segments(path: &syn::Path) -> Option<(String, String)> {
+        if path.leading_colon.is_none() && path.segments.len() == 2 {
+            let first_segment = &path.segments[0];
+            let last_segment = &path.segments[1];
+            if first_segment.arguments == syn::PathArguments::None
+                && last_segment.arguments == syn::PathArguments::None
+            {
+                return Some((first_segment.ident.to_string(), last_segment.ident.to_string()));
+            }
+        }
+
+        None
+    }
+
+    pub fn make_self_path(&self, name: &str) -> syn::Path {
+        let mut segments = Punctuated::<syn::PathSegment, syn::token::Colon2>::new();
+        segments.push_value(syn::PathSegment {
+            ident: Ident::new(
+                self.prefix
+                    .as_ref()
+                    .map(|s| s.as_str())
+                    .unwrap_or(self.prefix_get()),
+                Span::call_site(),
+            ),
+            arguments: syn::PathArguments::None,
+        });
+        segments.push_punct(syn::Token![::](Span::call_site()));
+        segments.push_value(syn::PathSegment {
+            ident: Ident::new(name, Span::call_site()),
+            arguments: syn::PathArguments::None,
+        });
+
+        syn::Path {
+            leading_colon: None,
+            segments,
+        }
+    }
+
+    // Whether the arguments of the macro should be visited as expressions: standard macros (also
+    // as `std::name!`, `core::name!` or `alloc::name!`) and the ones from `macros(...)`.
+    pub fn is_expr_macro(&self, path: &syn::Path) -> bool {
+        let name = path_to_string(path);
+        if self.macros.contains(&name) {
+            return true;
+        }
+
+        let standard = match path.segments.len() {
+            1 => true,
+            2 => {
+                let first = &path.segments[0].ident;
+                first == "std" || first == "core" || first == "alloc"
+            }
+            _ => false,
+        };
+
+        standard
+            && STANDARD_MACROS
+                .iter()
+                .any(|m| path.segments.last().map(|s| s.ident == m).unwrap_or(false))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub struct MacroParametersBuilder {
+    params: MacroParameters,
+    profile: Option<LitStr>,
+}
+
+impl MacroParametersBuilder {
+    pub fn new() -> Self {
+        Self {
+            params: MacroParameters {
+                mode: None,
+                disable: false,
+                key: None,
+                suffix: None,
+                self_name: None, 
+                original_self_name: None,
+                prefix: None,
+                idents: HashMap::new(),
+                ident_patterns: vec![],
+                allow_unused_idents: false,
+                used_idents: RefCell::new(HashSet::new()),
+                keep_self: false,
+                send: None,
+                trait_backend: None,
+                recursive_asyncness_removal: true,
+                streams: false,
+                io: false,
+                unwrap_locks: false,
+                convert_doctests: false,
+                validate: false,
+                allow_async_leftovers: false,
+                runtime: None,
+                paths: HashMap::new(),
+                macros: vec![],
+                executor: None,
+                block_on: None,
+                target: None,
+                target_suffix: None,
+                cfg: None,
+                outer_attrs: Punctuated::new(),
+                inner_attrs: Punctuated::new(),
+                drop_attrs: vec![],
+                replace_features: HashMap::new(),
+                config_file: None,
+                io_traits: vec![],
+                futures_fns: vec![],
+                stream_traits: vec![],
+                versions: vec![],
+            },
+            profile: None,
+        }
+    }
+
+    pub fn mode_into_sync(&mut self) -> syn::Result<()> {
+        self.params.mode = Some(ConvertMode::IntoSync);
+        Ok(())
+    }
+
+    pub fn mode_into_async(&mut self) -> syn::Result<()> {
+        self.params.mode = Some(ConvertMode::IntoAsync);
+        Ok(())
+    }
+
+    pub fn mode_into_blocking(&mut self) -> syn::Result<()> {
+        self.params.mode = Some(ConvertMode::IntoBlocking);
+        Ok(())
+    }
+
+    pub fn key(&mut self, key: String) -> syn::Result<()> {
+        self.params.key = Some(key);
+        Ok(())
+    }
+
+    pub fn suffix(&mut self, suffix: String) -> syn::Result<()> {
+        if syn::parse_str::<Ident>(&format!("X{}", suffix)).is_err() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "Suffix must be a valid part of an identifier",
+            ));
+        }
+
+        self.params.suffix = Some(suffix);
+        Ok(())
+    }
+
+    pub fn self_name(&mut self, self_name: String) -> syn::Result<()> {
+        self.params.self_name = Some(self_name);
+        Ok(())
+    }
+
+    pub fn disable(&mut self) {
+        self.params.disable = true;
+    }
+
+    pub fn keep_self(&mut self) {
+        self.params.keep_self = true;
+    }
+
+    pub fn streams(&mut self) {
+        self.params.streams = true;
+    }
+
+    pub fn io(&mut self) {
+        self.params.io = true;
+    }
+
+    pub fn unwrap_locks(&mut self) {
+        self.params.unwrap_locks = true;
+    }
+
+    pub fn convert_doctests(&mut self) {
+        self.params.convert_doctests = true;
+    }
+
+    pub fn allow_unused_idents(&mut self) {
+        self.params.allow_unused_idents = true;
+    }
+
+    pub fn validate(&mut self) {
+        self.params.validate = true;
+    }
+
+    pub fn allow_async_leftovers(&mut self) {
+        self.params.allow_async_leftovers = true;
+    }
+
+    pub fn profile(&mut self, profile: &LitStr) -> syn::Result<()> {
+        if self.profile.is_some() {
+            return Err(syn::Error::new_spanned(profile, "Only one profile is allowed"));
+        }
+
+        self.profile = Some(profile.clone());
+        Ok(())
+    }
+
+    pub fn runtime(&mut self, runtime: String) -> syn::Result<()> {
+        self.params.runtime = Some(Runtime::from_str(&runtime).ok_or_else(|| {
+            syn::Error::new(Span::call_site(), "Only accepts `tokio` or `async-std`")
+        })?);
+
+        Ok(())
+    }
+
+    pub fn executor(&mut self, executor: String) -> syn::Result<()> {
+        self.params.executor = Some(Executor::from_str(&executor).ok_or_else(|| {
+            syn::Error::new(Span::call_site(), "Only accepts `tokio`, `futures` or `async-std`")
+        })?);
+
+        Ok(())
+    }
+
+    pub fn block_on(&mut self, block_on: String) -> syn::Result<()> {
+        if syn::parse_str::<syn::Path>(&block_on).is_err() {
+            return Err(syn::Error::new(Span::call_site(), "Expected path"));
+        }
+
+        self.params.block_on = Some(block_on);
+        Ok(())
+    }
+
+    pub fn target(&mut self, target: String) -> syn::Result<()> {
+        self.params.target = Some(target);
+        Ok(())
+    }
+
+    pub fn target_suffix(&mut self, target_suffix: String) -> syn::Result<()> {
+        self.params.target_suffix = Some(target_suffix);
+        Ok(())
+    }
+
+    pub fn paths(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for nm in list {
+            match nm {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(lit),
+                    ..
+                })) => {
+                    let value = lit.value();
+                    if syn::parse_str::<syn::Path>(&value).is_err() {
+                        return Err(syn::Error::new_spanned(lit.to_token_stream(), "Expected path"));
+                    }
+                    self.params.paths.insert(path_to_string(path), value);
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        nm.to_token_stream(),
+                        "Expected path = \"path\" pair",
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn macros(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for nm in list {
+            match nm {
+                NestedMeta::Meta(Meta::Path(path)) => {
+                    let name = path_to_string(path);
+                    if !self.params.macros.contains(&name) {
+                        self.params.macros.push(name);
+                    }
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(nm.to_token_stream(), "Expected macro path"))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn prefix(&mut self, prefix: String) -> syn::Result<()> {
+        self.params.prefix = Some(prefix);
+        Ok(())
+    }
+
+    pub fn idents(
+        idents: &mut HashMap<String, IdentRecord>,
+        ident_patterns: &mut Vec<IdentPattern>,
+        list: &Punctuated<NestedMeta, Comma>,
+    ) -> syn::Result<()> {
+        let mut pattern: Option<IdentPattern> = None;
+        let mut pattern_values = vec![];
+
+        for nm in list {
+            match nm {
+                NestedMeta::Meta(Meta::Path(path)) => {
+                    let ident = path
+                        .get_ident()
+                        .ok_or(syn::Error::new_spanned(
+                            nm.to_token_stream(),
+                            "Expected ident, but not complex path",
+                        ))?
+                        .to_string();
+                    let mut ir = IdentRecord::new();
+                    ir.span = Some(path.span());
+                    idents.insert(ident, ir);
+                }
+                NestedMeta::Meta(Meta::List(syn::MetaList { path, nested, .. })) => {
+                    let ident = path
+                        .get_ident()
+                        .ok_or(syn::Error::new_spanned(
+                            nm.to_token_stream(),
+                            "Expected ident, but not complex path",
+                        ))?
+                        .to_string();
+                    let mut ir = IdentRecord::new();
+                    ir.span = Some(path.span());
+                    for inm in nested {
+                        match inm {
+                            NestedMeta::Meta(Meta::Path(path)) => {
+                                let iname = path
+                                    .get_ident()
+                                    .ok_or(syn::Error::new_spanned(
+                                        nm.to_token_stream(),
+                                        "Expected ident, but not complex path",
+                                    ))?
+                                    .to_string();
+                                match iname.as_str() {
+                                    "snake" | "fn" | "mod" => {
+                                        ir.case = IdentCase::Snake;
+                                    }
+                                    "upper" | "const" | "static" => {
+                                        ir.case = IdentCase::Screaming;
+                                    }
+                                    "use" => {
+                                        ir.use_mode = true;
+                                    }
+                                    "keep" => {
+                                        ir.keep = true;
+                                    }
+                                    "allow_unused" => {
+                                        ir.allow_unused = true;
+                                    }
+                                    "sync" => {
+                                        ir.ident_sync = Some(ident.clone());
+                                    }
+                                    "async" => {
+                                        ir.ident_async = Some(ident.clone());
+                                    }
+                                    _ => {
+                                        return Err(syn::Error::new_spanned(
+                                            nm.to_token_stream(),
+                                            "Expected snake, fn, mod, upper, const, static, use, keep, allow_unused, sync, async",
+                                        ))
+                                    }
+                                }
+                            }
+                            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                                path,
+                                lit: syn::Lit::Str(lit),
+                                ..
+                            })) => {
+                                let iname = path
+                                    .get_ident()
+                                    .ok_or(syn::Error::new_spanned(
+                                        nm.to_token_stream(),
+                                        "Expected ident, but not complex path",
+                                    ))?
+                                    .to_string();
+                                let ivalue = lit.value();
+                                match iname.as_str() {
+                                    "sync" => {
+                                        ir.ident_sync = Some(ivalue);
+                                    }
+                                    "async" => {
+                                        ir.ident_async = Some(ivalue);
+                                    }
+                                    _ => {
+                                        let idents = ir.idents.get_or_insert_with(|| HashMap::new());
+                                        idents.insert(iname, ivalue);
+                                    }
+                                }
+                            }
+                            _ => {
+                                return Err(syn::Error::new_spanned(
+                                    nm.to_token_stream(),
+                                    "Expected fn, sync = \"ident\", or async = \"ident\"",
+                                ))
+                            }
+                        }
+                    }
+                    idents.insert(ident, ir);
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(lit),
+                    ..
+                })) => {
+                    let name = path
+                        .get_ident()
+                        .ok_or(syn::Error::new_spanned(
+                            nm.to_token_stream(),
+                            "Expected ident, but not complex path",
+                        ))?
+                        .to_string();
+
+                    match IdentPatternKind::from_str(&name) {
+                        Some(_) if pattern.is_some() => {
+                            return Err(syn::Error::new_spanned(
+                                nm.to_token_stream(),
+                                "Only one pattern is allowed in `idents(...)`",
+                            ))
+                        }
+                        Some(kind) => {
+                            pattern = Some(IdentPattern::new(kind, lit.value()).map_err(|err| {
+                                syn::Error::new_spanned(lit.to_token_stream(), err.to_string())
+                            })?);
+                        }
+                        None => pattern_values.push((nm, name, lit.value())),
+                    }
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        nm.to_token_stream(),
+                        "Expected ident, ident(...) or pattern = \"value\" pair",
+                    ))
+                }
+            }
+        }
+
+        match pattern {
+            Some(mut pattern) => {
+                for (_, name, value) in pattern_values {
+                    match name.as_str() {
+                        "sync" => pattern.ident_sync = Some(value),
+                        "async" => pattern.ident_async = Some(value),
+                        _ => {
+                            pattern.idents.insert(name, value);
+                        }
+                    }
+                }
+                ident_patterns.push(pattern);
+            }
+            None => {
+                if let Some((nm, _, _)) = pattern_values.first() {
+                    return Err(syn::Error::new_spanned(
+                        nm.to_token_stream(),
+                        "Expected `regex`, `prefix` or `suffix` pattern",
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn send(&mut self, send: String) -> syn::Result<()> {
+        self.params.send = Some(match send.as_str() {
+            "" | "Send" | "true" => true,
+            "?Send" | "false" => false,
+            _ => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    "Only accepts `Send` or `?Send`",
+                ));
+            }
+        });
+
+        Ok(())
+    }
+
+    pub fn trait_backend(&mut self, trait_backend: String) -> syn::Result<()> {
+        self.params.trait_backend = Some(TraitBackend::from_str(&trait_backend).ok_or_else(|| {
+            syn::Error::new(
+                Span::call_site(),
+                "Only accepts `async_trait`, `native` or `trait_variant`",
+            )
+        })?);
+
+        Ok(())
+    }
+
+    pub fn feature(&mut self, meta: &Meta) -> syn::Result<()> {
+        self.cfg_meta(meta)
+    }
+
+    pub fn cfg_list(&mut self, list: &MetaList) -> syn::Result<()> {
+        match list.nested.len() {
+            0 => {
+                return Err(syn::Error::new_spanned(
+                    list.to_token_stream(),
+                    "Expected condition",
+                ))
+            }
+            1 => {
+                let first = list.nested.first().unwrap();
+                match first {
+                    NestedMeta::Meta(first_meta) => self.cfg_meta(first_meta)?,
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            list.to_token_stream(),
+                            "Expected condition",
+                        ))
+                    }
+                }
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    list.to_token_stream(),
+                    "Expected condition",
+                ))
+            }
+        };
+
+        Ok(())
+    }
+
+    pub fn cfg_meta(&mut self, meta: &Meta) -> syn::Result<()> {
+        self.params.cfg = Some(meta.clone());
+        Ok(())
+    }
+
+    pub fn outer_attrs(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        if self.params.outer_attrs.is_empty() {
+            self.params.outer_attrs = list.clone();
+        } else {
+            self.params.outer_attrs.extend(list.into_iter().cloned());
+        }
+        Ok(())
+    }
+
+    pub fn inner_attr_str(&mut self, lit: &syn::Lit) -> syn::Result<()> {
+        self.params.inner_attrs.push(NestedMeta::Lit(lit.clone()));
+        Ok(())
+    }
+
+    pub fn inner_attr(&mut self, meta: &Meta) -> syn::Result<()> {
+        self.params.inner_attrs.push(NestedMeta::Meta(meta.clone()));
+        Ok(())
+    }
+
+    pub fn version_or_inner_attr(
+        &mut self,
+        name: &str,
+        list: &Punctuated<NestedMeta, Comma>,
+        meta: &Meta,
+    ) -> syn::Result<()> {
+        if let Some(kind) = ConvertMode::from_str(name) {
+            self.version(kind, list)?;
+        } else {
+            self.params.inner_attrs.push(NestedMeta::Meta(meta.clone()));
+        };
+        Ok(())
+    }
+
+    pub fn inner_attrs(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        if self.params.inner_attrs.is_empty() {
+            self.params.inner_attrs = list.clone();
+        } else {
+            self.params.inner_attrs.extend(list.into_iter().cloned());
+        }
+        Ok(())
+    }
+
+    pub fn version(
+        &mut self,
+        kind: ConvertMode,
+        list: &Punctuated<NestedMeta, Comma>,
+    ) -> syn::Result<()> {
+        for nm in list {
+            if let NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, .. })) = nm {
+                if path.is_ident("profile") {
+                    return Err(syn::Error::new_spanned(
+                        nm.to_token_stream(),
+                        "Profile can be used at the top level only",
+                    ));
+                }
+            }
+        }
+
+        let inner = MacroParameters::from_args(list)?;
+        self.params.versions.push(MacroParameterVersion {
+            kind,
+            params: inner,
+        });
+        Ok(())
+    }
+
+    pub fn drop_attrs(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for nm in meta {
+            match nm {
+                NestedMeta::Meta(Meta::Path(path)) => {
+                    let name = path
+                        .get_ident()
+                        .ok_or(syn::Error::new_spanned(
+                            path.to_token_stream(),
+                            "Expected ident",
+                        ))?
+                        .to_string();
+                    self.params.drop_attrs.push(name);
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        nm.to_token_stream(),
+                        "Expected list of idents",
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn replace_feature(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        match meta.len() {
+            2 => {
+                let prev = match &meta[0] {
+                    NestedMeta::Lit(Lit::Str(lit)) => lit.value(),
+                    nm @ _ => {
+                        return Err(syn::Error::new_spanned(
+                            nm.to_token_stream(),
+                            "Expected string literal",
+                        ))
+                    }
+                };
+                let new = match &meta[1] {
+                    NestedMeta::Lit(Lit::Str(lit)) => lit.value(),
+                    nm @ _ => {
+                        return Err(syn::Error::new_spanned(
+                            nm.to_token_stream(),
+                            "Expected string literal",
+                        ))
+                    }
+                };
+
+                self.params.replace_features.insert(prev, new);
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    meta.to_token_stream(),
+                    "Expected two string literals",
+                ))
+            }
+        };
+
+        Ok(())
+    }
+
+    // The parameters with the profile applied, but without the common parameters merged into the
+    // versions, so the ones specified in place can take precedence over the profile
+    fn build_profile(mut self) -> syn::Result<MacroParameters> {
+        if let Some(profile) = self.profile.take() {
+            self.apply_profile(&profile)?;
+        }
+
+        Ok(self.params)
+    }
+
+    pub fn build(mut self) -> syn::Result<MacroParameters> {
+        if let Some(profile) = self.profile.take() {
+            self.apply_profile(&profile)?;
+        }
+
+        let mut versions = std::mem::replace(&mut self.params.versions, vec![]);
+
+        for version in &mut versions {
+            // the paths of the item replace the async code with the sync one, so the async
+            // versions keep only their own paths
+            let own_paths = match version.kind {
+                ConvertMode::IntoAsync => Some(version.params.paths.clone()),
+                ConvertMode::IntoSync | ConvertMode::IntoBlocking => None,
+            };
+            MacroParameters::apply_parent(&mut version.params, &self.params)?;
+            if let Some(own_paths) = own_paths {
+                version.params.paths = own_paths;
+            }
+
+            if version.params.key.is_none() {
+                version.params.key = Some(version.kind.to_str().to_string());
+            }
+        }
+
+        // the blocking version delegates to one of the async versions
+        let targets = versions
+            .iter()
+            .filter(|version| matches!(version.kind, ConvertMode::IntoAsync))
+            .map(|version| (version.params.key.clone(), version.params.suffix.clone()))
+            .collect::<Vec<_>>();
+
+        for version in &mut versions {
+            if !matches!(version.kind, ConvertMode::IntoBlocking) || version.params.disable {
+                continue;
+            }
+
+            if version.params.executor.is_none() && version.params.block_on.is_none() {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    "Blocking version requires `executor` or `block_on` parameter",
+                ));
+            }
+
+            let target = match &version.params.target {
+                Some(key) => targets.iter().find(|(k, _)| k.as_ref() == Some(key)),
+                None => targets.first(),
+            };
+
+            match target {
+                Some((key, suffix)) => {
+                    version.params.target = key.clone();
+                    version.params.target_suffix = suffix.clone();
+                }
+                None => {
+                    return Err(syn::Error::new(
+                        Span::call_site(),
+                        "Blocking version requires an async version to delegate to",
+                    ))
+                }
+            }
+        }
+
+        // the same suffix would give the same names to the items of different versions, unless
+        // these versions are enabled by different conditions
+        let mut suffixes: Vec<(String, String)> = vec![];
+        for version in versions.iter().filter(|version| !version.params.disable) {
+            let suffix = match (&version.params.suffix, version.kind) {
+                (Some(suffix), _) => suffix.clone(),
+                (None, ConvertMode::IntoSync) => "Sync".to_string(),
+                (None, ConvertMode::IntoAsync) => "Async".to_string(),
+                (None, ConvertMode::IntoBlocking) => "Blocking".to_string(),
+            };
+
+            let cfg = version.params.cfg.to_token_stream().to_string();
+            if suffixes.iter().any(|(s, c)| *s == suffix && *c == cfg) {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    format!(
+                        "Several versions with the same conditions use the `{}` suffix, so their \
+                        items would get the same names; specify `suffix = \"...\"` for each of them",
+                        suffix
+                    ),
+                ));
+            }
+            suffixes.push((suffix, cfg));
+        }
+
+        self.params.versions = versions;
+
+        Ok(self.params)
+    }
+
+    // The profile from the config file is merged as the parent; its versions are used only if
+    // there are no versions specified in place
+    fn apply_profile(&mut self, profile: &LitStr) -> syn::Result<()> {
+        let (mut parent, config_file) =
+            config::load_profile(&profile.value(), profile.span(), |args| {
+                MacroParameters::parse_args(args)?.build_profile()
+            })?;
+        parent.idents_allow_unused();
+        self.params.config_file = Some(config_file);
+
+        MacroParameters::apply_parent(&mut self.params, &parent)?;
+
+        if self.params.prefix.is_none() {
+            self.params.prefix = parent.prefix.clone();
+        }
+
+        if self.params.versions.is_empty() {
+            self.params.versions = parent.versions;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,308 @@
+use proc_macro2::Span;
+use syn::{punctuated::Punctuated, spanned::Spanned};
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Runtime {
+    Tokio,
+    AsyncStd,
+}
+
+impl Runtime {
+    pub fn from_str<S: AsRef<str>>(s: S) -> Option<Self> {
+        match s.as_ref() {
+            "tokio" => Some(Self::Tokio),
+            "async-std" | "async_std" => Some(Self::AsyncStd),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Self::Tokio => "tokio",
+            Self::AsyncStd => "async-std",
+        }
+    }
+
+    pub fn paths(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Tokio => TOKIO_PATHS,
+            Self::AsyncStd => ASYNC_STD_PATHS,
+        }
+    }
+}
+
+const TOKIO_PATHS: &[(&str, &str)] = &[
+    // fs
+    ("tokio::fs", "std::fs"),
+    // io
+    ("tokio::io", "std::io"),
+    ("tokio::io::AsyncRead", "std::io::Read"),
+    ("tokio::io::AsyncReadExt", "std::io::Read"),
+    ("tokio::io::AsyncWrite", "std::io::Write"),
+    ("tokio::io::AsyncWriteExt", "std::io::Write"),
+    ("tokio::io::AsyncBufRead", "std::io::BufRead"),
+    ("tokio::io::AsyncBufReadExt", "std::io::BufRead"),
+    ("tokio::io::AsyncSeek", "std::io::Seek"),
+    ("tokio::io::AsyncSeekExt", "std::io::Seek"),
+    // net
+    ("tokio::net", "std::net"),
+    ("tokio::net::UnixStream", "std::os::unix::net::UnixStream"),
+    ("tokio::net::UnixListener", "std::os::unix::net::UnixListener"),
+    ("tokio::net::UnixDatagram", "std::os::unix::net::UnixDatagram"),
+    // time
+    ("tokio::time::sleep", "std::thread::sleep"),
+    ("tokio::time::Duration", "std::time::Duration"),
+    ("tokio::time::Instant", "std::time::Instant"),
+    ("tokio::task::yield_now", "std::thread::yield_now"),
+    // sync
+    ("tokio::sync::Mutex", "std::sync::Mutex"),
+    ("tokio::sync::MutexGuard", "std::sync::MutexGuard"),
+    ("tokio::sync::RwLock", "std::sync::RwLock"),
+    ("tokio::sync::RwLockReadGuard", "std::sync::RwLockReadGuard"),
+    ("tokio::sync::RwLockWriteGuard", "std::sync::RwLockWriteGuard"),
+    ("tokio::sync::Barrier", "std::sync::Barrier"),
+    ("tokio::sync::BarrierWaitResult", "std::sync::BarrierWaitResult"),
+];
+
+const ASYNC_STD_PATHS: &[(&str, &str)] = &[
+    // fs
+    ("async_std::fs", "std::fs"),
+    ("async_std::path", "std::path"),
+    // io
+    ("async_std::io", "std::io"),
+    ("async_std::io::ReadExt", "std::io::Read"),
+    ("async_std::io::WriteExt", "std::io::Write"),
+    ("async_std::io::BufReadExt", "std::io::BufRead"),
+    ("async_std::io::SeekExt", "std::io::Seek"),
+    ("async_std::io::prelude", "std::io::prelude"),
+    ("async_std::prelude", "std::io::prelude"),
+    // net
+    ("async_std::net", "std::net"),
+    ("async_std::os::unix::net", "std::os::unix::net"),
+    // time
+    ("async_std::task::sleep", "std::thread::sleep"),
+    ("async_std::task::yield_now", "std::thread::yield_now"),
+    // sync
+    ("async_std::sync::Arc", "std::sync::Arc"),
+    ("async_std::sync::Weak", "std::sync::Weak"),
+    ("async_std::sync::Mutex", "std::sync::Mutex"),
+    ("async_std::sync::MutexGuard", "std::sync::MutexGuard"),
+    ("async_std::sync::RwLock", "std::sync::RwLock"),
+    ("async_std::sync::RwLockReadGuard", "std::sync::RwLockReadGuard"),
+    ("async_std::sync::RwLockWriteGuard", "std::sync::RwLockWriteGuard"),
+    ("async_std::sync::Barrier", "std::sync::Barrier"),
+    ("async_std::sync::BarrierWaitResult", "std::sync::BarrierWaitResult"),
+];
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// `a::b::c` -> "a::b::c"
+pub fn path_to_string(path: &syn::Path) -> String {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+fn parse_replacement(replacement: &str, span: Span) -> syn::Result<syn::Path> {
+    let mut path: syn::Path = syn::parse_str(replacement)
+        .map_err(|_| syn::Error::new(span, format!("Wrong path: {}", replacement)))?;
+    for segment in &mut path.segments {
+        segment.ident.set_span(span);
+    }
+    Ok(path)
+}
+
+// Replaces the longest known prefix of the path:
+//
+// `tokio::sync::Mutex::<T>::new` -> `std::sync::Mutex::<T>::new`
+pub fn convert_path(
+    path: &mut syn::Path,
+    lookup: impl Fn(&[String]) -> Option<(usize, String)>,
+) -> syn::Result<()> {
+    let names: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+
+    let (len, replacement) = match lookup(&names) {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+
+    let mut new_path = parse_replacement(&replacement, path.segments[len - 1].ident.span())?;
+    if let Some(last) = new_path.segments.last_mut() {
+        last.arguments = path.segments[len - 1].arguments.clone();
+    }
+    for segment in path.segments.iter().skip(len) {
+        new_path.segments.push(segment.clone());
+    }
+
+    *path = new_path;
+
+    Ok(())
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+enum UseLeaf {
+    Name(syn::Ident),
+    Rename(syn::Ident, syn::Ident),
+    Glob,
+}
+
+fn flatten_use_tree(tree: &syn::UseTree, prefix: &mut Vec<syn::Ident>, acc: &mut Vec<(Vec<syn::Ident>, UseLeaf)>) {
+    match tree {
+        syn::UseTree::Path(path) => {
+            prefix.push(path.ident.clone());
+            flatten_use_tree(&path.tree, prefix, acc);
+            prefix.pop();
+        }
+        syn::UseTree::Name(name) => acc.push((prefix.clone(), UseLeaf::Name(name.ident.clone()))),
+        syn::UseTree::Rename(rename) => acc.push((
+            prefix.clone(),
+            UseLeaf::Rename(rename.ident.clone(), rename.rename.clone()),
+        )),
+        syn::UseTree::Glob(_) => acc.push((prefix.clone(), UseLeaf::Glob)),
+        syn::UseTree::Group(group) => {
+            for item in &group.items {
+                flatten_use_tree(item, prefix, acc);
+            }
+        }
+    }
+}
+
+fn build_use_tree(mut idents: Vec<syn::Ident>, leaf: UseLeaf) -> syn::UseTree {
+    let mut tree = match leaf {
+        UseLeaf::Name(ident) => syn::UseTree::Name(syn::UseName { ident }),
+        UseLeaf::Rename(ident, rename) => syn::UseTree::Rename(syn::UseRename {
+            as_token: syn::Token![as](ident.span()),
+            ident,
+            rename,
+        }),
+        UseLeaf::Glob => syn::UseTree::Glob(syn::UseGlob {
+            star_token: syn::Token![*](Span::call_site()),
+        }),
+    };
+
+    while let Some(ident) = idents.pop() {
+        tree = syn::UseTree::Path(syn::UsePath {
+            colon2_token: syn::Token![::](ident.span()),
+            ident,
+            tree: Box::new(tree),
+        });
+    }
+
+    tree
+}
+
+// Converts the paths in the use declaration. If some path is converted, the tree is flattened:
+//
+// `use tokio::{fs, time::sleep};` -> `use {std::fs, std::thread::sleep};`
+//
+// The name under which the item is imported does not change:
+//
+// `use tokio::io::AsyncReadExt;` -> `use std::io::Read as AsyncReadExt;`
+pub fn convert_use(
+    item: &mut syn::ItemUse,
+    lookup: impl Fn(&[String]) -> Option<(usize, String)>,
+) -> syn::Result<()> {
+    let mut leaves = vec![];
+    flatten_use_tree(&item.tree, &mut vec![], &mut leaves);
+
+    let mut changed = false;
+    let mut trees = Vec::with_capacity(leaves.len());
+    let mut leading_colon = item.leading_colon;
+
+    for (prefix, leaf) in leaves {
+        // `use a::b::{self}` imports `a::b` itself
+        let (mut idents, leaf) = match leaf {
+            UseLeaf::Name(ident) if ident == "self" => {
+                let mut idents = prefix;
+                let last = idents.pop().unwrap_or(ident);
+                (idents, UseLeaf::Name(last))
+            }
+            UseLeaf::Rename(ident, rename) if ident == "self" => {
+                let mut idents = prefix;
+                let last = idents.pop().unwrap_or(ident);
+                (idents, UseLeaf::Rename(last, rename))
+            }
+            leaf => (prefix, leaf),
+        };
+
+        let mut names: Vec<String> = idents.iter().map(|i| i.to_string()).collect();
+        match &leaf {
+            UseLeaf::Name(ident) | UseLeaf::Rename(ident, _) => names.push(ident.to_string()),
+            UseLeaf::Glob => {}
+        }
+
+        let found = lookup(&names);
+        let (len, replacement) = match found {
+            Some(found) => found,
+            None => {
+                trees.push(build_use_tree(idents, leaf));
+                continue;
+            }
+        };
+
+        changed = true;
+        let span = item.tree.span();
+        let new_path = parse_replacement(&replacement, span)?;
+        if new_path.leading_colon.is_some() {
+            leading_colon = new_path.leading_colon;
+        }
+
+        let mut new_idents: Vec<syn::Ident> =
+            new_path.segments.into_iter().map(|s| s.ident).collect();
+        if len <= idents.len() {
+            new_idents.extend(idents.drain(len..));
+            trees.push(build_use_tree(new_idents, leaf));
+        } else {
+            // the whole path including the last ident was replaced
+            let last = new_idents.pop().expect("Non-empty path");
+            let leaf = match leaf {
+                UseLeaf::Name(ident) if ident != last => UseLeaf::Rename(last, ident),
+                UseLeaf::Name(_) => UseLeaf::Name(last),
+                UseLeaf::Rename(_, rename) => UseLeaf::Rename(last, rename),
+                UseLeaf::Glob => UseLeaf::Glob,
+            };
+            trees.push(build_use_tree(new_idents, leaf));
+        }
+    }
+
+    if !changed {
+        return Ok(());
+    }
+
+    item.tree = if trees.len() == 1 {
+        item.leading_colon = leading_colon;
+        trees.pop().unwrap()
+    } else {
+        item.leading_colon = None;
+        syn::UseTree::Group(syn::UseGroup {
+            brace_token: syn::token::Brace(item.tree.span()),
+            items: Punctuated::from_iter(trees),
+        })
+    };
+
+    Ok(())
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// `mutex.lock().await` -> `mutex.lock().unwrap()`, same for `read()`/`write()` of `RwLock`
+//
+// The type of the receiver is unknown here, so any call of these methods without arguments is
+// converted; that's why it is done only with the `unwrap_locks` parameter.
+pub fn convert_lock_await(expr: &syn::ExprAwait) -> Option<syn::Expr> {
+    match &*expr.base {
+        syn::Expr::MethodCall(call)
+            if call.args.is_empty()
+                && (call.method == "lock" || call.method == "read" || call.method == "write") =>
+        {
+            let base = &expr.base;
+            Some(syn::parse_quote!(#base.unwrap()))
+        }
+        _ => None,
+    }
+}
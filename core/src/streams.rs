@@ -0,0 +1,333 @@
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    parse::Parser, punctuated::Punctuated, token::Comma, visit_mut::{self, VisitMut},
+};
+
+use crate::paths::path_to_string;
+
+const ITEMS_NAME: &str = "__maybe_async_cfg_items";
+const RESULT_NAME: &str = "__maybe_async_cfg_result";
+
+// The `Stream` traits which become `Iterator`
+const STREAM_PATHS: &[&str] = &[
+    "futures::Stream",
+    "futures::stream::Stream",
+    "futures_core::Stream",
+    "futures_core::stream::Stream",
+    "tokio_stream::Stream",
+];
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+fn path_ends_with(path: &syn::Path, tail: &[&str]) -> bool {
+    let len = path.segments.len();
+    if len < tail.len() {
+        return false;
+    }
+
+    path.segments
+        .iter()
+        .skip(len - tail.len())
+        .zip(tail)
+        .all(|(segment, name)| segment.ident == name)
+}
+
+// `stream!`, `try_stream!`, `async_stream::stream!`, `async_stream::try_stream!`
+pub fn stream_macro_kind(path: &syn::Path) -> Option<bool> {
+    let last = path.segments.last()?;
+    let try_stream = match last.ident.to_string().as_str() {
+        "stream" => false,
+        "try_stream" => true,
+        _ => return None,
+    };
+
+    match path.segments.len() {
+        1 => Some(try_stream),
+        2 if path.segments[0].ident == "async_stream" => Some(try_stream),
+        _ => None,
+    }
+}
+
+// `pin_mut!`, `futures::pin_mut!`, `tokio::pin!`
+fn is_pin_macro(path: &syn::Path) -> bool {
+    path_ends_with(path, &["pin_mut"])
+        || (path.segments.len() == 1 && path.segments[0].ident == "pin")
+        || path_ends_with(path, &["tokio", "pin"])
+}
+
+fn is_stream_stmt_macro(path: &syn::Path) -> bool {
+    stream_macro_kind(path).is_some() || is_pin_macro(path)
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// `futures::Stream` and so on, or a bare name imported from one of the paths above
+pub fn is_stream_path(path: &syn::Path, imported: &[String]) -> bool {
+    match path.segments.len() {
+        1 => path.leading_colon.is_none() && imported.contains(&path_to_string(path)),
+        _ => STREAM_PATHS.contains(&path_to_string(path).as_str()),
+    }
+}
+
+// `futures::Stream<Item = T>` -> `::core::iter::Iterator<Item = T>`
+pub fn convert_stream_bound(bound: &mut syn::TraitBound, imported: &[String]) {
+    let last = match bound.path.segments.last() {
+        Some(last) if is_stream_path(&bound.path, imported) => last.clone(),
+        _ => return,
+    };
+
+    let mut path: syn::Path = syn::parse_quote!(::core::iter::Iterator);
+    if let Some(segment) = path.segments.last_mut() {
+        segment.ident.set_span(last.ident.span());
+        segment.arguments = last.arguments;
+    }
+    bound.path = path;
+}
+
+// `stream::iter(x)` -> `IntoIterator::into_iter(x)`, `stream::once(x)` -> `iter::once(x)`, etc.
+pub fn convert_stream_constructor(expr: &mut syn::ExprCall) {
+    if let syn::Expr::Path(syn::ExprPath { qself: None, path, .. }) = &mut *expr.func {
+        let new_path: syn::Path = if path_ends_with(path, &["stream", "iter"]) {
+            syn::parse_quote!(::core::iter::IntoIterator::into_iter)
+        } else if path_ends_with(path, &["stream", "empty"]) {
+            syn::parse_quote!(::core::iter::empty)
+        } else if path_ends_with(path, &["stream", "once"]) {
+            syn::parse_quote!(::core::iter::once)
+        } else if path_ends_with(path, &["stream", "repeat"]) {
+            syn::parse_quote!(::core::iter::repeat)
+        } else {
+            return;
+        };
+
+        *path = new_path;
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+struct YieldVisitor {
+    items: syn::Ident,
+    wrap_ok: bool,
+    // `return` ends the stream in the async version, but would leave the enclosing function (or
+    // the `try_stream!` closure) in the sync one
+    return_found: Option<syn::ExprReturn>,
+}
+
+impl VisitMut for YieldVisitor {
+    fn visit_expr_mut(&mut self, node: &mut syn::Expr) {
+        match node {
+            // yields inside nested closures and blocks do not belong to this stream
+            syn::Expr::Closure(_) | syn::Expr::Async(_) => {}
+            syn::Expr::Return(expr) => {
+                if self.return_found.is_none() {
+                    self.return_found = Some(expr.clone());
+                }
+            }
+            syn::Expr::Yield(expr) => {
+                let items = &self.items;
+                let value = match &expr.expr {
+                    Some(value) => quote!(#value),
+                    None => quote!(()),
+                };
+                *node = if self.wrap_ok {
+                    syn::parse_quote!(#items.push(::core::result::Result::Ok(#value)))
+                } else {
+                    syn::parse_quote!(#items.push(#value))
+                };
+            }
+            _ => visit_mut::visit_expr_mut(self, node),
+        }
+    }
+
+    fn visit_item_mut(&mut self, _node: &mut syn::Item) {}
+}
+
+// Converts the body of `stream!`/`try_stream!` into a block that eagerly collects all yielded
+// values and returns an iterator over them:
+//
+// ```ignore
+// {
+//     let mut __maybe_async_cfg_items = ::std::vec::Vec::new();
+//     // body with `yield x` replaced by `__maybe_async_cfg_items.push(x)`
+//     __maybe_async_cfg_items.into_iter()
+// }
+// ```
+//
+// For `try_stream!` the body is wrapped in a closure, so `?` stops the iteration and the error
+// becomes the last item.
+//
+// The whole stream is materialized before the first item is returned, so infinite streams never
+// return. `return` in the body is rejected, since it can not end the iteration the way it does in
+// the async version.
+pub fn convert_stream_macro(mac: &syn::Macro) -> syn::Result<Option<syn::Expr>> {
+    let try_stream = match stream_macro_kind(&mac.path) {
+        Some(try_stream) => try_stream,
+        None => return Ok(None),
+    };
+
+    let mut stmts = syn::Block::parse_within.parse2(mac.tokens.clone())?;
+
+    let items = syn::Ident::new(ITEMS_NAME, Span::call_site());
+    let mut visitor = YieldVisitor {
+        items: items.clone(),
+        wrap_ok: try_stream,
+        return_found: None,
+    };
+    for stmt in &mut stmts {
+        visitor.visit_stmt_mut(stmt);
+    }
+
+    if let Some(expr) = visitor.return_found {
+        return Err(syn::Error::new_spanned(
+            expr,
+            "`return` in the body of `stream!`/`try_stream!` can not be converted into sync code: \
+            the body is executed eagerly; use `only_if`/`remove_if` or `sync_impl!`/`async_impl!` \
+            to write the sync variant",
+        ));
+    }
+
+    let expr = if try_stream {
+        let result = syn::Ident::new(RESULT_NAME, Span::call_site());
+        syn::parse_quote!({
+            let mut #items = ::std::vec::Vec::new();
+            let #result: ::core::result::Result<(), _> = (|| {
+                #(#stmts)*
+                ::core::result::Result::Ok(())
+            })();
+            if let ::core::result::Result::Err(err) = #result {
+                #items.push(::core::result::Result::Err(err));
+            }
+            #items.into_iter()
+        })
+    } else {
+        syn::parse_quote!({
+            let mut #items = ::std::vec::Vec::new();
+            #(#stmts)*
+            #items.into_iter()
+        })
+    };
+
+    Ok(Some(expr))
+}
+
+// `pin_mut!(a, b)` -> `let mut a = a; let mut b = b;`
+//
+// `tokio::pin! { let a = ...; }` -> `let mut a = ...;`
+fn convert_pin_macro(mac: &syn::Macro) -> syn::Result<Vec<syn::Stmt>> {
+    let parser = Punctuated::<syn::Ident, Comma>::parse_terminated;
+    if let Ok(idents) = parser.parse2(mac.tokens.clone()) {
+        return Ok(idents
+            .into_iter()
+            .map(|ident| syn::parse_quote!(let mut #ident = #ident;))
+            .collect());
+    }
+
+    let mut stmts = syn::Block::parse_within.parse2(mac.tokens.clone())?;
+    for stmt in &mut stmts {
+        if let syn::Stmt::Local(syn::Local { pat: syn::Pat::Ident(pat), .. }) = stmt {
+            pat.mutability = Some(syn::Token![mut](pat.ident.span()));
+        }
+    }
+
+    Ok(stmts)
+}
+
+// Converts statement macros in the block:
+//
+// - `pin_mut!`/`tokio::pin!` are replaced with plain `let mut` bindings, since iterators need not
+// be pinned;
+//
+// - `stream!`/`try_stream!` in statement position are turned into expressions, so they will be
+// converted by the expression visitor.
+pub fn convert_stream_stmts(block: &mut syn::Block) -> syn::Result<()> {
+    let mut stmts = Vec::with_capacity(block.stmts.len());
+
+    for stmt in block.stmts.drain(..) {
+        match stmt {
+            syn::Stmt::Semi(syn::Expr::Macro(expr), _) if is_pin_macro(&expr.mac.path) => {
+                stmts.extend(convert_pin_macro(&expr.mac)?);
+            }
+            syn::Stmt::Item(syn::Item::Macro(item))
+                if item.ident.is_none() && is_stream_stmt_macro(&item.mac.path) =>
+            {
+                if is_pin_macro(&item.mac.path) {
+                    stmts.extend(convert_pin_macro(&item.mac)?);
+                    continue;
+                }
+
+                let expr = syn::Expr::Macro(syn::ExprMacro {
+                    attrs: item.attrs,
+                    mac: item.mac,
+                });
+                stmts.push(match item.semi_token {
+                    Some(semi) => syn::Stmt::Semi(expr, semi),
+                    None => syn::Stmt::Expr(expr),
+                });
+            }
+            stmt => stmts.push(stmt),
+        }
+    }
+
+    block.stmts = stmts;
+
+    Ok(())
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// The names of the `Stream` traits imported from the paths above. Only these bare names are
+// converted, so a trait of the user named `Stream` is left intact
+pub fn imported_stream_traits(file: &mut syn::File) -> Vec<String> {
+    let mut imports = StreamImports { names: vec![] };
+    imports.visit_file_mut(file);
+    imports.names
+}
+
+struct StreamImports {
+    names: Vec<String>,
+}
+
+impl StreamImports {
+    fn add(&mut self, name: String) {
+        if !self.names.contains(&name) {
+            self.names.push(name);
+        }
+    }
+
+    // `use futures::{Stream, StreamExt};`, `use tokio_stream::Stream as AsyncIter;`
+    fn collect(&mut self, tree: &syn::UseTree, prefix: &mut Vec<String>) {
+        let is_stream_path = |prefix: &[String], name: &syn::Ident| {
+            STREAM_PATHS.contains(&format!("{}::{}", prefix.join("::"), name).as_str())
+        };
+
+        match tree {
+            syn::UseTree::Path(path) => {
+                prefix.push(path.ident.to_string());
+                self.collect(&path.tree, prefix);
+                prefix.pop();
+            }
+            syn::UseTree::Name(name) if is_stream_path(prefix, &name.ident) => {
+                self.add(name.ident.to_string());
+            }
+            syn::UseTree::Rename(rename) if is_stream_path(prefix, &rename.ident) => {
+                self.add(rename.rename.to_string());
+            }
+            syn::UseTree::Glob(_) if STREAM_PATHS.contains(&format!("{}::Stream", prefix.join("::")).as_str()) => {
+                self.add("Stream".to_string());
+            }
+            syn::UseTree::Group(group) => {
+                for item in &group.items {
+                    self.collect(item, prefix);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl VisitMut for StreamImports {
+    fn visit_item_use_mut(&mut self, node: &mut syn::ItemUse) {
+        self.collect(&node.tree, &mut vec![]);
+    }
+}
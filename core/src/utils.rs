@@ -1,4 +1,4 @@
-use proc_macro2::{Span, TokenStream as TokenStream2};
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
 use syn::{
     parenthesized,
     parse::{Parse, ParseStream},
@@ -15,61 +15,6 @@ use quote::ToTokens;
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
-macro_rules! unwrap_or_error {
-    ($res:expr) => {
-        match $res {
-            Ok(p) => p,
-            Err(err) => {
-                abort!(err)
-            }
-        }
-    };
-}
-pub(crate) use unwrap_or_error;
-
-// macro_rules! unwrap2_or_error {
-//     ($res:expr) => {
-//         match $res {
-//             Ok(p) => p,
-//             Err(err) => {
-//                 return proc_macro2::TokenStream::from(syn::Error::from(err).to_compile_error());
-//             }
-//         }
-//     };
-// }
-
-// pub(crate) use unwrap2_or_error;
-
-macro_rules! set_error_and_return {
-    ($err:expr) => {{
-        emit_error!($err);
-        return;
-    }};
-    ($err:expr, $ret:expr) => {{
-        emit_error!($err);
-        return $ret;
-    }};
-}
-pub(crate) use set_error_and_return;
-
-macro_rules! unwrap_or_set_error_and_return {
-    ($res:expr) => {
-        match $res {
-            Ok(p) => p,
-            Err(err) => set_error_and_return!(err),
-        }
-    };
-    ($res:expr, $ret:expr) => {
-        match $res {
-            Ok(p) => p,
-            Err(err) => set_error_and_return!(err, $ret),
-        }
-    };
-}
-pub(crate) use unwrap_or_set_error_and_return;
-
-////////////////////////////////////////////////////////////////////////////////////////////////////
-
 pub(crate) fn make_path(name: &str) -> syn::Path {
     let mut segments = Punctuated::<syn::PathSegment, syn::token::Colon2>::new();
     segments.push_value(syn::PathSegment {
@@ -104,7 +49,7 @@ pub(crate) fn make_nestedmeta_list(
 
 pub(crate) fn make_attr_from_str<S: AsRef<str>>(s: S, span: Span) -> syn::Result<syn::Attribute> {
     let stream: TokenStream2 = format!("#[{}]", s.as_ref()).parse()?;
-    let mut attrs: VecOfAttrs = syn::parse(stream.into())?;
+    let mut attrs: VecOfAttrs = syn::parse2(stream)?;
     let attr = match attrs.attrs.len() {
         1 => attrs.attrs.remove(0),
         _ => return Err(syn::Error::new(span, "Expected attribute")),
@@ -162,6 +107,72 @@ impl Parse for PunctuatedList {
     }
 }
 
+// Visits the expressions among the macro arguments separated by `,` or `;`. Arguments that can not
+// be parsed as expressions (e.g. patterns in `matches!` or `target: "..."` in `tracing::info!`) are
+// kept as is.
+pub fn visit_macro_args(tokens: TokenStream2, mut visit: impl FnMut(&mut Expr)) -> TokenStream2 {
+    if let Ok(mut args) = syn::parse2::<PunctuatedList>(tokens.clone()) {
+        for arg in &mut args.list {
+            visit(arg);
+        }
+        return args.list.into_token_stream();
+    }
+
+    let mut chunks = vec![TokenStream2::new()];
+    let mut separators: Vec<TokenTree> = vec![];
+    for tt in tokens {
+        match &tt {
+            TokenTree::Punct(punct) if punct.as_char() == ',' || punct.as_char() == ';' => {
+                separators.push(tt);
+                chunks.push(TokenStream2::new());
+            }
+            _ => chunks.last_mut().unwrap().extend(std::iter::once(tt)),
+        }
+    }
+
+    let is_comma = |tt: &TokenTree| matches!(tt, TokenTree::Punct(punct) if punct.as_char() == ',');
+
+    let mut result = TokenStream2::new();
+    let mut start = 0;
+    while start < chunks.len() {
+        // an expression may contain commas itself, e.g. `|a, b| a + b` or `Foo::<A, B>::new()`
+        let mut tokens = TokenStream2::new();
+        let mut parsed = None;
+        for end in start..chunks.len() {
+            if end > start {
+                if !is_comma(&separators[end - 1]) {
+                    break;
+                }
+                tokens.extend(std::iter::once(separators[end - 1].clone()));
+            }
+            tokens.extend(chunks[end].clone());
+            if let Ok(expr) = syn::parse2::<Expr>(tokens.clone()) {
+                parsed = Some((expr, end));
+                break;
+            }
+        }
+
+        let end = match parsed {
+            Some((mut expr, end)) => {
+                visit(&mut expr);
+                expr.to_tokens(&mut result);
+                end
+            }
+            None => {
+                result.extend(chunks[start].clone());
+                start
+            }
+        };
+
+        if let Some(separator) = separators.get(end) {
+            result.extend(std::iter::once(separator.clone()));
+        }
+        start = end + 1;
+    }
+
+    result
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
 pub struct DebugByDisplay<T: std::fmt::Display>(pub T);
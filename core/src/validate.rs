@@ -0,0 +1,240 @@
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens};
+use syn::visit_mut::{self, VisitMut};
+
+use crate::{
+    MACRO_REMOVE_NAME,
+    params::MacroParameters,
+    streams::{is_stream_path, stream_macro_kind},
+    visit_ext::{VisitMutExt, Visitor},
+};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Searches the converted sync version for the async constructs left by the convertation, so the
+// user gets an error pointing to the original code (with a hint) instead of a rustc error somewhere
+// in the generated one
+pub struct SyncValidator<'p> {
+    params: &'p MacroParameters,
+}
+
+impl<'p> SyncValidator<'p> {
+    pub fn new(params: &'p MacroParameters) -> Self {
+        Self { params }
+    }
+}
+
+pub fn validate_sync(params: &MacroParameters, file: &syn::File) -> Option<syn::Error> {
+    let mut file = file.clone();
+    RemovedItems { params }.visit_file_mut(&mut file);
+
+    let mut visitor = Visitor::new(SyncValidator::new(params));
+    visitor.visit_file_mut(&mut file);
+    visitor.take_error()
+}
+
+// The items marked by `only_if`/`remove_if` for the other versions get the `remove` attribute, and
+// the compiler removes them after the convertation, so they are not checked
+struct RemovedItems<'p> {
+    params: &'p MacroParameters,
+}
+
+impl<'p> RemovedItems<'p> {
+    fn is_removed(&self, attrs: &[syn::Attribute]) -> bool {
+        attrs.iter().any(|attr| match self.params.is_our_attr(attr) {
+            Some(name) => name == MACRO_REMOVE_NAME,
+            None => false,
+        })
+    }
+}
+
+fn item_attrs(item: &syn::Item) -> &[syn::Attribute] {
+    match item {
+        syn::Item::Const(item) => &item.attrs,
+        syn::Item::Enum(item) => &item.attrs,
+        syn::Item::ExternCrate(item) => &item.attrs,
+        syn::Item::Fn(item) => &item.attrs,
+        syn::Item::ForeignMod(item) => &item.attrs,
+        syn::Item::Impl(item) => &item.attrs,
+        syn::Item::Macro(item) => &item.attrs,
+        syn::Item::Macro2(item) => &item.attrs,
+        syn::Item::Mod(item) => &item.attrs,
+        syn::Item::Static(item) => &item.attrs,
+        syn::Item::Struct(item) => &item.attrs,
+        syn::Item::Trait(item) => &item.attrs,
+        syn::Item::TraitAlias(item) => &item.attrs,
+        syn::Item::Type(item) => &item.attrs,
+        syn::Item::Union(item) => &item.attrs,
+        syn::Item::Use(item) => &item.attrs,
+        _ => &[],
+    }
+}
+
+impl<'p> VisitMut for RemovedItems<'p> {
+    fn visit_file_mut(&mut self, node: &mut syn::File) {
+        node.items.retain(|item| !self.is_removed(item_attrs(item)));
+        visit_mut::visit_file_mut(self, node);
+    }
+
+    fn visit_item_mod_mut(&mut self, node: &mut syn::ItemMod) {
+        if let Some((_, items)) = &mut node.content {
+            items.retain(|item| !self.is_removed(item_attrs(item)));
+        }
+        visit_mut::visit_item_mod_mut(self, node);
+    }
+
+    fn visit_block_mut(&mut self, node: &mut syn::Block) {
+        node.stmts.retain(|stmt| match stmt {
+            syn::Stmt::Item(item) => !self.is_removed(item_attrs(item)),
+            _ => true,
+        });
+        visit_mut::visit_block_mut(self, node);
+    }
+
+    fn visit_item_impl_mut(&mut self, node: &mut syn::ItemImpl) {
+        node.items.retain(|item| {
+            let attrs: &[syn::Attribute] = match item {
+                syn::ImplItem::Const(item) => &item.attrs,
+                syn::ImplItem::Method(item) => &item.attrs,
+                syn::ImplItem::Type(item) => &item.attrs,
+                syn::ImplItem::Macro(item) => &item.attrs,
+                _ => &[],
+            };
+            !self.is_removed(attrs)
+        });
+        visit_mut::visit_item_impl_mut(self, node);
+    }
+
+    fn visit_item_trait_mut(&mut self, node: &mut syn::ItemTrait) {
+        node.items.retain(|item| {
+            let attrs: &[syn::Attribute] = match item {
+                syn::TraitItem::Const(item) => &item.attrs,
+                syn::TraitItem::Method(item) => &item.attrs,
+                syn::TraitItem::Type(item) => &item.attrs,
+                syn::TraitItem::Macro(item) => &item.attrs,
+                _ => &[],
+            };
+            !self.is_removed(attrs)
+        });
+        visit_mut::visit_item_trait_mut(self, node);
+    }
+}
+
+fn leftover<T: ToTokens>(tokens: T, what: &str, hint: &str) -> syn::Error {
+    syn::Error::new_spanned(
+        tokens,
+        format!("{} is left in the sync version; {}", what, hint),
+    )
+}
+
+// `.await` or `async {`, `async move`, `async |` in the macro tokens
+fn search_async_tokens(tokens: TokenStream2) -> Option<(TokenStream2, &'static str)> {
+    let mut iter = tokens.into_iter().peekable();
+
+    while let Some(tt) = iter.next() {
+        match tt {
+            TokenTree::Group(group) => {
+                if let Some(found) = search_async_tokens(group.stream()) {
+                    return Some(found);
+                }
+            }
+            TokenTree::Ident(ident) if ident == "async" => {
+                let is_async = match iter.peek() {
+                    Some(TokenTree::Ident(next)) => next == "move",
+                    Some(TokenTree::Group(group)) => group.delimiter() == proc_macro2::Delimiter::Brace,
+                    Some(TokenTree::Punct(punct)) => punct.as_char() == '|',
+                    _ => false,
+                };
+                if is_async {
+                    return Some((ident.into_token_stream(), "`async` block"));
+                }
+            }
+            TokenTree::Punct(punct) if punct.as_char() == '.' => {
+                if let Some(TokenTree::Ident(next)) = iter.peek() {
+                    if next == "await" {
+                        return Some((quote!(#punct #next), "`.await`"));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+impl<'p> VisitMutExt for Visitor<SyncValidator<'p>> {
+    fn process_attribute(&mut self, node: &mut syn::Attribute) -> syn::Result<()> {
+        match node.path.segments.last() {
+            Some(last) if last.ident == "async_trait" => Err(leftover(
+                &node,
+                "`#[async_trait]` attribute",
+                "use `trait_backend = \"async_trait\"` to add it to the async version only",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn process_expr(&mut self, node: &mut syn::Expr) -> syn::Result<()> {
+        match node {
+            syn::Expr::Closure(syn::ExprClosure { asyncness: Some(asyncness), .. }) => Err(leftover(
+                asyncness,
+                "`async` closure",
+                "move it into `async_impl!` and write the sync variant in `sync_impl!`",
+            )),
+            syn::Expr::Async(expr) => Err(leftover(
+                expr.async_token,
+                "`async` block",
+                "move it into `async_impl!` and write the sync variant in `sync_impl!`",
+            )),
+            syn::Expr::Await(expr) => Err(leftover(
+                expr,
+                "`.await`",
+                "move it into `async_impl!` and write the sync variant in `sync_impl!`",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // The arguments of the known macros are converted already, so only the opaque ones can
+    // contain something async
+    fn process_macro(&mut self, node: &mut syn::Macro) -> syn::Result<()> {
+        let (tokens, what) = match search_async_tokens(node.tokens.clone()) {
+            Some(found) => found,
+            None => return Ok(()),
+        };
+
+        let hint = if stream_macro_kind(&node.path).is_some() {
+            "use the `streams` parameter to convert streams into iterators"
+        } else {
+            "add the macro to `macros(...)` if its arguments are expressions"
+        };
+
+        Err(leftover(tokens, &format!("{} in the macro arguments", what), hint))
+    }
+
+    fn process_trait_bound(&mut self, node: &mut syn::TraitBound) -> syn::Result<()> {
+        let last = match node.path.segments.last() {
+            Some(last) => last,
+            None => return Ok(()),
+        };
+
+        if last.ident == "Future" {
+            Err(leftover(
+                &last.ident,
+                "`Future` bound",
+                "only `impl Future` types and the generic parameters bounded by `Future<Output = ...>` \
+                are converted, use `only_if`/`remove_if` for the rest",
+            ))
+        } else if is_stream_path(&node.path, self.inner.params.stream_traits_get()) {
+            // with the `streams` parameter these bounds are converted
+            Err(leftover(
+                &last.ident,
+                "`Stream` bound",
+                "use the `streams` parameter to convert streams into iterators",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -1,17 +1,12 @@
 #[allow(unused_imports)]
 use std::iter::FromIterator;
 
-#[allow(unused_imports)]
-use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use proc_macro_error::{emit_error, abort};
 use quote::quote;
 use syn::{
     visit_mut::{self, VisitMut},
 };
 
-use crate::utils::unwrap_or_error;
-
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -31,6 +26,9 @@ pub trait VisitMutExt {
     fn process_attribute(&mut self, _node: &mut syn::Attribute) -> syn::Result<()> {
         Ok(())
     }
+    fn process_block(&mut self, _node: &mut syn::Block) -> syn::Result<()> {
+        Ok(())
+    }
     fn process_expr(&mut self, _node: &mut syn::Expr) -> syn::Result<()> {
         Ok(())
     }
@@ -49,12 +47,24 @@ pub trait VisitMutExt {
     fn process_item_fn(&mut self, _node: &mut syn::ItemFn) -> syn::Result<()> {
         Ok(())
     }
+    fn process_item_use(&mut self, _node: &mut syn::ItemUse) -> syn::Result<()> {
+        Ok(())
+    }
     fn process_macro(&mut self, _node: &mut syn::Macro) -> syn::Result<()> {
         Ok(())
     }
+    fn process_path(&mut self, _node: &mut syn::Path) -> syn::Result<()> {
+        Ok(())
+    }
     fn process_path_segment(&mut self, _node: &mut syn::PathSegment) -> syn::Result<()> {
         Ok(())
     }
+    fn process_trait_bound(&mut self, _node: &mut syn::TraitBound) -> syn::Result<()> {
+        Ok(())
+    }
+    fn process_type(&mut self, _node: &mut syn::Type) -> syn::Result<()> {
+        Ok(())
+    }
     fn process_type_param(&mut self, _node: &mut syn::TypeParam) -> syn::Result<()> {
         Ok(())
     }
@@ -65,34 +75,54 @@ pub trait VisitMutExt {
     fn after_process_item(&mut self, _node: &mut syn::Item) -> syn::Result<()> {
         Ok(())
     }
+
+    // Called for every item (or method) declaring generic parameters, before and after its content
+    fn process_generics_scope(&mut self, _node: &mut syn::Generics) -> syn::Result<()> {
+        Ok(())
+    }
+    fn after_process_generics_scope(&mut self, _node: &mut syn::Generics) -> syn::Result<()> {
+        Ok(())
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
 pub struct Visitor<T> {
     pub inner: T,
-//    err: Option<syn::Error>,
+    err: Option<syn::Error>,
 }
 
 impl<T> Visitor<T> {
     pub fn new(inner: T) -> Self {
-        Self { inner }
+        Self { inner, err: None }
+    }
+
+    // The errors are collected, so the visiting continues and all of them are reported at once
+    pub fn emit_error(&mut self, err: syn::Error) {
+        match &mut self.err {
+            Some(prev) => prev.combine(err),
+            None => self.err = Some(err),
+        }
+    }
+
+    pub fn take_error(&mut self) -> Option<syn::Error> {
+        self.err.take()
     }
 
-    pub fn process(&mut self, item: TokenStream2) -> TokenStream2
+    pub fn process(&mut self, item: TokenStream2) -> syn::Result<TokenStream2>
     where
         Self: VisitMutExt,
     {
-        let mut syntax_tree: syn::File = unwrap_or_error!(syn::parse(item.into()));
+        let mut syntax_tree: syn::File = syn::parse2(item)?;
         self.visit_file_mut(&mut syntax_tree);
-        let ts = quote!(#syntax_tree);
+        if let Some(err) = self.take_error() {
+            return Err(err);
+        }
 
-        ts
+        Ok(quote!(#syntax_tree))
     }
 }
 
-
-
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
 macro_rules! impl_fn {
@@ -107,16 +137,16 @@ macro_rules! impl_fn {
             match $self.$proc( impl_fn!(@expr value, $expr) $(, $mode)? ) {
                 Ok(_) => {},
                 Err(e) => {
-                    emit_error!(e)
+                    $self.emit_error(e)
                 }
             };
         };
     };
-    (@func $self:expr, $node:ident, $proc:ident(node $(.$path:ident)? $(, $mode:expr)?) ) => {
-        match $self.$proc( impl_fn!(@arg $node $(.$path)?) $(, $mode)? ) {
+    (@func $self:expr, $node:ident, $proc:ident(node $(.$path:ident)* $(, $mode:expr)?) ) => {
+        match $self.$proc( impl_fn!(@arg $node $(.$path)*) $(, $mode)? ) {
             Ok(_) => {},
             Err(e) => {
-                emit_error!(e)
+                $self.emit_error(e)
             }
         };
     };
@@ -167,7 +197,7 @@ impl<T> VisitMut for Visitor<T> where Self: VisitMutExt,
     impl_fn!(visit_bare_fn_arg_mut,         syn::BareFnArg,         { process_attrs(node.attrs); });
     impl_fn!(visit_bin_op_mut,              syn::BinOp,             );
     impl_fn!(visit_binding_mut,             syn::Binding,           { process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_block_mut,               syn::Block,             );
+    impl_fn!(visit_block_mut,               syn::Block,             { process_block(node); });
     impl_fn!(visit_bound_lifetimes_mut,     syn::BoundLifetimes,    );
     impl_fn!(visit_const_param_mut,         syn::ConstParam,        { process_attrs(node.attrs); });
     impl_fn!(visit_constraint_mut,          syn::Constraint,        );
@@ -239,26 +269,34 @@ impl<T> VisitMut for Visitor<T> where Self: VisitMutExt,
     impl_fn!(visit_impl_item_mut,           syn::ImplItem,          );
     impl_fn!(visit_impl_item_const_mut,     syn::ImplItemConst,     { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_impl_item_macro_mut,     syn::ImplItemMacro,     { process_attrs(node.attrs); });
-    impl_fn!(visit_impl_item_method_mut,    syn::ImplItemMethod,    { process_attrs(node.attrs); });
+    impl_fn!(visit_impl_item_method_mut,    syn::ImplItemMethod,    { process_attrs(node.attrs); process_generics_scope(node.sig.generics); },
+                                                                    { after_process_generics_scope(node.sig.generics); });
     impl_fn!(visit_impl_item_type_mut,      syn::ImplItemType,      { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_index_mut,               syn::Index,             );
     impl_fn!(visit_item_mut,                syn::Item,              { process_item(node); },            { after_process_item(node); });
     impl_fn!(visit_item_const_mut,          syn::ItemConst,         { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_item_enum_mut,           syn::ItemEnum,          { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
+    impl_fn!(visit_item_enum_mut,           syn::ItemEnum,          { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); process_generics_scope(node.generics); },
+                                                                    { after_process_generics_scope(node.generics); });
     impl_fn!(visit_item_extern_crate_mut,   syn::ItemExternCrate,   { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); process_ident(node.rename as Some((_, value)), IdentMode::Other); });
-    impl_fn!(visit_item_fn_mut,             syn::ItemFn,            { process_attrs(node.attrs); process_item_fn(node); });
+    impl_fn!(visit_item_fn_mut,             syn::ItemFn,            { process_attrs(node.attrs); process_item_fn(node); process_generics_scope(node.sig.generics); },
+                                                                    { after_process_generics_scope(node.sig.generics); });
     impl_fn!(visit_item_foreign_mod_mut,    syn::ItemForeignMod,    { process_attrs(node.attrs); });
-    impl_fn!(visit_item_impl_mut,           syn::ItemImpl,          { process_attrs(node.attrs); process_item_impl(node); });
+    impl_fn!(visit_item_impl_mut,           syn::ItemImpl,          { process_attrs(node.attrs); process_item_impl(node); process_generics_scope(node.generics); },
+                                                                    { after_process_generics_scope(node.generics); });
     impl_fn!(visit_item_macro_mut,          syn::ItemMacro,         { process_attrs(node.attrs); process_ident(node.ident as Some(value), IdentMode::Other); });
     impl_fn!(visit_item_macro2_mut,         syn::ItemMacro2,        { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_item_mod_mut,            syn::ItemMod,           { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_item_static_mut,         syn::ItemStatic,        { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_item_struct_mut,         syn::ItemStruct,        { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_item_trait_mut,          syn::ItemTrait,         { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); process_item_trait(node); });
+    impl_fn!(visit_item_struct_mut,         syn::ItemStruct,        { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); process_generics_scope(node.generics); },
+                                                                    { after_process_generics_scope(node.generics); });
+    impl_fn!(visit_item_trait_mut,          syn::ItemTrait,         { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); process_item_trait(node); process_generics_scope(node.generics); },
+                                                                    { after_process_generics_scope(node.generics); });
     impl_fn!(visit_item_trait_alias_mut,    syn::ItemTraitAlias,    { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_item_type_mut,           syn::ItemType,          { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_item_union_mut,          syn::ItemUnion,         { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_item_use_mut,            syn::ItemUse,           { process_attrs(node.attrs); });
+    impl_fn!(visit_item_type_mut,           syn::ItemType,          { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); process_generics_scope(node.generics); },
+                                                                    { after_process_generics_scope(node.generics); });
+    impl_fn!(visit_item_union_mut,          syn::ItemUnion,         { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); process_generics_scope(node.generics); },
+                                                                    { after_process_generics_scope(node.generics); });
+    impl_fn!(visit_item_use_mut,            syn::ItemUse,           { process_attrs(node.attrs); process_item_use(node); });
     impl_fn!(visit_label_mut,               syn::Label,             );
     impl_fn!(visit_lifetime_mut,            syn::Lifetime,          );
     impl_fn!(visit_lifetime_def_mut,        syn::LifetimeDef,       { process_attrs(node.attrs); });
@@ -298,7 +336,7 @@ impl<T> VisitMut for Visitor<T> where Self: VisitMutExt,
     impl_fn!(visit_pat_tuple_struct_mut,    syn::PatTupleStruct,    { process_attrs(node.attrs); });
     impl_fn!(visit_pat_type_mut,            syn::PatType,           { process_attrs(node.attrs); });
     impl_fn!(visit_pat_wild_mut,            syn::PatWild,           { process_attrs(node.attrs); });
-    impl_fn!(visit_path_mut,                syn::Path,              );
+    impl_fn!(visit_path_mut,                syn::Path,              { process_path(node); });
     impl_fn!(visit_path_arguments_mut,      syn::PathArguments,     );
     impl_fn!(visit_path_segment_mut,        syn::PathSegment,       { process_path_segment(node); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_predicate_eq_mut,        syn::PredicateEq,       );
@@ -311,14 +349,15 @@ impl<T> VisitMut for Visitor<T> where Self: VisitMutExt,
     impl_fn!(visit_signature_mut,           syn::Signature,         { process_ident(node.ident, IdentMode::Other); } );
     impl_fn!(visit_span_mut,                Span,                   );
     impl_fn!(visit_stmt_mut,                syn::Stmt,              );
-    impl_fn!(visit_trait_bound_mut,         syn::TraitBound,        );
+    impl_fn!(visit_trait_bound_mut,         syn::TraitBound,        { process_trait_bound(node); });
     impl_fn!(visit_trait_bound_modifier_mut,syn::TraitBoundModifier,);
     impl_fn!(visit_trait_item_mut,          syn::TraitItem,         );
     impl_fn!(visit_trait_item_const_mut,    syn::TraitItemConst,    { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_trait_item_macro_mut,    syn::TraitItemMacro,    { process_attrs(node.attrs); });
-    impl_fn!(visit_trait_item_method_mut,   syn::TraitItemMethod,   { process_attrs(node.attrs); });
+    impl_fn!(visit_trait_item_method_mut,   syn::TraitItemMethod,   { process_attrs(node.attrs); process_generics_scope(node.sig.generics); },
+                                                                    { after_process_generics_scope(node.sig.generics); });
     impl_fn!(visit_trait_item_type_mut,     syn::TraitItemType,     { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_type_mut,                syn::Type,              );
+    impl_fn!(visit_type_mut,                syn::Type,              { process_type(node); });
     impl_fn!(visit_type_array_mut,          syn::TypeArray,         );
     impl_fn!(visit_type_bare_fn_mut,        syn::TypeBareFn,        );
     impl_fn!(visit_type_group_mut,          syn::TypeGroup,         );
@@ -0,0 +1,1376 @@
+#[allow(unused_imports)]
+use std::{cell::RefCell, collections::HashMap, iter::FromIterator};
+
+use proc_macro2::{Delimiter, Group, Span, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{parse::Parser, visit_mut::VisitMut, spanned::Spanned};
+
+use crate::{
+    MACRO_NOOP_NAME, MACRO_REMOVE_NAME, MACRO_ONLY_IF_NAME, MACRO_REMOVE_IF_NAME,
+    MACRO_SYNC_IMPL_NAME, MACRO_ASYNC_IMPL_NAME, MACRO_VERSION_IMPL_NAME,
+    params::{ConvertMode, MacroParameters, TraitBackend},
+    utils::{AttributeArgsInParens, EqStr, make_path, make_attr_from_str, visit_macro_args},
+    visit_ext::{IdentMode, VisitMutExt, Visitor},
+    doctests::{convert_doctest, process_doctests, remove_runtime_attrs, process_prose, rename_links, CodeBlock},
+    io::{convert_io_path, convert_io_use, remove_io_unpin_in_generics, remove_io_unpin_in_type},
+    paths::{convert_lock_await, convert_path, convert_use, path_to_string},
+    combinators::{convert_combinator_stmts, convert_combinators, is_join_macro, is_select_macro, visit_select_macro},
+    streams::{convert_stream_bound, convert_stream_constructor, convert_stream_macro, convert_stream_stmts},
+};
+
+pub struct AsyncAwaitVisitor<'p> {
+    convert_mode: ConvertMode,
+    params: &'p mut MacroParameters,
+    generics: Vec<HashMap<String, syn::Type>>,
+}
+
+impl<'p> AsyncAwaitVisitor<'p> {
+    pub fn new(params: &'p mut MacroParameters, convert_mode: ConvertMode) -> Self {
+        Self {
+            convert_mode,
+            params,
+            generics: vec![],
+        }
+    }
+
+    // The innermost scope wins, as the generic parameters of a method shadow the impl ones
+    fn generics_get<S: AsRef<str>>(&self, key: S) -> Option<&syn::Type> {
+        for gens in self.generics.iter().rev() {
+            if let Some(ty) = gens.get(key.as_ref()) {
+                return Some(ty);
+            }
+        }
+
+        None
+    }
+}
+
+// `T: Future<Output = X>` -> X
+fn search_future_bound_output(bound: &syn::TypeParamBound) -> Option<syn::Type> {
+    let trait_bound = match bound {
+        syn::TypeParamBound::Trait(trait_bound) => trait_bound,
+        _ => return None,
+    };
+
+    let segment = trait_bound.path.segments.last()?;
+    if segment.ident != "Future" {
+        return None;
+    }
+
+    // match Future<Output=Type>
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Binding(binding) if binding.ident == "Output" => {
+                Some(binding.ty.clone())
+            }
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+// `F` -> Some("F"), but `Vec<F>`, `F::Output` or `&F` -> None
+fn generic_param_name(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path.get_ident().map(|i| i.to_string()),
+        _ => None,
+    }
+}
+
+// `<T, F: Future<Output = X>>` or `<T, F> where F: Future<Output = X>` -> { "F": X }
+fn search_future_generics(generics: &syn::Generics) -> syn::Result<HashMap<String, syn::Type>> {
+    let mut gens = HashMap::new();
+
+    // generic params: <T:Future<Output=()>, F>
+    for param in &generics.params {
+        // generic param: T:Future<Output=()>
+        if let syn::GenericParam::Type(type_param) = param {
+            // bound: Future<Output=()>
+            for bound in &type_param.bounds {
+                if let Some(output) = search_future_bound_output(bound) {
+                    gens.insert(type_param.ident.to_string(), output);
+                }
+            }
+        }
+    }
+
+    if let Some(where_clause) = &generics.where_clause {
+        for predicate in &where_clause.predicates {
+            if let syn::WherePredicate::Type(predicate_type) = predicate {
+                for bound in &predicate_type.bounds {
+                    let output = match search_future_bound_output(bound) {
+                        Some(output) => output,
+                        None => continue,
+                    };
+
+                    match generic_param_name(&predicate_type.bounded_ty) {
+                        Some(name) => {
+                            gens.insert(name, output);
+                        }
+                        None => {
+                            return Err(syn::Error::new_spanned(
+                                &predicate_type.bounded_ty,
+                                "Only generic parameters bounded by `Future<Output = ...>` can be \
+                                replaced with the output type in the sync version",
+                            ))
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(gens)
+}
+
+// Removes the replaced generic parameters and their where predicates
+fn remove_future_generics(generics: &mut syn::Generics, gens: &HashMap<String, syn::Type>) {
+    if gens.is_empty() {
+        return;
+    }
+
+    // remove generic type from generics <T, F>
+    let params = generics
+        .params
+        .iter()
+        .filter(|param| match param {
+            syn::GenericParam::Type(type_param) => !gens.contains_key(&type_param.ident.to_string()),
+            _ => true,
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    generics.params = syn::punctuated::Punctuated::from_iter(params);
+
+    // remove generic type from where clause
+    if let Some(where_clause) = &mut generics.where_clause {
+        let predicates = where_clause
+            .predicates
+            .iter()
+            .filter(|predicate| match predicate {
+                syn::WherePredicate::Type(predicate_type) => {
+                    match generic_param_name(&predicate_type.bounded_ty) {
+                        Some(name) => !gens.contains_key(&name),
+                        None => true,
+                    }
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        where_clause.predicates = syn::punctuated::Punctuated::from_iter(predicates);
+    }
+}
+
+// `Task<F, T>` -> `Task<T>`
+fn remove_future_generic_arguments(ty: &mut syn::Type, gens: &HashMap<String, syn::Type>) {
+    if let syn::Type::Path(syn::TypePath { qself: None, path }) = ty {
+        if let Some(last) = path.segments.last_mut() {
+            remove_future_generic_arguments_in_segment(last, gens);
+        }
+    }
+}
+
+fn remove_future_generic_arguments_in_segment(
+    segment: &mut syn::PathSegment,
+    gens: &HashMap<String, syn::Type>,
+) {
+    let args = match &mut segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args,
+        _ => return,
+    };
+
+    let kept = args
+        .args
+        .iter()
+        .filter(|arg| match arg {
+            syn::GenericArgument::Type(ty) => match generic_param_name(ty) {
+                Some(name) => !gens.contains_key(&name),
+                None => true,
+            },
+            _ => true,
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if kept.is_empty() {
+        segment.arguments = syn::PathArguments::None;
+    } else {
+        args.args = syn::punctuated::Punctuated::from_iter(kept);
+    }
+}
+
+fn search_future_output(bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>) -> Option<syn::Type> {
+    for bound in bounds {
+        if let syn::TypeParamBound::Trait(trait_bound) = bound {
+            let segment = match trait_bound.path.segments.last() {
+                Some(segment) => segment,
+                None => continue,
+            };
+            if segment.ident != "Future" {
+                continue;
+            }
+            // match Future<Output=Type>
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                for arg in &args.args {
+                    if let syn::GenericArgument::Binding(binding) = arg {
+                        if binding.ident == "Output" {
+                            return Some(binding.ty.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn search_type_argument(segment: &syn::PathSegment) -> Option<&syn::Type> {
+    if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+        for arg in &args.args {
+            if let syn::GenericArgument::Type(ty) = arg {
+                return Some(ty);
+            }
+        }
+    }
+
+    None
+}
+
+pub fn search_future_output_in_type(ty: &syn::Type) -> Option<syn::Type> {
+    match ty {
+        // impl Future<Output = T> + Send + 'a
+        syn::Type::ImplTrait(impl_trait) => search_future_output(&impl_trait.bounds),
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            let segment = path.segments.last()?;
+            match segment.ident.to_string().as_str() {
+                // BoxFuture<'a, T>, LocalBoxFuture<'a, T>
+                "BoxFuture" | "LocalBoxFuture" => search_type_argument(segment).cloned(),
+                // Pin<Box<dyn Future<Output = T> + Send + 'a>>
+                "Pin" => {
+                    let boxed = match search_type_argument(segment)? {
+                        syn::Type::Path(syn::TypePath { qself: None, path }) => path.segments.last()?,
+                        _ => return None,
+                    };
+                    if boxed.ident != "Box" {
+                        return None;
+                    }
+                    match search_type_argument(boxed)? {
+                        syn::Type::TraitObject(object) => search_future_output(&object.bounds),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn is_box_pin_path(expr: &syn::Expr) -> bool {
+    if let syn::Expr::Path(syn::ExprPath { qself: None, path, .. }) = expr {
+        let len = path.segments.len();
+        if len >= 2 {
+            return path.segments[len - 2].ident == "Box" && path.segments[len - 1].ident == "pin";
+        }
+    }
+
+    false
+}
+
+// Box::pin(async move { ... }), async move { ... }.boxed()
+fn search_boxed_async(expr: &syn::Expr) -> Option<&syn::ExprAsync> {
+    match expr {
+        syn::Expr::Call(call) if call.args.len() == 1 && is_box_pin_path(&call.func) => {
+            match &call.args[0] {
+                syn::Expr::Async(inner) => Some(inner),
+                _ => None,
+            }
+        }
+        syn::Expr::MethodCall(call)
+            if call.args.is_empty() && (call.method == "boxed" || call.method == "boxed_local") =>
+        {
+            match &*call.receiver {
+                syn::Expr::Async(inner) => Some(inner),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn remove_future_on_sig(sig: &mut syn::Signature, block: Option<&mut syn::Block>) {
+    // `fut: impl Future<Output = T>` -> `fut: T`
+    for input in &mut sig.inputs {
+        if let syn::FnArg::Typed(arg) = input {
+            if let syn::Type::ImplTrait(impl_trait) = &*arg.ty {
+                if let Some(output) = search_future_output(&impl_trait.bounds) {
+                    *arg.ty = output;
+                }
+            }
+        }
+    }
+
+    let output = match &sig.output {
+        syn::ReturnType::Type(_, ty) => search_future_output_in_type(ty),
+        syn::ReturnType::Default => None,
+    };
+
+    if let Some(output) = output {
+        if let syn::ReturnType::Type(_, ty) = &mut sig.output {
+            **ty = output;
+        }
+
+        // { async move { ... } } -> { ... }
+        // { Box::pin(async move { ... }) } -> { ... }
+        if let Some(block) = block {
+            if block.stmts.len() == 1 {
+                if let syn::Stmt::Expr(expr) = &block.stmts[0] {
+                    let inner = match expr {
+                        syn::Expr::Async(inner) => Some(inner),
+                        expr => search_boxed_async(expr),
+                    };
+                    if let Some(inner) = inner {
+                        *block = inner.block.clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+// `AsyncFn(T) -> U` -> `Fn(T) -> U` (also `AsyncFnMut` and `AsyncFnOnce`)
+fn convert_async_fn_bound(bound: &mut syn::TraitBound) {
+    if let Some(last) = bound.path.segments.last_mut() {
+        let name = match last.ident.to_string().as_str() {
+            "AsyncFn" => "Fn",
+            "AsyncFnMut" => "FnMut",
+            "AsyncFnOnce" => "FnOnce",
+            _ => return,
+        };
+        last.ident = syn::Ident::new(name, last.ident.span());
+    }
+}
+
+// `paths` are the full paths of the attribute (and the imported name, if it can be imported)
+fn has_attr_with_path(attrs: &[syn::Attribute], paths: &[&str]) -> bool {
+    attrs.iter().any(|attr| paths.contains(&path_to_string(&attr.path).as_str()))
+}
+
+// Adds `#[async_trait::async_trait]` (or `#[async_trait::async_trait(?Send)]`) unless the item is
+// already annotated with it
+fn add_async_trait_attr(attrs: &mut Vec<syn::Attribute>, send: Option<bool>, span: Span) {
+    if has_attr_with_path(attrs, &["async_trait", "async_trait::async_trait"]) {
+        return;
+    }
+
+    let attr_str = if send.unwrap_or(true) {
+        "async_trait::async_trait"
+    } else {
+        "async_trait::async_trait(?Send)"
+    };
+    attrs.push(make_attr_from_str(attr_str, span).unwrap());
+}
+
+// `async fn f(&self) -> T { ... }` -> `fn f(&self) -> impl Future<Output = T> + Send { async move { ... } }`
+fn desugar_async_method(sig: &mut syn::Signature, block: Option<&mut syn::Block>, send: bool) {
+    if sig.asyncness.is_none() {
+        return;
+    }
+    sig.asyncness = None;
+
+    let output = match &sig.output {
+        syn::ReturnType::Default => quote!(()),
+        syn::ReturnType::Type(_, ty) => quote!(#ty),
+    };
+    sig.output = if send {
+        syn::parse_quote!(-> impl ::core::future::Future<Output = #output> + ::core::marker::Send)
+    } else {
+        syn::parse_quote!(-> impl ::core::future::Future<Output = #output>)
+    };
+
+    if let Some(block) = block {
+        *block = syn::parse_quote!({ async move #block });
+    }
+}
+
+pub fn remove_asyncness_on_trait(
+    item: &mut syn::ItemTrait,
+    convert_mode: ConvertMode,
+    send: Option<bool>,
+    trait_backend: Option<TraitBackend>,
+) {
+    let span = item.span();
+    match convert_mode {
+        ConvertMode::IntoSync => {
+            for inner in &mut item.items {
+                if let syn::TraitItem::Method(ref mut method) = inner {
+                    if method.sig.asyncness.is_some() {
+                        method.sig.asyncness = None;
+                    }
+                    remove_future_on_sig(&mut method.sig, method.default.as_mut());
+                }
+            }
+        }
+        ConvertMode::IntoBlocking => {}
+        ConvertMode::IntoAsync => match trait_backend {
+            Some(TraitBackend::AsyncTrait) => {
+                add_async_trait_attr(&mut item.attrs, send, span);
+            }
+            Some(TraitBackend::Native) if send == Some(true) => {
+                for inner in &mut item.items {
+                    if let syn::TraitItem::Method(ref mut method) = inner {
+                        desugar_async_method(&mut method.sig, method.default.as_mut(), true);
+                    }
+                }
+            }
+            Some(TraitBackend::TraitVariant)
+                if send != Some(false) && !has_attr_with_path(&item.attrs, &["trait_variant::make"]) =>
+            {
+                // `trait_variant::make` does not wrap default bodies of `async fn` into
+                // `async` blocks, so these methods are desugared here
+                for inner in &mut item.items {
+                    if let syn::TraitItem::Method(ref mut method) = inner {
+                        if method.default.is_some() {
+                            desugar_async_method(&mut method.sig, method.default.as_mut(), false);
+                        }
+                    }
+                }
+
+                let attr = make_attr_from_str("trait_variant::make(Send)", span).unwrap();
+                item.attrs.push(attr);
+            }
+            Some(TraitBackend::Native) | Some(TraitBackend::TraitVariant) | None => {}
+        },
+    }
+}
+
+pub fn remove_asyncness_on_impl(
+    item: &mut syn::ItemImpl,
+    convert_mode: ConvertMode,
+    send: Option<bool>,
+    trait_backend: Option<TraitBackend>,
+) {
+    let span = item.span();
+    match convert_mode {
+        ConvertMode::IntoSync => {
+            for inner in &mut item.items {
+                if let syn::ImplItem::Method(ref mut method) = inner {
+                    if method.sig.asyncness.is_some() {
+                        method.sig.asyncness = None;
+                    }
+                    remove_future_on_sig(&mut method.sig, Some(&mut method.block));
+                }
+            }
+        }
+        ConvertMode::IntoBlocking => {}
+        ConvertMode::IntoAsync => match trait_backend {
+            Some(TraitBackend::AsyncTrait) => {
+                if item.trait_.is_some() {
+                    add_async_trait_attr(&mut item.attrs, send, span);
+                }
+            }
+            // native `async fn` in impls satisfies both plain and desugared trait methods
+            Some(TraitBackend::Native) | Some(TraitBackend::TraitVariant) => {}
+            // without the backend only impls are annotated, traits must be annotated manually
+            None => {
+                if send.is_some() {
+                    add_async_trait_attr(&mut item.attrs, send, span);
+                }
+            }
+        },
+    }
+}
+
+pub fn remove_asyncness_on_fn(item: &mut syn::ItemFn, convert_mode: ConvertMode) {
+    match convert_mode {
+        ConvertMode::IntoSync => {
+            if item.sig.asyncness.is_some() {
+                item.sig.asyncness = None;
+            }
+            remove_future_on_sig(&mut item.sig, Some(&mut item.block));
+        }
+        ConvertMode::IntoAsync | ConvertMode::IntoBlocking => {}
+    }
+}
+
+// `a`, `"a"` or `key = "a"` -> "a"
+fn nested_meta_to_key(arg: &syn::NestedMeta) -> syn::Result<String> {
+    match arg {
+        syn::NestedMeta::Lit(syn::Lit::Str(s)) => Ok(s.value()),
+        syn::NestedMeta::Meta(syn::Meta::Path(ref p)) => match p.get_ident() {
+            Some(s) => Ok(s.to_string()),
+            None => Err(syn::Error::new_spanned(arg.to_token_stream(), "Wrong ident")),
+        },
+        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+            path,
+            lit: syn::Lit::Str(value),
+            ..
+        })) if path.is_ident("key") => Ok(value.value()),
+        _ => Err(syn::Error::new_spanned(arg.to_token_stream(), "Wrong ident")),
+    }
+}
+
+// `key = "a", b, { ... }`
+struct VersionImplArgs {
+    keys: Vec<String>,
+    block: syn::Block,
+}
+
+impl syn::parse::Parse for VersionImplArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut keys = vec![];
+        while !input.peek(syn::token::Brace) {
+            let arg: syn::NestedMeta = input.parse()?;
+            keys.push(nested_meta_to_key(&arg)?);
+            input.parse::<syn::Token![,]>()?;
+        }
+        if keys.is_empty() {
+            return Err(input.error("Expected key"));
+        }
+
+        let block = input.parse()?;
+        input.parse::<Option<syn::Token![,]>>()?;
+
+        Ok(Self { keys, block })
+    }
+}
+
+impl<'p> AsyncAwaitVisitor<'p> {
+    fn process_replace_features_meta(&self, meta: &mut syn::Meta) -> syn::Result<bool> {
+        let mut changed = false;
+
+        match meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: syn::Lit::Str(s),
+                ..
+            }) => {
+                if let Some(ident) = path.get_ident() {
+                    if ident.to_string() == "feature" {
+                        let prev = s.value();
+                        if let Some(new) = self.params.replace_features_get(&prev) {
+                            *s = syn::LitStr::new(new, s.span());
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            syn::Meta::List(list) => {
+                for nm in &mut list.nested {
+                    if let syn::NestedMeta::Meta(m) = nm {
+                        changed |= self.process_replace_features_meta(m)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(changed)
+    }
+
+    // The predicates of `cfg(...)`, `cfg_attr(..., ...)` (including the nested ones) and
+    // `doc(cfg(...))`; `args` are the tokens inside the parentheses of the attribute `name`. Other
+    // attributes are not touched, even if they have a `feature` parameter, and are kept as raw
+    // tokens, so they need not be valid metas (e.g. `tracing::instrument(fields(a = %b))`).
+    fn process_replace_features_attr_args(&self, name: &str, args: TokenStream2) -> syn::Result<Option<TokenStream2>> {
+        let mut chunks = vec![TokenStream2::new()];
+        for tt in args {
+            match &tt {
+                TokenTree::Punct(punct) if punct.as_char() == ',' => chunks.push(TokenStream2::new()),
+                _ => chunks.last_mut().unwrap().extend(std::iter::once(tt)),
+            }
+        }
+
+        let mut changed = false;
+        for (index, chunk) in chunks.iter_mut().enumerate() {
+            let new = match name {
+                "cfg" => self.process_replace_features_predicate(chunk)?,
+                "cfg_attr" if index == 0 => self.process_replace_features_predicate(chunk)?,
+                "cfg_attr" | "doc" => {
+                    let tts = chunk.clone().into_iter().collect::<Vec<_>>();
+                    match tts.as_slice() {
+                        [TokenTree::Ident(ident), TokenTree::Group(group)]
+                            if group.delimiter() == Delimiter::Parenthesis
+                                && (ident == "cfg" || ident == "cfg_attr" || ident == "doc") =>
+                        {
+                            self.process_replace_features_attr_args(&ident.to_string(), group.stream())?
+                                .map(|args| quote!(#ident(#args)))
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            if let Some(new) = new {
+                *chunk = new;
+                changed = true;
+            }
+        }
+
+        Ok(changed.then(|| {
+            let chunks = chunks.iter();
+            quote!(#(#chunks),*)
+        }))
+    }
+
+    fn process_replace_features_predicate(&self, tokens: &TokenStream2) -> syn::Result<Option<TokenStream2>> {
+        // an invalid predicate is left to the compiler to report
+        let mut meta = match syn::parse2::<syn::Meta>(tokens.clone()) {
+            Ok(meta) => meta,
+            Err(_) => return Ok(None),
+        };
+
+        Ok(self.process_replace_features_meta(&mut meta)?.then(|| quote!(#meta)))
+    }
+
+    // `cfg!(feature = "...")`
+    fn process_replace_features_macro(&self, mac: &mut syn::Macro) -> syn::Result<()> {
+        let is_cfg = matches!(path_to_string(&mac.path).as_str(), "cfg" | "std::cfg" | "core::cfg");
+        if !is_cfg || self.params.replace_features_is_empty() {
+            return Ok(());
+        }
+
+        let mut meta = syn::parse2::<syn::Meta>(mac.tokens.clone())?;
+        if self.process_replace_features_meta(&mut meta)? {
+            mac.tokens = quote!(#meta);
+        }
+
+        Ok(())
+    }
+
+    fn keys_match(&self, keys: &[String]) -> bool {
+        match self.params.key_get() {
+            Some(current_key) => keys.iter().any(|key| key == current_key),
+            None => false,
+        }
+    }
+
+    // `sync_impl!{ ... }`, `async_impl!{ ... }` or `version_impl!(key = "...", { ... })`: returns
+    // the content and whether it should be kept in this version
+    fn process_version_impl(&self, mac: &syn::Macro) -> syn::Result<Option<(bool, syn::Block)>> {
+        let name = match mac.path.get_ident() {
+            Some(ident) => ident.to_string(),
+            None => match self.params.is_our_path(&mac.path) {
+                Some(name) => name,
+                None => return Ok(None),
+            },
+        };
+
+        let content = || -> syn::Result<syn::Block> {
+            Ok(syn::Block {
+                brace_token: syn::token::Brace(mac.span()),
+                stmts: syn::Block::parse_within.parse2(mac.tokens.clone())?,
+            })
+        };
+
+        Ok(match name.as_str() {
+            MACRO_SYNC_IMPL_NAME => Some((
+                matches!(self.convert_mode, ConvertMode::IntoSync),
+                content()?,
+            )),
+            MACRO_ASYNC_IMPL_NAME => Some((
+                matches!(self.convert_mode, ConvertMode::IntoAsync),
+                content()?,
+            )),
+            MACRO_VERSION_IMPL_NAME => {
+                let args: VersionImplArgs = syn::parse2(mac.tokens.clone())?;
+                Some((self.keys_match(&args.keys), args.block))
+            }
+            _ => None,
+        }
+        .map(|(keep, block)| {
+            // the idents used only by other versions are not reported as unused
+            if !keep {
+                self.params.idents_mark_used(block.to_token_stream());
+            }
+            (keep, block)
+        }))
+    }
+
+    // Kept content becomes a block, removed content becomes `()`
+    fn process_version_impl_expr(&self, node: &mut syn::Expr) -> syn::Result<()> {
+        if let syn::Expr::Macro(expr) = node {
+            if let Some((keep, block)) = self.process_version_impl(&expr.mac)? {
+                *node = if keep {
+                    syn::Expr::Block(syn::ExprBlock {
+                        attrs: expr.attrs.clone(),
+                        label: None,
+                        block,
+                    })
+                } else {
+                    syn::parse_quote!(())
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    // Kept content becomes a block statement, removed content is dropped
+    fn process_version_impl_stmts(&self, block: &mut syn::Block) -> syn::Result<()> {
+        let mut stmts = Vec::with_capacity(block.stmts.len());
+
+        for stmt in block.stmts.drain(..) {
+            let (mac, attrs, semi) = match &stmt {
+                syn::Stmt::Item(syn::Item::Macro(item)) if item.ident.is_none() => {
+                    (&item.mac, &item.attrs, item.semi_token)
+                }
+                syn::Stmt::Semi(syn::Expr::Macro(expr), semi) => (&expr.mac, &expr.attrs, Some(*semi)),
+                syn::Stmt::Expr(syn::Expr::Macro(expr)) => (&expr.mac, &expr.attrs, None),
+                _ => {
+                    stmts.push(stmt);
+                    continue;
+                }
+            };
+
+            match self.process_version_impl(mac)? {
+                Some((true, content)) => {
+                    let expr = syn::Expr::Block(syn::ExprBlock {
+                        attrs: attrs.clone(),
+                        label: None,
+                        block: content,
+                    });
+                    stmts.push(match semi {
+                        Some(semi) => syn::Stmt::Semi(expr, semi),
+                        None => syn::Stmt::Expr(expr),
+                    });
+                }
+                Some((false, _)) => {}
+                None => stmts.push(stmt),
+            }
+        }
+
+        block.stmts = stmts;
+
+        Ok(())
+    }
+
+    fn process_attribute_if(&mut self, attr: &mut syn::Attribute, not: bool) -> syn::Result<()> {
+        let args = syn::parse2::<AttributeArgsInParens>(attr.tokens.clone())?;
+
+        if args.args.is_empty() {
+            return Err(syn::Error::new_spanned(
+                attr.to_token_stream(),
+                "Expected ident",
+            ));
+        }
+
+        // only_if(a, b) means "in version a or in version b"
+        let mut keys = vec![];
+        for arg in &args.args {
+            keys.push(nested_meta_to_key(arg)?);
+        }
+
+        let success = self.keys_match(&keys) ^ not;
+
+        let new_name = if success { MACRO_NOOP_NAME } else { MACRO_REMOVE_NAME };
+        attr.path = self.params.make_self_path(new_name);
+
+        Ok(())
+    }
+
+    fn process_doc_attrs(&mut self, attrs: &mut Vec<syn::Attribute>) -> syn::Result<()> {
+
+        let mut acc: Vec<syn::Attribute> = vec![];
+        let mut acc_temp: Vec<syn::Attribute> = vec![];
+        let mut lines: Vec<String> = vec![];
+        let mut inside_doc = false;
+
+        fn process_docs(acc: &mut Vec<syn::Attribute>, acc_temp: &mut Vec<syn::Attribute>, lines: &mut Vec<String>, params: &MacroParameters, convert_mode: ConvertMode) -> syn::Result<()> {
+            assert!(!lines.is_empty());
+            let mut first = true;
+            let doc: String = lines.iter().map(|s| {
+                if first {
+                    first = false;
+                    s.clone()
+                } else {
+                    let mut ss = String::from("\n");
+                    ss.push_str( s.as_str() );
+                    ss
+                } 
+            }).collect();
+
+            let rename = |name: &str| {
+                let ident = syn::parse_str::<syn::Ident>(name).ok()?;
+                params.ident_rename(&ident, convert_mode).map(|ident| ident.to_string())
+            };
+
+            // the doctests which can not be converted are reported, unless rustdoc does not expect
+            // them to compile
+            let span = acc_temp.first().map(|attr| attr.span()).unwrap_or_else(Span::call_site);
+            let error: RefCell<Option<syn::Error>> = RefCell::new(None);
+
+            let processor = |block: &CodeBlock| -> Option<Option<(String, String)>> {
+                let key = params.key_get();
+                match key {
+                    Some(key) if !block.is_kept(key) => return Some(None),
+                    Some(_) => {}
+                    // the conditions can not be checked without the key
+                    None if block.has_conditions() => return None,
+                    None => {}
+                }
+
+                let mut code = block.code.to_string();
+                if params.convert_doctests_get() && block.is_rust() {
+                    // the doctest is converted like the item, but all the functions in it lose asyncness
+                    let convert = |item: &mut syn::ItemFn| {
+                        let mut params = params.clone();
+                        params.recursive_asyncness_removal_set(true);
+                        let mut visitor = Visitor::new(AsyncAwaitVisitor::new(&mut params, convert_mode));
+                        visitor.visit_item_fn_mut(item);
+                        if let Some(err) = visitor.take_error() {
+                            return Err(err);
+                        }
+                        if let ConvertMode::IntoSync = convert_mode {
+                            remove_runtime_attrs(item);
+                        }
+                        Ok(())
+                    };
+                    match convert_doctest(block.code, convert) {
+                        Ok(converted) => code = converted,
+                        Err(_) if block.may_be_invalid(key) => {}
+                        Err(err) => {
+                            let err = syn::Error::new(
+                                span,
+                                format!(
+                                    "Can not convert the doctest: {}; fix it, or mark it with \
+                                    `ignore` to keep it unchanged",
+                                    err
+                                ),
+                            );
+                            match &mut *error.borrow_mut() {
+                                Some(errors) => errors.combine(err),
+                                errors => *errors = Some(err),
+                            }
+                        }
+                    }
+                }
+
+                if block.has_conditions() || code != block.code {
+                    Some(Some((block.lang_get(key), code)))
+                } else {
+                    None
+                }
+            };
+    
+            let new_doc = process_doctests(doc.as_str(), processor);
+            if let Some(err) = error.into_inner() {
+                return Err(err);
+            }
+            let new_doc = match params.key_get() {
+                Some(key) => {
+                    let self_name = params.self_name_get(convert_mode);
+                    process_prose(new_doc.as_deref().unwrap_or(doc.as_str()), key, self_name.as_deref()).or(new_doc)
+                }
+                None => new_doc,
+            };
+            let new_doc = rename_links(new_doc.as_deref().unwrap_or(doc.as_str()), rename).or(new_doc);
+
+            if let Some(doc) = new_doc {
+                let mut acc_temp_drain = acc_temp.drain(..);
+                for line in doc.lines() {
+                    let tokens = quote!(= #line);
+                    let attr = if let Some(mut attr) = acc_temp_drain.next() {
+                        attr.tokens = tokens;
+                        attr
+                    } else {
+                        let sp = Span::call_site();
+                        syn::Attribute {
+                            pound_token: syn::Token![#]([sp]),
+                            style: syn::AttrStyle::Outer,
+                            bracket_token: syn::token::Bracket(sp),
+                            path: make_path("doc"),
+                            tokens,
+                        }
+                    };
+                    acc.push(attr);
+                }
+            } else {
+                for attr in acc_temp.drain(..) {
+                    acc.push(attr);
+                }
+            }
+
+            Ok(())
+        }
+
+        for attr in attrs.drain(..) {
+            match (inside_doc, attr.path.is_ident("doc")) {
+                (false, false) => {
+                    acc.push(attr);
+                },
+                (false, true) => {
+                    let es = syn::parse2::<EqStr>(attr.tokens.clone())?;
+                    let doc = es.str.value();
+
+                    lines.push(doc);
+                    acc_temp.push(attr);
+                    inside_doc = true;
+                },
+                (true, false) => {
+                    process_docs(&mut acc, &mut acc_temp, &mut lines, &self.params, self.convert_mode)?;
+            
+                    acc_temp.clear();
+                    lines.clear();
+                    inside_doc = false;
+
+                    acc.push(attr);
+                },
+                (true, true) => {
+                    let es = syn::parse2::<EqStr>(attr.tokens.clone())?;
+                    let doc = es.str.value();
+
+                    lines.push(doc);
+                    acc_temp.push(attr);
+                }
+            }
+        };
+
+        if inside_doc {
+            process_docs(&mut acc, &mut acc_temp, &mut lines, &self.params, self.convert_mode)?;
+        }
+
+        let _ = std::mem::replace(attrs, acc);
+
+        Ok(())
+    }
+
+    fn process_attrs(&mut self, attrs: &mut Vec<syn::Attribute>) -> syn::Result<()> {
+        self.process_doc_attrs(attrs)?;
+
+        for attr in attrs.iter_mut() {
+            if let Some(name) = self.params.is_our_attr(attr) {
+                match name.as_str() {
+                    MACRO_ONLY_IF_NAME => self.process_attribute_if(attr, false)?,
+                    MACRO_REMOVE_IF_NAME => self.process_attribute_if(attr, true)?,
+                    _ => {
+                        // Attribute stays unchanged. Unknown attributes will be
+                        // rejected by compiter later.
+                    }
+                }
+            }
+        }
+
+        if !self.params.drop_attrs_is_empty() {
+            attrs.retain(|attr| {
+                if let Some(ident) = attr.path.get_ident() {
+                    let ident = ident.to_string();
+                    !self.params.drop_attrs_contains(&ident)
+                } else {
+                    true
+                }
+            });
+        }
+
+        if !self.params.replace_features_is_empty() {
+            for attr in attrs {
+                if let Some(ident) = attr.path.get_ident() {
+                    if ident == "cfg" || ident == "cfg_attr" || ident == "doc" {
+                        let ident = ident.to_string();
+                        let tts = attr.tokens.clone().into_iter().collect::<Vec<_>>();
+                        if let [TokenTree::Group(group)] = tts.as_slice() {
+                            if group.delimiter() == Delimiter::Parenthesis {
+                                if let Some(args) = self.process_replace_features_attr_args(&ident, group.stream())? {
+                                    attr.tokens = quote!((#args));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn process_expr(&mut self, node: &mut syn::Expr) -> syn::Result<()> {
+        self.process_version_impl_expr(node)?;
+
+        match self.convert_mode {
+            ConvertMode::IntoSync => {
+                // async -> sync, remove async_impl blocks
+                if let Some(inner) = search_boxed_async(node) {
+                    *node = syn::Expr::Async(inner.clone());
+                }
+
+                if self.params.streams_get() {
+                    match node {
+                        syn::Expr::Macro(expr) => {
+                            if let Some(new_expr) = convert_stream_macro(&expr.mac)? {
+                                *node = new_expr;
+                            }
+                        }
+                        syn::Expr::Call(expr) => convert_stream_constructor(expr),
+                        _ => {}
+                    }
+                }
+
+                match node {
+                    syn::Expr::Await(expr) => {
+                        let lock = if self.params.unwrap_locks_get() { convert_lock_await(expr) } else { None };
+                        *node = match lock {
+                            Some(expr) => expr,
+                            None => (*expr.base).clone(),
+                        }
+                    }
+
+                    syn::Expr::Async(expr) => {
+                        let inner = &expr.block;
+                        let sync_expr = match inner.stmts.as_slice() {
+                            // remove useless braces when there is only one expression
+                            [syn::Stmt::Expr(stmt)] if expr.attrs.is_empty() => stmt.clone(),
+                            _ => syn::Expr::Block(syn::ExprBlock {
+                                attrs: expr.attrs.clone(),
+                                block: inner.clone(),
+                                label: None,
+                            }),
+                        };
+                        *node = sync_expr;
+                    }
+
+                    // `async move |x| ...` -> `move |x| ...`, `|| async move { ... }` -> `move || { ... }`
+                    // (the body is converted as an `async` block)
+                    syn::Expr::Closure(expr) => {
+                        expr.asyncness = None;
+                        if let syn::Expr::Async(body) = &*expr.body {
+                            if expr.capture.is_none() {
+                                expr.capture = body.capture;
+                            }
+                        }
+                    }
+
+                    _ => {}
+                }
+
+                convert_combinators(node, self.params.futures_fns_get())?;
+            }
+            ConvertMode::IntoAsync | ConvertMode::IntoBlocking => {
+                // stay async, just remove sync_impl blocks
+                match node {
+                    _ => {}
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    fn process_block(&mut self, node: &mut syn::Block) -> syn::Result<()> {
+        self.process_version_impl_stmts(node)?;
+
+        match self.convert_mode {
+            ConvertMode::IntoSync => {
+                if self.params.streams_get() {
+                    convert_stream_stmts(node)?;
+                }
+                convert_combinator_stmts(node);
+            }
+            ConvertMode::IntoAsync | ConvertMode::IntoBlocking => {}
+        }
+
+        Ok(())
+    }
+
+    fn process_trait_bound(&mut self, node: &mut syn::TraitBound) -> syn::Result<()> {
+        match self.convert_mode {
+            ConvertMode::IntoSync => {
+                convert_async_fn_bound(node);
+
+                if self.params.streams_get() {
+                    convert_stream_bound(node, self.params.stream_traits_get());
+                }
+            }
+            ConvertMode::IntoAsync | ConvertMode::IntoBlocking => {}
+        }
+
+        Ok(())
+    }
+
+    // Generic parameters bounded by `Future<Output = T>` are removed, and replaced with `T` inside
+    // the item
+    fn process_generics_scope(&mut self, node: &mut syn::Generics) -> syn::Result<()> {
+        match self.convert_mode {
+            ConvertMode::IntoSync => {
+                // the scope is pushed anyway, so `after_process_generics_scope` pops the right one
+                let gens = search_future_generics(node);
+                let gens = match gens {
+                    Ok(gens) => gens,
+                    Err(err) => {
+                        self.generics.push(HashMap::new());
+                        return Err(err);
+                    }
+                };
+                remove_future_generics(node, &gens);
+                self.generics.push(gens);
+
+                if self.params.io_get() {
+                    remove_io_unpin_in_generics(node, self.params.io_traits_get());
+                }
+            }
+            ConvertMode::IntoAsync | ConvertMode::IntoBlocking => {}
+        };
+
+        Ok(())
+    }
+
+    fn after_process_generics_scope(&mut self, _node: &mut syn::Generics) -> syn::Result<()> {
+        match self.convert_mode {
+            ConvertMode::IntoSync => {
+                self.generics.pop();
+            }
+            ConvertMode::IntoAsync | ConvertMode::IntoBlocking => {}
+        }
+        Ok(())
+    }
+
+    fn process_item_impl(&mut self, node: &mut syn::ItemImpl) -> syn::Result<()> {
+        if let ConvertMode::IntoSync = self.convert_mode {
+            // the item declaring these parameters loses them too: `impl<F: Future> Task<F>` -> `impl Task`
+            // errors are reported by `process_generics_scope`
+            let gens = search_future_generics(&node.generics).unwrap_or_default();
+            if !gens.is_empty() {
+                remove_future_generic_arguments(&mut node.self_ty, &gens);
+                if let Some((_, path, _)) = &mut node.trait_ {
+                    if let Some(last) = path.segments.last_mut() {
+                        remove_future_generic_arguments_in_segment(last, &gens);
+                    }
+                }
+            }
+        }
+
+        if self.params.recursive_asyncness_removal_get() {
+            remove_asyncness_on_impl(
+                node,
+                self.convert_mode,
+                self.params.send_get(),
+                self.params.trait_backend_get(),
+            );
+        };
+
+        Ok(())
+    }
+
+    fn process_item_trait(&mut self, node: &mut syn::ItemTrait) -> syn::Result<()> {
+        if self.params.recursive_asyncness_removal_get() {
+            remove_asyncness_on_trait(
+                node,
+                self.convert_mode,
+                self.params.send_get(),
+                self.params.trait_backend_get(),
+            );
+        };
+
+        Ok(())
+    }
+
+    fn process_item_fn(&mut self, node: &mut syn::ItemFn) -> syn::Result<()> {
+        if self.params.recursive_asyncness_removal_get() {
+            remove_asyncness_on_fn( node, self.convert_mode );
+        };
+
+        Ok(())
+    }
+
+    fn process_item_use(&mut self, node: &mut syn::ItemUse) -> syn::Result<()> {
+        let convert_mode = self.convert_mode;
+        convert_use(node, |path| self.params.paths_get(path, convert_mode))?;
+
+        if let (ConvertMode::IntoSync, true) = (convert_mode, self.params.io_get()) {
+            convert_io_use(&mut node.tree, self.params.io_traits_get());
+        }
+
+        Ok(())
+    }
+
+    fn process_path(&mut self, node: &mut syn::Path) -> syn::Result<()> {
+        let convert_mode = self.convert_mode;
+        convert_path(node, |path| self.params.paths_get(path, convert_mode))?;
+
+        if let (ConvertMode::IntoSync, true) = (convert_mode, self.params.io_get()) {
+            convert_io_path(node, self.params.io_traits_get());
+        }
+
+        Ok(())
+    }
+
+    fn process_type(&mut self, node: &mut syn::Type) -> syn::Result<()> {
+        if let (ConvertMode::IntoSync, true) = (self.convert_mode, self.params.io_get()) {
+            remove_io_unpin_in_type(node, self.params.io_traits_get());
+        }
+
+        // replace generic type with target type
+        if let Some(name) = generic_param_name(node) {
+            if let Some(ty) = self.generics_get(&name) {
+                *node = ty.clone();
+            }
+        }
+
+        Ok(())
+    }
+
+    // Macro bodies (e.g. `macro_rules!`) can not be parsed, so they are converted token by token:
+    // idents are renamed, and for the sync version `async` (`async move`) and `.await` are removed.
+    // Metavariables (`$name`) stay unchanged.
+    fn process_macro_tokens(&self, tokens: TokenStream2) -> TokenStream2 {
+        let into_sync = matches!(self.convert_mode, ConvertMode::IntoSync);
+
+        let mut result: Vec<TokenTree> = vec![];
+        let mut iter = tokens.into_iter().peekable();
+
+        while let Some(tt) = iter.next() {
+            let after_dollar = matches!(result.last(), Some(TokenTree::Punct(p)) if p.as_char() == '$');
+
+            match tt {
+                TokenTree::Group(group) => {
+                    let mut new_group =
+                        Group::new(group.delimiter(), self.process_macro_tokens(group.stream()));
+                    new_group.set_span(group.span());
+                    result.push(TokenTree::Group(new_group));
+                }
+                TokenTree::Ident(ident) if !after_dollar => {
+                    if into_sync && ident == "async" {
+                        if matches!(iter.peek(), Some(TokenTree::Ident(next)) if next == "move") {
+                            iter.next();
+                        }
+                        continue;
+                    }
+
+                    let ident = self.params.ident_rename(&ident, self.convert_mode).unwrap_or(ident);
+                    result.push(TokenTree::Ident(ident));
+                }
+                TokenTree::Punct(punct)
+                    if into_sync
+                        && punct.as_char() == '.'
+                        && punct.spacing() == proc_macro2::Spacing::Alone
+                        && matches!(iter.peek(), Some(TokenTree::Ident(next)) if next == "await") =>
+                {
+                    iter.next();
+                }
+                tt => result.push(tt),
+            }
+        }
+
+        TokenStream2::from_iter(result)
+    }
+
+    fn process_ident(&mut self, ident: &mut syn::Ident, mode: IdentMode) -> syn::Result<()> {
+        if mode == IdentMode::Use {
+            return Ok(());
+        };
+
+        if let Some(new_ident) = self.params.ident_rename(ident, self.convert_mode) {
+            *ident = new_ident;
+            return Ok(());
+        }
+
+        Ok(())
+    }
+
+    fn process_type_param(&mut self, node: &mut syn::TypeParam) -> syn::Result<()> {
+        let ident = &mut node.ident;
+
+        if let Some(new_ident) = self.params.ident_rename(ident, self.convert_mode) {
+            *ident = new_ident;
+        }
+
+        Ok(())
+    }
+
+    fn process_use_tree(&mut self, node: &mut syn::UseTree) -> syn::Result<()> {
+        match node {
+            syn::UseTree::Path(syn::UsePath { ident, .. }) => {
+                if let Some(ir) = self.params.idents_get(&ident.to_string()) {
+                    if !ir.use_mode {
+                        *ident = ir.ident_add_suffix(ident, self.convert_mode, self.params.key_get(), self.params.suffix_get());
+                    }
+                } else if let Some(new_ident) = self.params.ident_rename(ident, self.convert_mode) {
+                    *ident = new_ident;
+                }
+            }
+            syn::UseTree::Name(name) => {
+                let ident = &mut name.ident;
+
+                if let Some(ir) = self.params.idents_get(&ident.to_string()) {
+                    if ir.use_mode {
+                        *node = syn::UseTree::Rename(syn::UseRename {
+                            ident: ident.clone(),
+                            as_token: syn::Token![as](ident.span()),
+                            rename: ir.ident_add_suffix(ident, self.convert_mode, self.params.key_get(), self.params.suffix_get()),
+                        });
+                    } else {
+                        *ident = ir.ident_add_suffix(ident, self.convert_mode, self.params.key_get(), self.params.suffix_get());
+                    }
+                } else if let Some(new_ident) = self.params.ident_rename(ident, self.convert_mode) {
+                    *ident = new_ident;
+                }
+            }
+            _ => {}
+        };
+
+        Ok(())
+    }
+}
+
+impl<'p> VisitMutExt for Visitor<AsyncAwaitVisitor<'p>> {
+    fn process_attrs(&mut self, attrs: &mut Vec<syn::Attribute>) -> syn::Result<()> {
+        self.inner.process_attrs(attrs)
+    }
+    fn process_ident(&mut self, ident: &mut syn::Ident, mode: IdentMode) -> syn::Result<()> {
+        self.inner.process_ident(ident, mode)
+    }
+    fn process_block(&mut self, node: &mut syn::Block) -> syn::Result<()> {
+        self.inner.process_block(node)
+    }
+    fn process_expr(&mut self, node: &mut syn::Expr) -> syn::Result<()> {
+        self.inner.process_expr(node)
+    }
+    fn process_item_impl(&mut self, node: &mut syn::ItemImpl) -> syn::Result<()> {
+        self.inner.process_item_impl(node)
+    }
+    fn process_item_trait(&mut self, node: &mut syn::ItemTrait) -> syn::Result<()> {
+        self.inner.process_item_trait(node)
+    }
+    fn process_item_fn(&mut self, node: &mut syn::ItemFn) -> syn::Result<()> {
+        self.inner.process_item_fn(node)
+    }
+    fn process_item_use(&mut self, node: &mut syn::ItemUse) -> syn::Result<()> {
+        self.inner.process_item_use(node)
+    }
+
+    fn process_macro(&mut self, node: &mut syn::Macro) -> syn::Result<()> {
+        self.inner.process_replace_features_macro(node)?;
+
+        if self.inner.params.is_expr_macro(&node.path) || is_join_macro(&node.path) {
+            node.tokens = visit_macro_args(node.tokens.clone(), |arg| self.visit_expr_mut(arg));
+        } else if node.path.is_ident("macro_rules") {
+            node.tokens = self.inner.process_macro_tokens(node.tokens.clone());
+        } else if is_select_macro(&node.path) {
+            visit_select_macro(node, |expr| self.visit_expr_mut(expr))?;
+        };
+        Ok(())
+    }
+    fn process_path(&mut self, node: &mut syn::Path) -> syn::Result<()> {
+        self.inner.process_path(node)
+    }
+    fn process_type(&mut self, node: &mut syn::Type) -> syn::Result<()> {
+        self.inner.process_type(node)
+    }
+    fn process_trait_bound(&mut self, node: &mut syn::TraitBound) -> syn::Result<()> {
+        self.inner.process_trait_bound(node)
+    }
+    fn process_type_param(&mut self, node: &mut syn::TypeParam) -> syn::Result<()> {
+        self.inner.process_type_param(node)
+    }
+    fn process_generics_scope(&mut self, node: &mut syn::Generics) -> syn::Result<()> {
+        self.inner.process_generics_scope(node)
+    }
+    fn after_process_generics_scope(&mut self, node: &mut syn::Generics) -> syn::Result<()> {
+        self.inner.after_process_generics_scope(node)
+    }
+    fn process_use_tree(&mut self, node: &mut syn::UseTree) -> syn::Result<()> {
+        self.inner.process_use_tree(node)
+    }
+}
+
+impl<'p> AsyncAwaitVisitor<'p> {}
@@ -1,15 +1,11 @@
 #[allow(unused_imports)]
 use std::iter::FromIterator;
 
-#[allow(unused_imports)]
-use proc_macro::TokenStream;
 use proc_macro2::{Delimiter, Group, TokenStream as TokenStream2, TokenTree};
-use proc_macro_error::emit_error;
 use quote::quote;
 
 use crate::{
     params::MacroParameters,
-    utils::{set_error_and_return, unwrap_or_set_error_and_return},
     visit_ext::{VisitMutExt, Visitor},
     DEFAULT_CRATE_NAME, MACRO_MAYBE_NAME, MACRO_DEFAULT_NAME,
 };
@@ -26,7 +22,7 @@ impl ContentVisitor {
     }
 
     fn process_attribute_maybe(&mut self, node: &mut syn::Attribute) -> syn::Result<()> {
-        let mut params = MacroParameters::from_tokens_in_parens(node.tokens.clone().into())?;
+        let mut params = MacroParameters::from_tokens_in_parens(node.tokens.clone())?;
 
         MacroParameters::apply_parent(&mut params, &self.params)?;
 
@@ -37,20 +33,25 @@ impl ContentVisitor {
     }
 
     fn process_file(&mut self, node: &mut syn::File) -> syn::Result<()> {
+        let mut result = Ok(());
+
         node.attrs.retain(|attr| {
             if let Some(prefix) = is_default_attr(attr) {
-                self.params = unwrap_or_set_error_and_return!(
-                    MacroParameters::from_tokens_in_parens(attr.tokens.clone().into()),
-                    false
-                );
-                self.params.prefix_set(prefix);
+                match MacroParameters::from_tokens_in_parens(attr.tokens.clone()) {
+                    Ok(params) => {
+                        self.params = params;
+                        self.params.prefix_set(prefix);
+                        self.params.idents_allow_unused();
+                    }
+                    Err(err) => result = Err(err),
+                }
                 false
             } else {
                 true
             }
         });
 
-        Ok(())
+        result
     }
 
     fn process_attribute(&mut self, node: &mut syn::Attribute) -> syn::Result<()> {
@@ -74,7 +75,7 @@ impl ContentVisitor {
         let mut result = TokenStream2::new();
 
         if state == 7 {
-            let mut params = match MacroParameters::from_tokens(ts.clone().into()) {
+            let mut params = match MacroParameters::from_tokens(ts.clone()) {
                 Ok(p) => p,
                 Err(_) => return ts,
             };
@@ -84,7 +85,7 @@ impl ContentVisitor {
                 Err(_) => return ts,
             };
 
-            return params.to_tokens(None).into();
+            return params.to_tokens(None);
         }
 
         let mut iter = ts.into_iter();
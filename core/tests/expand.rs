@@ -0,0 +1,882 @@
+use quote::quote;
+
+#[test]
+fn expand_single_version() {
+    let args = quote!(idents(Client), sync(feature = "use_sync"), async(feature = "use_async"));
+    let input = quote!(
+        async fn open(c: &Client) -> u32 {
+            c.get().await
+        }
+    );
+
+    let sync = maybe_async_cfg_core::expand(args.clone(), input.clone(), Some("sync")).unwrap();
+    assert_eq!(
+        sync.to_string(),
+        quote!(
+            #[cfg(feature = "use_sync")]
+            fn open_sync(c: &ClientSync) -> u32 {
+                c.get()
+            }
+        )
+        .to_string()
+    );
+
+    let all = maybe_async_cfg_core::expand(args, input, None).unwrap();
+    assert_eq!(
+        all.to_string(),
+        quote!(
+            #[cfg(feature = "use_sync")]
+            fn open_sync(c: &ClientSync) -> u32 {
+                c.get()
+            }
+            #[cfg(feature = "use_async")]
+            async fn open_async(c: &ClientAsync) -> u32 {
+                c.get().await
+            }
+        )
+        .to_string()
+    );
+}
+
+#[test]
+fn expand_reports_errors() {
+    let err = maybe_async_cfg_core::expand(quote!(sync(), bogus = "x"), quote!(fn f() {}), None)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Wrong name for name-value pair: bogus");
+}
+
+#[test]
+fn expand_only_if_remove_if() {
+    let args = quote!(sync(), async());
+    let input = quote!(
+        impl Client {
+            #[maybe_async_cfg::only_if(async)]
+            async fn spawn(&self) {}
+            #[maybe_async_cfg::remove_if(async)]
+            fn close(&self) {}
+        }
+    );
+
+    let sync = maybe_async_cfg_core::expand(args.clone(), input.clone(), Some("sync")).unwrap();
+    assert_eq!(
+        sync.to_string(),
+        quote!(
+            impl ClientSync {
+                #[maybe_async_cfg::remove(async)]
+                fn spawn(&self) {}
+                #[maybe_async_cfg::noop(async)]
+                fn close(&self) {}
+            }
+        )
+        .to_string()
+    );
+
+    let async_ = maybe_async_cfg_core::expand(args, input, Some("async")).unwrap();
+    assert_eq!(
+        async_.to_string(),
+        quote!(
+            impl ClientAsync {
+                #[maybe_async_cfg::noop(async)]
+                async fn spawn(&self) {}
+                #[maybe_async_cfg::remove(async)]
+                fn close(&self) {}
+            }
+        )
+        .to_string()
+    );
+}
+
+#[test]
+fn expand_unwrap_locks() {
+    let input = quote!(
+        async fn inc(counter: &tokio::sync::Mutex<u32>, file: &File) {
+            *counter.lock().await += 1;
+            file.read().await;
+        }
+    );
+
+    let plain =
+        maybe_async_cfg_core::expand(quote!(runtime = "tokio", sync()), input.clone(), None).unwrap();
+    assert_eq!(
+        plain.to_string(),
+        quote!(
+            fn inc_sync(counter: &std::sync::Mutex<u32>, file: &File) {
+                *counter.lock() += 1;
+                file.read();
+            }
+        )
+        .to_string()
+    );
+
+    let unwrapped =
+        maybe_async_cfg_core::expand(quote!(runtime = "tokio", unwrap_locks, sync()), input, None).unwrap();
+    assert_eq!(
+        unwrapped.to_string(),
+        quote!(
+            fn inc_sync(counter: &std::sync::Mutex<u32>, file: &File) {
+                *counter.lock().unwrap() += 1;
+                file.read().unwrap();
+            }
+        )
+        .to_string()
+    );
+}
+
+#[test]
+fn expand_extern_versions() {
+    let input = quote!(
+        extern "C" {
+            fn abs(x: i32) -> i32;
+            #[link_name = "labs"]
+            fn abs_long(x: i64) -> i64;
+        }
+    );
+
+    let sync = maybe_async_cfg_core::expand(
+        quote!(idents(abs(fn), abs_long(fn)), sync(), async()),
+        input.clone(),
+        Some("sync"),
+    )
+    .unwrap();
+    assert_eq!(
+        sync.to_string(),
+        quote!(
+            extern "C" {
+                #[link_name = "abs"]
+                fn abs_sync(x: i32) -> i32;
+                #[link_name = "labs"]
+                fn abs_long_sync(x: i64) -> i64;
+            }
+        )
+        .to_string()
+    );
+
+    let err = maybe_async_cfg_core::expand(quote!(idents(abs(fn)), sync(), async()), input, None)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`abs_long` would be declared in every version; add it to `idents(...)` or keep the \
+        extern block in one version only"
+    );
+}
+
+#[test]
+fn expand_duplicate_suffixes() {
+    let input = quote!(
+        async fn open() {}
+    );
+
+    let args = quote!(sync(), async(key = "tokio"), async(key = "smol"));
+    let err = maybe_async_cfg_core::expand(args, input.clone(), None).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Several versions with the same conditions use the `Async` suffix, so their items would get \
+        the same names; specify `suffix = \"...\"` for each of them"
+    );
+
+    let smol = maybe_async_cfg_core::expand(
+        quote!(sync(), async(key = "tokio"), async(key = "smol", suffix = "Smol")),
+        input,
+        Some("smol"),
+    )
+    .unwrap();
+    assert_eq!(smol.to_string(), quote!(async fn open_smol() {}).to_string());
+}
+
+#[test]
+fn expand_trait_backend_attrs() {
+    let args = quote!(trait_backend = "trait_variant", async());
+
+    let other = maybe_async_cfg_core::expand(
+        args.clone(),
+        quote!(
+            #[builder::make]
+            pub trait Counter {
+                async fn get(&self) -> u32;
+            }
+        ),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        other.to_string(),
+        quote!(
+            #[builder::make]
+            #[trait_variant::make(Send)]
+            pub trait CounterAsync {
+                async fn get(&self) -> u32;
+            }
+        )
+        .to_string()
+    );
+
+    let annotated = maybe_async_cfg_core::expand(
+        args,
+        quote!(
+            #[trait_variant::make(Send)]
+            pub trait Counter {
+                async fn get(&self) -> u32;
+            }
+        ),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        annotated.to_string(),
+        quote!(
+            #[trait_variant::make(Send)]
+            pub trait CounterAsync {
+                async fn get(&self) -> u32;
+            }
+        )
+        .to_string()
+    );
+}
+
+#[test]
+fn expand_select_patterns() {
+    let args = quote!(sync());
+
+    let irrefutable = maybe_async_cfg_core::expand(
+        args.clone(),
+        quote!(
+            async fn recv(rx: Rx) -> u32 {
+                tokio::select! {
+                    #[sync]
+                    (a, _) = rx.pair() => a,
+                    _ = tick() => 0,
+                }
+            }
+        ),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        irrefutable.to_string(),
+        quote!(
+            fn recv_sync(rx: Rx) -> u32 {
+                match rx.pair() {
+                    (a, _) => a,
+                }
+            }
+        )
+        .to_string()
+    );
+
+    let with_else = maybe_async_cfg_core::expand(
+        args.clone(),
+        quote!(
+            async fn recv(rx: Rx) -> u32 {
+                tokio::select! {
+                    #[sync]
+                    Some(v) = rx.recv() => v,
+                    else => 0,
+                }
+            }
+        ),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        with_else.to_string(),
+        quote!(
+            fn recv_sync(rx: Rx) -> u32 {
+                match rx.recv() {
+                    Some(v) => v,
+                    #[allow(unreachable_patterns)]
+                    _ => 0,
+                }
+            }
+        )
+        .to_string()
+    );
+
+    let err = maybe_async_cfg_core::expand(
+        args,
+        quote!(
+            async fn recv(rx: Rx) -> u32 {
+                tokio::select! {
+                    #[sync]
+                    Some(v) = rx.recv() => v,
+                    _ = tick() => 0,
+                }
+            }
+        ),
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The pattern of the `#[sync]` arm of `select!` may not match: add an `else` arm to be used \
+        in this case in the sync version"
+    );
+}
+
+#[test]
+fn expand_join_all_paths() {
+    let all = maybe_async_cfg_core::expand(
+        quote!(sync()),
+        quote!(
+            async fn fetch(ids: Vec<u32>) {
+                futures::future::join_all(ids.iter().map(get)).await;
+                join_all(ids.iter().map(get)).await;
+                pool::join_all(ids.iter().map(get)).await;
+            }
+        ),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        all.to_string(),
+        quote!(
+            fn fetch_sync(ids: Vec<u32>) {
+                ::core::iter::IntoIterator::into_iter(ids.iter().map(get)).collect::<::std::vec::Vec<_> >();
+                join_all(ids.iter().map(get));
+                pool::join_all(ids.iter().map(get));
+            }
+        )
+        .to_string()
+    );
+
+    // the bare names are converted if imported from `futures::future`
+    let all = maybe_async_cfg_core::expand(
+        quote!(sync()),
+        quote!(
+            mod fetch {
+                use futures::future::{join_all, try_join_all as all};
+
+                async fn load(ids: Vec<u32>) {
+                    join_all(ids.iter().map(get)).await;
+                    all(ids.iter().map(get)).await;
+                    try_join_all(ids.iter().map(get)).await;
+                }
+            }
+        ),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        all.to_string(),
+        quote!(
+            mod fetch_sync {
+                use futures::future::{join_all, try_join_all as all};
+
+                fn load(ids: Vec<u32>) {
+                    ::core::iter::IntoIterator::into_iter(ids.iter().map(get)).collect::<::std::vec::Vec<_> >();
+                    ::core::iter::IntoIterator::into_iter(ids.iter().map(get))
+                        .collect::<::core::result::Result<::std::vec::Vec<_>, _> >();
+                    try_join_all(ids.iter().map(get));
+                }
+            }
+        )
+        .to_string()
+    );
+}
+
+#[test]
+fn expand_own_select_macro() {
+    let input = quote!(
+        async fn pick(a: u32, b: u32) -> u32 {
+            select!(a, b)
+        }
+    );
+
+    let all = maybe_async_cfg_core::expand(quote!(sync(), async()), input, None).unwrap();
+    assert_eq!(
+        all.to_string(),
+        quote!(
+            fn pick_sync(a: u32, b: u32) -> u32 {
+                select!(a, b)
+            }
+            async fn pick_async(a: u32, b: u32) -> u32 {
+                select!(a, b)
+            }
+        )
+        .to_string()
+    );
+}
+
+#[test]
+fn expand_only_own_paths() {
+    let sync = maybe_async_cfg_core::expand(
+        quote!(sync()),
+        quote!(
+            async fn run() {
+                maybe_async_cfg::sync_impl! { blocking(); }
+                tasks::sync_impl! { spawn(); }
+            }
+        ),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        sync.to_string(),
+        quote!(
+            fn run_sync() {
+                { blocking(); }
+                tasks::sync_impl! { spawn(); }
+            }
+        )
+        .to_string()
+    );
+}
+
+#[test]
+fn maybe_tracks_config_file() {
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/../tests/maybe-async-cfg.toml");
+    std::env::set_var("MAYBE_ASYNC_CFG_CONFIG", config);
+    let track = quote!(const _: &[u8] = include_bytes!(#config);).to_string();
+
+    let tokens = maybe_async_cfg_core::maybe(
+        quote!(profile = "client"),
+        quote!(
+            struct Connection {
+                client: Client,
+            }
+        ),
+    )
+    .unwrap()
+    .to_string();
+    assert!(tokens.starts_with(&track), "{}", tokens);
+    assert_eq!(tokens.matches(&track).count(), 1);
+
+    // functions may be methods, so the file is tracked inside the body of the first version
+    let tokens = maybe_async_cfg_core::maybe(
+        quote!(profile = "client"),
+        quote!(
+            async fn open(client: &Client) {
+                client.open().await
+            }
+        ),
+    )
+    .unwrap()
+    .to_string();
+    let body = format!("{{ {} client . open () . await }}", track);
+    assert_eq!(tokens.matches(&body).count(), 1, "{}", tokens);
+    assert_eq!(tokens.matches(&track).count(), 1, "{}", tokens);
+
+    let tokens = maybe_async_cfg_core::maybe(quote!(sync()), quote!(struct Connection;))
+        .unwrap()
+        .to_string();
+    assert!(!tokens.contains("include_bytes"));
+}
+
+#[test]
+fn unused_idents_reported_once() {
+    let args = quote!(idents(Conn, Cnn, Pool), sync(), async());
+    let input = quote!(
+        async fn open(c: Conn) -> Conn {
+            maybe_async_cfg::async_impl! {
+                let _: Pool = Pool;
+            }
+            c
+        }
+    );
+    let warning = "`Cnn` in `idents(...)` does not match anything";
+
+    let expanded = maybe_async_cfg_core::expand(args.clone(), input.clone(), None)
+        .unwrap()
+        .to_string();
+    assert_eq!(expanded.matches(warning).count(), 1, "{}", expanded);
+    assert!(!expanded.contains("`Pool`"), "{}", expanded);
+
+    // the versions are converted by their own invocations
+    let tokens = maybe_async_cfg_core::maybe(args, input).unwrap();
+    assert!(!tokens.to_string().contains(warning), "{}", tokens);
+
+    let versions = expand_maybe_attrs(tokens).to_string();
+    assert_eq!(versions.matches(warning).count(), 1, "{}", versions);
+    assert!(!versions.contains("`Pool`"), "{}", versions);
+}
+
+// Converts each `#[maybe(...)] item` produced by `maybe` the way the compiler does (the item must
+// have no outer attributes of its own)
+fn expand_maybe_attrs(tokens: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    use proc_macro2::TokenTree;
+
+    let mut chunks: Vec<Vec<TokenTree>> = vec![];
+    for tt in tokens {
+        match &tt {
+            TokenTree::Punct(punct) if punct.as_char() == '#' => chunks.push(vec![]),
+            _ => {}
+        }
+        chunks.last_mut().unwrap().push(tt);
+    }
+
+    chunks
+        .into_iter()
+        .map(|chunk| {
+            let args = match &chunk[1] {
+                TokenTree::Group(attr) => match attr.stream().into_iter().last() {
+                    Some(TokenTree::Group(args)) => args.stream(),
+                    _ => panic!("no arguments in {}", attr),
+                },
+                tt => panic!("expected an attribute, found {}", tt),
+            };
+            let item = chunk[2..].iter().cloned().collect();
+            maybe_async_cfg_core::maybe(args, item).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn expand_validate_skips_removed_items() {
+    let input = quote!(
+        impl Runner {
+            #[maybe_async_cfg::only_if(async)]
+            async fn spawn(&self) -> u32 {
+                run!(async { 1 }.await)
+            }
+
+            async fn run(&self) -> u32 {
+                run!(async { 2 }.await)
+            }
+        }
+    );
+
+    // the leftovers are warnings unless `validate` is specified
+    let warning = "`async` block in the macro arguments is left in the sync version";
+    let sync = maybe_async_cfg_core::expand(quote!(sync()), input.clone(), None).unwrap().to_string();
+    assert_eq!(sync.matches(warning).count(), 1, "{}", sync);
+    let sync = maybe_async_cfg_core::expand(quote!(allow_async_leftovers, sync()), input.clone(), None)
+        .unwrap()
+        .to_string();
+    assert!(!sync.contains(warning), "{}", sync);
+
+    // `spawn` is removed from the sync version, `run` is not
+    let err = maybe_async_cfg_core::expand(quote!(validate, sync()), input, None).unwrap_err();
+    let errors = err.into_iter().map(|err| err.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        vec![
+            "`async` block in the macro arguments is left in the sync version; add the macro to \
+            `macros(...)` if its arguments are expressions"
+        ]
+    );
+}
+
+#[test]
+fn expand_io_imported_traits_only() {
+    let args = quote!(io, sync());
+    let input = quote!(
+        mod codec {
+            use tokio::io::AsyncReadExt;
+
+            pub trait AsyncWrite {
+                fn put(&mut self, b: u8);
+            }
+
+            pub async fn copy<R: AsyncReadExt + Unpin, W: AsyncWrite + Unpin>(r: &mut R, w: &mut W) {
+                let mut buf = [0u8];
+                AsyncReadExt::read_exact(r, &mut buf).await.unwrap();
+                w.put(buf[0]);
+            }
+        }
+    );
+
+    let sync = maybe_async_cfg_core::expand(args, input, None).unwrap();
+    assert_eq!(
+        sync.to_string(),
+        quote!(
+            mod codec_sync {
+                use std::io::Read;
+
+                pub trait AsyncWrite {
+                    fn put(&mut self, b: u8);
+                }
+
+                pub fn copy<R: Read, W: AsyncWrite + Unpin>(r: &mut R, w: &mut W) {
+                    let mut buf = [0u8];
+                    Read::read_exact(r, &mut buf).unwrap();
+                    w.put(buf[0]);
+                }
+            }
+        )
+        .to_string()
+    );
+}
+
+// The text of all the `#[doc]` attributes in the tokens, line by line
+fn doc_lines(tokens: proc_macro2::TokenStream) -> Vec<String> {
+    let mut lines = vec![];
+    for tt in tokens {
+        if let proc_macro2::TokenTree::Group(group) = tt {
+            match syn::parse2::<syn::MetaNameValue>(group.stream()) {
+                Ok(syn::MetaNameValue { path, lit: syn::Lit::Str(text), .. }) if path.is_ident("doc") => {
+                    lines.push(text.value());
+                }
+                _ => lines.extend(doc_lines(group.stream())),
+            }
+        }
+    }
+    lines
+}
+
+#[test]
+fn expand_convert_doctests() {
+    let args = quote!(convert_doctests, runtime = "tokio", idents(Client), sync(), async());
+    let input = quote!(
+        /// Connects.
+        ///
+        /// ```
+        /// # use std::time::Duration;
+        /// # #[tokio::main]
+        /// # async fn main() {
+        /// let client = Client::new(); // `Client` is kept in comments
+        /// tokio::time::sleep(Duration::from_millis(1))
+        ///     .await;
+        /// let name = "Client.await";
+        /// # }
+        /// ```
+        ///
+        /// ```ignore
+        /// not Rust: Client.await
+        /// ```
+        struct Client;
+    );
+
+    let sync = maybe_async_cfg_core::expand(args.clone(), input.clone(), Some("sync")).unwrap();
+    assert_eq!(
+        doc_lines(sync).join("\n"),
+        " Connects.
+
+ ``` 
+ # use std::time::Duration;
+ # fn main() {
+ let client = ClientSync::new(); // `Client` is kept in comments
+ std::thread::sleep(Duration::from_millis(1));
+ let name = \"Client.await\";
+ # }
+ ```
+
+ ```ignore
+ not Rust: Client.await
+ ```"
+    );
+
+    // only the identifiers are renamed in the async version
+    let expected = doc_lines(input.clone())
+        .join("\n")
+        .replacen("```", "``` ", 1)
+        .replace("Client::new()", "ClientAsync::new()");
+    let r#async = maybe_async_cfg_core::expand(args, input, Some("async")).unwrap();
+    assert_eq!(doc_lines(r#async).join("\n"), expected);
+}
+
+#[test]
+fn expand_invalid_doctest() {
+    let input = quote!(
+        /// ```
+        /// let client = Client::new(
+        /// ```
+        struct Client;
+    );
+
+    let err = maybe_async_cfg_core::expand(quote!(convert_doctests, sync()), input, None).unwrap_err();
+    assert!(err.to_string().starts_with("Can not convert the doctest: "), "{}", err);
+}
+
+#[test]
+fn expand_doctest_conditions() {
+    let args = quote!(sync(key = "sync"), sync(key = "blocking", suffix = "Blocking"), async(key = "async"));
+    let input = quote!(
+        /// A client.
+        ///
+        /// ```rust, only_if(sync, blocking)
+        /// sync_or_blocking();
+        /// ```
+        ///
+        /// ```rust, remove_if(blocking)
+        /// not_blocking();
+        /// ```
+        ///
+        /// ```rust, only_if("async"), flags_if(async, no_run, edition2021)
+        /// only_async();
+        /// ```
+        ///
+        /// - In a list:
+        ///   ```{.rust .remove_if(sync)}
+        ///   not_sync();
+        ///   ```
+        struct Client;
+    );
+    let docs = |key| {
+        let tokens = maybe_async_cfg_core::expand(args.clone(), input.clone(), Some(key)).unwrap();
+        doc_lines(tokens).join("\n")
+    };
+
+    assert_eq!(
+        docs("sync"),
+        " A client.
+
+ ``` rust
+ sync_or_blocking();
+ ```
+
+ ``` rust
+ not_blocking();
+ ```
+
+
+ - In a list:"
+    );
+    assert_eq!(
+        docs("blocking"),
+        " A client.
+
+ ``` rust
+ sync_or_blocking();
+ ```
+
+
+
+ - In a list:
+   ``` rust
+   not_sync();
+   ```"
+    );
+    assert_eq!(
+        docs("async"),
+        " A client.
+
+
+ ``` rust
+ not_blocking();
+ ```
+
+ ``` rust, no_run, edition2021
+ only_async();
+ ```
+
+ - In a list:
+   ``` rust
+   not_sync();
+   ```"
+    );
+}
+
+#[test]
+fn expand_doc_prose() {
+    let args = quote!(idents(Client), sync(), async());
+    let input = quote!(
+        /// The client.
+        ///
+        /// {{sync: Blocks the thread | async: Does not block the thread}} while waiting.
+        /// Returns {{async, blocking: later | _: now}}.
+        ///
+        /// <!-- only_if(async) -->
+        /// Requires a runtime.
+        /// <!-- end -->
+        /// Dropping the [`{Self}`] closes it<!-- remove_if(sync) -->, even when busy<!-- end -->.
+        ///
+        /// ```rust, ignore
+        /// let s = "{Self} {{sync: a | async: b}}";
+        /// ```
+        struct Client;
+    );
+    let docs = |key| {
+        let tokens = maybe_async_cfg_core::expand(args.clone(), input.clone(), Some(key)).unwrap();
+        doc_lines(tokens).join("\n")
+    };
+
+    assert_eq!(
+        docs("sync"),
+        " The client.
+
+ Blocks the thread while waiting.
+ Returns now.
+
+ Dropping the [`ClientSync`] closes it.
+
+ ```rust, ignore
+ let s = \"{Self} {{sync: a | async: b}}\";
+ ```"
+    );
+    assert_eq!(
+        docs("async"),
+        " The client.
+
+ Does not block the thread while waiting.
+ Returns later.
+
+ Requires a runtime.
+ Dropping the [`ClientAsync`] closes it, even when busy.
+
+ ```rust, ignore
+ let s = \"{Self} {{sync: a | async: b}}\";
+ ```"
+    );
+}
+
+#[test]
+fn expand_doc_links() {
+    let args = quote!(idents(Client, fetch(fn)), sync(), async());
+    let input = quote!(
+        /// See [`Client::get`], [Client], [`fetch()`], [the client](Client "title"),
+        /// [`Client`](crate::Client), [struct](struct@Client), [`Client`][client-ref], [`Client`][],
+        /// [Self::get] and [web](https://example.com/Client).
+        ///
+        /// [client-ref]: crate::Client
+        /// [`Client`]: Client
+        struct Client;
+    );
+
+    let sync = maybe_async_cfg_core::expand(args.clone(), input.clone(), Some("sync")).unwrap();
+    assert_eq!(
+        doc_lines(sync).join("\n"),
+        " See [`ClientSync::get`], [ClientSync], [`fetch_sync()`], [the client](ClientSync \"title\"),
+ [`ClientSync`](crate::ClientSync), [struct](struct@ClientSync), [`Client`][client-ref], [`ClientSync`][],
+ [Self::get] and [web](https://example.com/Client).
+
+ [client-ref]: crate::ClientSync
+ [`ClientSync`]: ClientSync"
+    );
+
+    let r#async = maybe_async_cfg_core::expand(args, input, Some("async")).unwrap();
+    assert_eq!(
+        doc_lines(r#async).join("\n"),
+        " See [`ClientAsync::get`], [ClientAsync], [`fetch_async()`], [the client](ClientAsync \"title\"),
+ [`ClientAsync`](crate::ClientAsync), [struct](struct@ClientAsync), [`Client`][client-ref], [`ClientAsync`][],
+ [Self::get] and [web](https://example.com/Client).
+
+ [client-ref]: crate::ClientAsync
+ [`ClientAsync`]: ClientAsync"
+    );
+}
+
+#[test]
+fn expand_replace_features() {
+    let args = quote!(sync(replace_feature("secure", "secure_sync")), async());
+    let input = quote!(
+        #[cfg_attr(feature = "secure", derive(Debug))]
+        #[cfg_attr(docsrs, cfg_attr(feature = "secure", doc(cfg(feature = "secure"))))]
+        #[cfg_attr(feature = "secure", tracing::instrument(fields(port = %self.port)))]
+        #[cfg(any(feature = "secure", feature = "other"))]
+        async fn is_secure(&self) -> bool {
+            #[cfg_attr(all(feature = "secure", not(test)), allow(unused))]
+            let x = 1;
+            cfg!(feature = "secure") || cfg!(all(unix, feature = "secure"))
+        }
+    );
+
+    let sync = maybe_async_cfg_core::expand(args.clone(), input.clone(), Some("sync")).unwrap();
+    assert_eq!(
+        sync.to_string(),
+        quote!(
+            #[cfg_attr(feature = "secure_sync", derive(Debug))]
+            #[cfg_attr(docsrs, cfg_attr(feature = "secure_sync", doc(cfg(feature = "secure_sync"))))]
+            #[cfg_attr(feature = "secure_sync", tracing::instrument(fields(port = %self.port)))]
+            #[cfg(any(feature = "secure_sync", feature = "other"))]
+            fn is_secure_sync(&self) -> bool {
+                #[cfg_attr(all(feature = "secure_sync", not(test)), allow(unused))]
+                let x = 1;
+                cfg!(feature = "secure_sync") || cfg!(all(unix, feature = "secure_sync"))
+            }
+        )
+        .to_string()
+    );
+
+    // the async version keeps the features
+    let r#async = maybe_async_cfg_core::expand(args, input, Some("async")).unwrap();
+    assert!(!r#async.to_string().contains("secure_sync"), "{}", r#async);
+}
@@ -0,0 +1,347 @@
+//! Prints the code generated by `maybe-async-cfg` for a source file:
+//!
+//! ```text
+//! maybe-async-cfg-expand <FILE> [KEY]
+//! ```
+//!
+//! All the items marked with the `maybe` attribute (and the `content!` macros) are expanded, and
+//! the `remove` and `noop` attributes they produce are applied; if the version key is specified,
+//! only this version is printed. So the versions can be compared
+//! with any diff tool:
+//!
+//! ```text
+//! diff <(maybe-async-cfg-expand src/client.rs sync) <(maybe-async-cfg-expand src/client.rs async)
+//! ```
+
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use syn::{
+    parse::{Parse, ParseStream, Parser},
+    visit_mut::{self, VisitMut},
+};
+
+const USAGE: &str = "Usage: maybe-async-cfg-expand <FILE> [KEY]";
+
+const MACRO_MAYBE_NAME: &str = "maybe";
+const MACRO_CONTENT_NAME: &str = "content";
+const MACRO_NOOP_NAME: &str = "noop";
+const MACRO_REMOVE_NAME: &str = "remove";
+const DEFAULT_CRATE_NAME: &str = "maybe_async_cfg";
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// `maybe`, `maybe_async_cfg::maybe` or `renamed_crate::maybe`
+fn is_our_path(path: &syn::Path, name: &str) -> bool {
+    match path.segments.len() {
+        1 => path.segments[0].ident == name,
+        2 => path.segments[1].ident == name,
+        _ => false,
+    }
+}
+
+// `content!` is too common a name to be recognized without the crate name
+fn is_content_path(path: &syn::Path) -> bool {
+    path.segments.len() == 2
+        && path.segments[0].ident == DEFAULT_CRATE_NAME
+        && path.segments[1].ident == MACRO_CONTENT_NAME
+}
+
+// `(args)` -> `args`
+fn attr_args(attr: &syn::Attribute) -> TokenStream {
+    let mut iter = attr.tokens.clone().into_iter();
+    match (iter.next(), iter.next()) {
+        (Some(TokenTree::Group(group)), None) if group.delimiter() == Delimiter::Parenthesis => {
+            group.stream()
+        }
+        _ => attr.tokens.clone(),
+    }
+}
+
+fn item_attrs_mut(item: &mut syn::Item) -> Option<&mut Vec<syn::Attribute>> {
+    match item {
+        syn::Item::Const(item) => Some(&mut item.attrs),
+        syn::Item::Enum(item) => Some(&mut item.attrs),
+        syn::Item::ExternCrate(item) => Some(&mut item.attrs),
+        syn::Item::Fn(item) => Some(&mut item.attrs),
+        syn::Item::ForeignMod(item) => Some(&mut item.attrs),
+        syn::Item::Impl(item) => Some(&mut item.attrs),
+        syn::Item::Macro(item) => Some(&mut item.attrs),
+        syn::Item::Macro2(item) => Some(&mut item.attrs),
+        syn::Item::Mod(item) => Some(&mut item.attrs),
+        syn::Item::Static(item) => Some(&mut item.attrs),
+        syn::Item::Struct(item) => Some(&mut item.attrs),
+        syn::Item::Trait(item) => Some(&mut item.attrs),
+        syn::Item::TraitAlias(item) => Some(&mut item.attrs),
+        syn::Item::Type(item) => Some(&mut item.attrs),
+        syn::Item::Union(item) => Some(&mut item.attrs),
+        syn::Item::Use(item) => Some(&mut item.attrs),
+        _ => None,
+    }
+}
+
+// Removes the first `maybe` attribute and returns its arguments
+fn take_maybe_attr(item: &mut syn::Item) -> Option<TokenStream> {
+    let attrs = item_attrs_mut(item)?;
+    let pos = attrs
+        .iter()
+        .position(|attr| is_our_path(&attr.path, MACRO_MAYBE_NAME))?;
+
+    Some(attr_args(&attrs.remove(pos)))
+}
+
+fn expand_tokens(tokens: TokenStream, key: Option<&str>) -> syn::Result<Vec<syn::Item>> {
+    let file: syn::File = syn::parse2(tokens)?;
+    expand_items(file.items, key)
+}
+
+fn expand_items(items: Vec<syn::Item>, key: Option<&str>) -> syn::Result<Vec<syn::Item>> {
+    let mut result = vec![];
+
+    for mut item in items {
+        if let Some(args) = take_maybe_attr(&mut item) {
+            let tokens = maybe_async_cfg_core::expand(args, quote::quote!(#item), key)?;
+            result.extend(expand_tokens(tokens, key)?);
+            continue;
+        }
+
+        match item {
+            syn::Item::Macro(item) if is_content_path(&item.mac.path) => {
+                let tokens = maybe_async_cfg_core::content(item.mac.tokens)?;
+                result.extend(expand_tokens(tokens, key)?);
+            }
+            syn::Item::Mod(mut item) => {
+                if let Some((brace, items)) = item.content.take() {
+                    item.content = Some((brace, expand_items(items, key)?));
+                }
+                result.push(syn::Item::Mod(item));
+            }
+            item => result.push(item),
+        }
+    }
+
+    Ok(result)
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+fn impl_item_attrs_mut(item: &mut syn::ImplItem) -> Option<&mut Vec<syn::Attribute>> {
+    match item {
+        syn::ImplItem::Const(item) => Some(&mut item.attrs),
+        syn::ImplItem::Method(item) => Some(&mut item.attrs),
+        syn::ImplItem::Type(item) => Some(&mut item.attrs),
+        syn::ImplItem::Macro(item) => Some(&mut item.attrs),
+        _ => None,
+    }
+}
+
+fn trait_item_attrs_mut(item: &mut syn::TraitItem) -> Option<&mut Vec<syn::Attribute>> {
+    match item {
+        syn::TraitItem::Const(item) => Some(&mut item.attrs),
+        syn::TraitItem::Method(item) => Some(&mut item.attrs),
+        syn::TraitItem::Type(item) => Some(&mut item.attrs),
+        syn::TraitItem::Macro(item) => Some(&mut item.attrs),
+        _ => None,
+    }
+}
+
+fn foreign_item_attrs_mut(item: &mut syn::ForeignItem) -> Option<&mut Vec<syn::Attribute>> {
+    match item {
+        syn::ForeignItem::Fn(item) => Some(&mut item.attrs),
+        syn::ForeignItem::Static(item) => Some(&mut item.attrs),
+        syn::ForeignItem::Type(item) => Some(&mut item.attrs),
+        syn::ForeignItem::Macro(item) => Some(&mut item.attrs),
+        _ => None,
+    }
+}
+
+fn parse_many<T: Parse>(input: ParseStream) -> syn::Result<Vec<T>> {
+    let mut nodes = vec![];
+    while !input.is_empty() {
+        nodes.push(input.parse()?);
+    }
+    Ok(nodes)
+}
+
+// Expands the `remove` and `noop` attributes of the nodes, like the compiler does with the code
+// produced by `maybe`
+fn expand_attr_macros<T: Parse + quote::ToTokens>(
+    nodes: Vec<T>,
+    attrs_mut: fn(&mut T) -> Option<&mut Vec<syn::Attribute>>,
+) -> syn::Result<Vec<T>> {
+    let mut result = vec![];
+
+    for mut node in nodes {
+        let attrs = match attrs_mut(&mut node) {
+            Some(attrs) => attrs,
+            None => {
+                result.push(node);
+                continue;
+            }
+        };
+        let pos = attrs.iter().position(|attr| {
+            is_our_path(&attr.path, MACRO_REMOVE_NAME) || is_our_path(&attr.path, MACRO_NOOP_NAME)
+        });
+        let attr = match pos {
+            Some(pos) => attrs.remove(pos),
+            None => {
+                result.push(node);
+                continue;
+            }
+        };
+
+        let args = attr_args(&attr);
+        let tokens = quote::quote!(#node);
+        let tokens = if is_our_path(&attr.path, MACRO_REMOVE_NAME) {
+            maybe_async_cfg_core::remove(args, tokens)
+        } else {
+            maybe_async_cfg_core::noop(args, tokens)
+        };
+
+        // the node may have more attributes to expand
+        let nodes = parse_many.parse2(tokens)?;
+        result.extend(expand_attr_macros(nodes, attrs_mut)?);
+    }
+
+    Ok(result)
+}
+
+#[derive(Default)]
+struct AttrMacros {
+    error: Option<syn::Error>,
+}
+
+impl AttrMacros {
+    fn expand<T: Parse + quote::ToTokens>(
+        &mut self,
+        nodes: &mut Vec<T>,
+        attrs_mut: fn(&mut T) -> Option<&mut Vec<syn::Attribute>>,
+    ) {
+        match expand_attr_macros(std::mem::take(nodes), attrs_mut) {
+            Ok(expanded) => *nodes = expanded,
+            Err(err) => match &mut self.error {
+                Some(prev) => prev.combine(err),
+                None => self.error = Some(err),
+            },
+        }
+    }
+}
+
+impl VisitMut for AttrMacros {
+    fn visit_file_mut(&mut self, node: &mut syn::File) {
+        self.expand(&mut node.items, item_attrs_mut);
+        visit_mut::visit_file_mut(self, node);
+    }
+
+    fn visit_item_mod_mut(&mut self, node: &mut syn::ItemMod) {
+        if let Some((_, items)) = &mut node.content {
+            self.expand(items, item_attrs_mut);
+        }
+        visit_mut::visit_item_mod_mut(self, node);
+    }
+
+    fn visit_item_impl_mut(&mut self, node: &mut syn::ItemImpl) {
+        self.expand(&mut node.items, impl_item_attrs_mut);
+        visit_mut::visit_item_impl_mut(self, node);
+    }
+
+    fn visit_item_trait_mut(&mut self, node: &mut syn::ItemTrait) {
+        self.expand(&mut node.items, trait_item_attrs_mut);
+        visit_mut::visit_item_trait_mut(self, node);
+    }
+
+    fn visit_item_foreign_mod_mut(&mut self, node: &mut syn::ItemForeignMod) {
+        self.expand(&mut node.items, foreign_item_attrs_mut);
+        visit_mut::visit_item_foreign_mod_mut(self, node);
+    }
+
+    fn visit_block_mut(&mut self, node: &mut syn::Block) {
+        let mut stmts = vec![];
+        for stmt in node.stmts.drain(..) {
+            match stmt {
+                syn::Stmt::Item(item) => {
+                    let mut items = vec![item];
+                    self.expand(&mut items, item_attrs_mut);
+                    stmts.extend(items.into_iter().map(syn::Stmt::Item));
+                }
+                stmt => stmts.push(stmt),
+            }
+        }
+        node.stmts = stmts;
+        visit_mut::visit_block_mut(self, node);
+    }
+}
+
+fn expand_file(file: &mut syn::File, key: Option<&str>) -> syn::Result<()> {
+    file.items = expand_items(std::mem::take(&mut file.items), key)?;
+
+    let mut attr_macros = AttrMacros::default();
+    attr_macros.visit_file_mut(file);
+    match attr_macros.error {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Profiles are searched for relative to the crate root, as if the file is being compiled by cargo
+fn set_manifest_dir(path: &Path) {
+    if std::env::var_os("CARGO_MANIFEST_DIR").is_some() {
+        return;
+    }
+
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if let Some(dir) = path.ancestors().find(|dir| dir.join("Cargo.toml").is_file()) {
+        std::env::set_var("CARGO_MANIFEST_DIR", dir);
+    }
+}
+
+fn report(path: &Path, err: syn::Error) {
+    for err in err {
+        let start = err.span().start();
+        eprintln!("error: {}", err);
+        eprintln!(" --> {}:{}:{}", path.display(), start.line, start.column + 1);
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (path, key) = match args.as_slice() {
+        [path] => (PathBuf::from(path), None),
+        [path, key] => (PathBuf::from(path), Some(key.as_str())),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let source = match std::fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("error: can not read `{}`: {}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    set_manifest_dir(&path);
+
+    let result = syn::parse_file(&source).and_then(|mut file| {
+        expand_file(&mut file, key)?;
+        Ok(file)
+    });
+
+    match result {
+        Ok(file) => {
+            print!("{}", prettyplease::unparse(&file));
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            report(&path, err);
+            ExitCode::FAILURE
+        }
+    }
+}
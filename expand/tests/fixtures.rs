@@ -0,0 +1,21 @@
+use std::{path::Path, process::Command};
+
+// Expands `fixtures/<name>.rs` and compares the version with the `key` to `fixtures/<name>.<key>.rs`
+fn check_fixture(name: &str, key: &str) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let output = Command::new(env!("CARGO_BIN_EXE_maybe-async-cfg-expand"))
+        .arg(dir.join(format!("{}.rs", name)))
+        .arg(key)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let expected = std::fs::read_to_string(dir.join(format!("{}.{}.rs", name, key))).unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn removed_items() {
+    check_fixture("removed-items", "sync");
+    check_fixture("removed-items", "async");
+}
@@ -0,0 +1,15 @@
+impl ClientAsync {
+    fn is_async(&self) -> bool {
+        true
+    }
+}
+trait ConnectAsync {
+    async fn spawn(&self);
+    async fn connect(&self) -> ClientAsync;
+}
+async fn run_async() {
+    fn name() -> &'static str {
+        "async"
+    }
+    println!("{}", name());
+}
@@ -0,0 +1,35 @@
+#[maybe_async_cfg::maybe(idents(Client), sync(), async())]
+impl Client {
+    #[maybe_async_cfg::only_if(async)]
+    fn is_async(&self) -> bool {
+        true
+    }
+
+    #[maybe_async_cfg::remove_if(async)]
+    fn is_async(&self) -> bool {
+        false
+    }
+}
+
+#[maybe_async_cfg::maybe(idents(Client), sync(), async())]
+trait Connect {
+    #[maybe_async_cfg::only_if(async)]
+    async fn spawn(&self);
+
+    async fn connect(&self) -> Client;
+}
+
+#[maybe_async_cfg::maybe(sync(), async())]
+async fn run() {
+    #[maybe_async_cfg::only_if(async)]
+    fn name() -> &'static str {
+        "async"
+    }
+
+    #[maybe_async_cfg::only_if(sync)]
+    fn name() -> &'static str {
+        "sync"
+    }
+
+    println!("{}", name());
+}
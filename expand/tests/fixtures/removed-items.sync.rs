@@ -0,0 +1,14 @@
+impl ClientSync {
+    fn is_async(&self) -> bool {
+        false
+    }
+}
+trait ConnectSync {
+    fn connect(&self) -> ClientSync;
+}
+fn run_sync() {
+    fn name() -> &'static str {
+        "sync"
+    }
+    println!("{}", name());
+}
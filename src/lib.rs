@@ -23,8 +23,11 @@
 //! - trait declaration
 //! - trait implementation
 //! - function definition
-//! - struct and enum definition
+//! - struct, enum and union definition
 //! - modules
+//! - type aliases, constants and statics
+//! - `macro_rules!` definitions
+//! - extern blocks
 //!
 //! **RECOMMENDATION**: Enable **resolver ver2** in your crate, which is introduced in Rust 1.51. If
 //! not, two crates in dependency with conflict version (one async and another blocking) can fail
@@ -150,6 +153,158 @@
 //!     }
 //!     ```
 //!
+//! - procedural macros **`sync_impl`**, **`async_impl`** and **`version_impl`**
+//!
+//!     Mark code inside a `maybe` item that should be kept only in some versions.
+//!   `sync_impl!{ ... }` is kept in the sync versions, `async_impl!{ ... }` in the async ones, and
+//!   `version_impl!(key = "...", { ... })` in the versions with the specified keys (several keys
+//!   may be given, like in `only_if`). The kept content becomes a block; in statement position
+//!   the removed content is dropped, in expression position it becomes `()`.
+//!
+//!     ```rust
+//!     #[maybe_async_cfg::maybe(
+//!         sync(feature="use_sync"),
+//!         async(feature="use_async")
+//!     )]
+//!     async fn pause(ms: u64) {
+//!         maybe_async_cfg::sync_impl! {
+//!             std::thread::sleep(std::time::Duration::from_millis(ms));
+//!         }
+//!         maybe_async_cfg::async_impl! {
+//!             tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+//!         }
+//!     }
+//!     ```
+//!     After convertation:
+//!     ```rust
+//!     #[cfg(feature="use_sync")]
+//!     fn pause_sync(ms: u64) {
+//!         {
+//!             std::thread::sleep(std::time::Duration::from_millis(ms));
+//!         }
+//!     }
+//!     #[cfg(feature="use_async")]
+//!     async fn pause_async(ms: u64) {
+//!         {
+//!             tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+//!         }
+//!     }
+//!     ```
+//!
+//! ## Configuration file
+//!
+//! Parameters shared by many modules can be moved into the `maybe-async-cfg.toml` file placed next to
+//! `Cargo.toml` (or in one of its parent directories, e.g. the workspace root; the `MAYBE_ASYNC_CFG_CONFIG`
+//! environment variable may specify another path relative to the crate root). The file contains named
+//! profiles, each of them is a string with the `maybe` parameters:
+//!
+//! ```toml
+//! [profiles]
+//! client = '''
+//!     idents(Client, Connection),
+//!     sync(feature="use_sync"),
+//!     async(feature="use_async"),
+//! '''
+//! ```
+//!
+//! The `profile` parameter merges the profile into the parameters of the `maybe` macro, like the
+//! `default` attribute of the `content` macro does. The parameters specified in place take precedence
+//! (e.g. an `idents` entry with the same name as in the profile), and the versions of the profile are
+//! used only if there are no versions specified in place. The flags of the profile (like `streams` or
+//! `keep_self`) are added to the ones specified in place and can not be turned off there:
+//!
+//! ```rust, ignore
+//! #[maybe_async_cfg::maybe(profile = "client")]
+//! async fn connect(client: &Client) -> Connection {
+//!     client.connect().await
+//! }
+//! ```
+//! After convertation:
+//! ```rust
+//! #[cfg(feature="use_sync")]
+//! fn connect_sync(client: &ClientSync) -> ConnectionSync {
+//!     client.connect()
+//! }
+//! #[cfg(feature="use_async")]
+//! async fn connect_async(client: &ClientAsync) -> ConnectionAsync {
+//!     client.connect().await
+//! }
+//! ```
+//!
+//! The configuration file is searched for up to the workspace root (or only in the crate root if the
+//! crate is not a member of a workspace). The expansion includes the file with `include_bytes!` once
+//! per item, so cargo rebuilds the crate after the file is edited. Since the `const _` item is not
+//! allowed among associated items, a function using a profile (it may be a method) gets this item
+//! inside the body of its first version (so it is not tracked if this version is disabled by its
+//! `cfg`), and the constants, type aliases and macro calls using a profile are not tracked.
+//!
+//! ## Concurrency combinators
+//!
+//! In the sync version, the common combinators of `tokio` and `futures` are replaced with their
+//! sequential equivalents:
+//!
+//! - `join!(a, b)` (as well as `tokio::join!`, `futures::join!` and `futures::future::join`,
+//!   `join3`, ...) becomes the tuple `(a, b)`;
+//! - `try_join!(a, b)` (as well as `futures::future::try_join`, `try_join3`, ...) evaluates the
+//!   results one by one and returns `Ok((a, b))` or the first error;
+//! - `join_all(iter)` becomes `iter.into_iter().collect::<Vec<_>>()`, `try_join_all(iter)` becomes
+//!   `iter.into_iter().collect::<Result<Vec<_>, _>>()` (these functions are recognized by the
+//!   `futures::future`, `futures_util::future` and `future` paths, or by the names imported from
+//!   `futures::future` in the item itself, so a function of your own named `join_all` is left
+//!   intact);
+//! - `select!` (`tokio::select!`, `futures::select!` or `futures::select_biased!`) has no
+//!   sequential equivalent, so the arm used in the sync version must be marked with `#[sync]`;
+//!   otherwise an error is reported. The marked arm is executed unconditionally, the marker is
+//!   removed in the async version. If the pattern of the marked arm may not match (like
+//!   `Some(v) = rx.recv()`), the `else` arm is executed in this case, and it is an error if there
+//!   is no `else` arm. A bare `select!` whose arguments are not the arms of `select!` is taken for
+//!   a macro of your own and left intact.
+//!
+//! ```rust
+//! #[maybe_async_cfg::maybe(
+//!     sync(feature="use_sync"),
+//!     async(feature="use_async")
+//! )]
+//! async fn fetch_all(client: &Client, ids: &[u32]) -> Result<Vec<Item>, Error> {
+//!     let (first, last) = tokio::try_join!(client.first(), client.last())?;
+//!     let mut items = futures::future::try_join_all(ids.iter().map(|id| client.get(*id))).await?;
+//!     items.push(tokio::select! {
+//!         #[sync]
+//!         item = client.get(0) => item?,
+//!         _ = tokio::time::sleep(TIMEOUT) => return Err(Error::Timeout),
+//!     });
+//!     Ok(items)
+//! }
+//! ```
+//! After convertation:
+//! ```rust
+//! #[cfg(feature="use_sync")]
+//! fn fetch_all_sync(client: &Client, ids: &[u32]) -> Result<Vec<Item>, Error> {
+//!     let (first, last) = match client.first() {
+//!         Ok(__maybe_async_cfg_value0) => match client.last() {
+//!             Ok(__maybe_async_cfg_value1) => Ok((__maybe_async_cfg_value0, __maybe_async_cfg_value1)),
+//!             Err(err) => Err(err),
+//!         },
+//!         Err(err) => Err(err),
+//!     }?;
+//!     let mut items = ids.iter().map(|id| client.get(*id)).into_iter().collect::<Result<Vec<_>, _>>()?;
+//!     items.push(match client.get(0) {
+//!         item => item?,
+//!     });
+//!     Ok(items)
+//! }
+//! #[cfg(feature="use_async")]
+//! async fn fetch_all_async(client: &Client, ids: &[u32]) -> Result<Vec<Item>, Error> {
+//!     let (first, last) = tokio::try_join!(client.first(), client.last())?;
+//!     let mut items = futures::future::try_join_all(ids.iter().map(|id| client.get(*id))).await?;
+//!     items.push(tokio::select! {
+//!         item = client.get(0) => item?,
+//!         _ = tokio::time::sleep(TIMEOUT) => return Err(Error::Timeout),
+//!     });
+//!     Ok(items)
+//! }
+//! ```
+//!
 //! ## Doctests
 //!     
 //! When writing doctests, you can mark them as applicable only in the corresponding code version. 
@@ -195,6 +350,161 @@
 //! }
 //! ```
 //!
+//! Several keys can be listed at once: `only_if(sync, blocking)` keeps the doctest in both
+//! versions. `remove_if(`_VARIANT_KEY_, ...`)` does the opposite and removes the doctest from the
+//! listed versions only. `flags_if(`_VARIANT_KEY_`, `_FLAG_, ...`)` adds the flags to the doctest
+//! attributes in the version with this key only, e.g. `flags_if(async, no_run)` does not run the
+//! doctest of the async version, which requires a runtime:
+//!
+//! ```rust
+//! #[maybe_async_cfg::maybe(
+//!     idents(Foo),
+//!     sync(feature="use_sync"),
+//!     async(feature="use_async")
+//! )]
+//! /// This is a structure.
+//! /// ```rust, flags_if(async, no_run)
+//! /// let f = connect();
+//! /// ```
+//! struct Struct {
+//!     f: Foo,
+//! }
+//! ```
+//! After convertation:
+//! ```rust
+//! #[cfg(feature="use_sync")]
+//! /// This is a structure.
+//! /// ```rust
+//! /// let f = connect();
+//! /// ```
+//! struct StructSync {
+//!     f: FooSync,
+//! }
+//! #[cfg(feature="use_async")]
+//! /// This is a structure.
+//! /// ```rust, no_run
+//! /// let f = connect();
+//! /// ```
+//! struct StructAsync {
+//!     f: FooAsync,
+//! }
+//! ```
+//!
+//! With the `convert_doctests` parameter, the code of the doctests is converted too, so a single
+//! example serves all the versions. The code is parsed as the body of a function and converted
+//! like the item itself (`idents`, `paths`, `runtime` and so on apply), except that all the
+//! functions in it lose `async` in the `sync` version, as with `recursive_asyncness_removal`. The
+//! `#[tokio::main]` and `#[async_std::main]` attributes (and their `test` counterparts) are removed
+//! from the `sync` version. Only the changed tokens are replaced in the text of the doctest, so
+//! the formatting, comments, string literals and hidden lines of the rest stay unchanged. Code
+//! blocks in other languages (e.g. `text`) are left as is. A doctest which can not be parsed or
+//! converted is reported as an error, unless it is marked with `ignore` or `compile_fail`: such a
+//! block is left as is.
+//!
+//! ```rust
+//! #[maybe_async_cfg::maybe(
+//!     convert_doctests,
+//!     idents(Foo),
+//!     sync(feature="use_sync"),
+//!     async(feature="use_async")
+//! )]
+//! /// This is a structure.
+//! /// ```rust
+//! /// # async fn example() {
+//! /// let s = Struct{ f: Foo::new().await };
+//! /// # }
+//! /// ```
+//! struct Struct {
+//!     f: Foo,
+//! }
+//! ```
+//! After convertation:
+//! ```rust
+//! #[cfg(feature="use_sync")]
+//! /// This is a structure.
+//! /// ```rust
+//! /// # fn example() {
+//! /// let s = StructSync{ f: FooSync::new() };
+//! /// # }
+//! /// ```
+//! struct StructSync {
+//!     f: FooSync,
+//! }
+//! #[cfg(feature="use_async")]
+//! /// This is a structure.
+//! /// ```rust
+//! /// # async fn example() {
+//! /// let s = StructAsync{ f: FooAsync::new().await };
+//! /// # }
+//! /// ```
+//! struct StructAsync {
+//!     f: FooAsync,
+//! }
+//! ```
+//!
+//! The text of the documentation can differ between the versions too:
+//!
+//! - `<!-- only_if(`_VARIANT_KEY_, ...`) -->`...`<!-- end -->` keeps the text in the listed versions
+//!   only, and `<!-- remove_if(`_VARIANT_KEY_, ...`) -->`...`<!-- end -->` removes it from them. A
+//!   marker on its own line is removed together with the line.
+//! - `{{sync: ... | async: ...}}` is replaced with the alternative for the version with this key (an
+//!   alternative can be listed for several keys, `_` is the default one).
+//! - `{Self}` is replaced with the name of the item in this version, including the inline code, so
+//!   ``[`{Self}`]`` is a link to the right item.
+//!
+//! The code blocks and the inline code (except `{Self}`) are not touched.
+//!
+//! The targets of the intra-doc links are renamed according to `idents` in all the versions, so
+//! ``[`Client::get`]``, `[client](crate::Client)` and `[client]: Client` definitions point to
+//! `ClientSync` in the `sync` version. The text of the link is renamed too if it repeats the target
+//! (``[`Client`](crate::Client)`` becomes ``[`ClientSync`](crate::ClientSync)``).
+//!
+//! ```rust
+//! #[maybe_async_cfg::maybe(sync(feature="use_sync"), async(feature="use_async"))]
+//! /// Fetches the data and {{sync: returns it | async: returns a future resolving to it}}.
+//! ///
+//! /// <!-- only_if(async) -->
+//! /// Requires a runtime.
+//! /// <!-- end -->
+//! /// See also [`{Self}`].
+//! async fn fetch() -> u32 {
+//!     42
+//! }
+//! ```
+//! After convertation:
+//! ```rust
+//! #[cfg(feature="use_sync")]
+//! /// Fetches the data and returns it.
+//! ///
+//! /// See also [`fetch_sync`].
+//! fn fetch_sync() -> u32 {
+//!     42
+//! }
+//! #[cfg(feature="use_async")]
+//! /// Fetches the data and returns a future resolving to it.
+//! ///
+//! /// Requires a runtime.
+//! /// See also [`fetch_async`].
+//! async fn fetch_async() -> u32 {
+//!     42
+//! }
+//! ```
+//!
+//! ## Reviewing the generated code
+//!
+//! The `maybe-async-cfg-expand` tool (the `expand` directory of the repository) prints the formatted
+//! code generated for a source file. Only the version with the specified key is printed (or all the
+//! versions, if the key is omitted), so the versions can be compared with any diff tool:
+//!
+//! ```text
+//! cargo run -p maybe-async-cfg-expand -- src/client.rs sync > client_sync.rs
+//! cargo run -p maybe-async-cfg-expand -- src/client.rs async > client_async.rs
+//! diff client_sync.rs client_async.rs
+//! ```
+//!
+//! The items marked with the `maybe` attribute, the `content!` macros and the inline modules are
+//! expanded; the rest of the file is printed as is.
+//!
 //! ## Examples
 //!
 //! ### rust client for services
@@ -226,35 +536,8 @@
 #![deny(rustdoc::missing_doc_code_examples)]
 
 use proc_macro::TokenStream;
-use proc_macro_error::proc_macro_error;
-
-mod macros;
-mod params;
-mod utils;
-mod visit_ext;
-mod visitor_async;
-mod visitor_content;
-
-mod doctests;
-
-mod debug;
+use proc_macro_error::{abort_call_site, proc_macro_error};
 
-const DEFAULT_CRATE_NAME: &'static str = "maybe_async_cfg";
-const MACRO_MAYBE_NAME: &'static str = "maybe";
-const MACRO_ONLY_IF_NAME: &'static str = "only_if";
-const MACRO_REMOVE_IF_NAME: &'static str = "remove_if";
-const MACRO_NOOP_NAME: &'static str = "noop";
-const MACRO_REMOVE_NAME: &'static str = "remove";
-const MACRO_DEFAULT_NAME: &'static str = "default";
-
-const STANDARD_MACROS: &'static [&'static str] = &[
-    "dbg",
-    "print",
-    "println",
-    "assert",
-    "assert_eq",
-    "assert_ne",
-];
 
 /// Marks the code that can be presented in several versions. 
 /// 
@@ -269,6 +552,12 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 /// 
 ///     The name of `maybe-async-cfg` crate. If not set, `"maybe_async_cfg"` will be used.
 ///
+/// - `profile`
+///
+///     The name of the profile from the `maybe-async-cfg.toml` file (see the crate-level
+/// documentation). The parameters of the profile are merged into the parameters of the macro, the
+/// parameters specified in place take precedence (the flags of the profile are added to them).
+///
 /// - `sync`, `async` 
 /// 
 ///     Defines versions of the code: the item to which the attribute `maybe` refers will be 
@@ -277,11 +566,121 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 ///
 ///     For the `sync` version, the item will be converted from async to sync code by deleting
 /// the `async` and `await` keywords. The types `Future<Output=XXX>` will also be replaced with just
-/// `XXX`. For the  `async` version, the item will be left async.
+/// `XXX`. Functions returning `impl Future<Output = XXX>`, `Pin<Box<dyn Future<Output = XXX>>>`,
+/// `BoxFuture<'a, XXX>` or `LocalBoxFuture<'a, XXX>` will return just `XXX`, and a body consisting
+/// of a single `async move { ... }` block (possibly wrapped in `Box::pin(...)`, `.boxed()` or
+/// `.boxed_local()`) will be replaced with the block itself. Async closures become plain closures:
+/// `async move |x| ...` becomes `move |x| ...`, `|| async move { ... }` becomes `move || { ... }`,
+/// and the `AsyncFn`, `AsyncFnMut` and `AsyncFnOnce` bounds become `Fn`, `FnMut` and `FnOnce`. For
+/// the `async` version, the item will be left async.
+///
+///     The generic parameters bounded by `Future<Output = XXX>` (in place or in the `where` clause)
+/// are removed from functions, methods, impls, structs, enums, unions, traits and type aliases, and
+/// replaced with `XXX` inside the item; the arguments of type `impl Future<Output = XXX>` become
+/// `XXX` too. In the header of an impl, such parameters are removed from the type and the trait as
+/// well, so `impl<F: Future<Output = u32>> Task<F>` becomes `impl TaskSync` for the `struct
+/// Task<F: Future<Output = u32>>` converted into `struct TaskSync`. The output type can be any type
+/// (`Result<Vec<u8>, io::Error>`, `(&'a str, u32)`, `()`), but a `Future<Output = XXX>` bound on
+/// anything other than a generic parameter (like `S::Fut: Future<Output = XXX>`) is an error.
 ///
 ///     In any case, the item will be converted according to all the parameters described below. For
-/// functions, structs/enums and traits, the name will be changed as if it is mentioned in the
-/// `idents` list (if it is not explicitly specified there and if `keep_self` is not present).
+/// functions, modules, structs/enums/unions, traits, type aliases, constants, statics and
+/// `macro_rules!` definitions, the name will be changed as if it is mentioned in the `idents` list
+/// (if it is not explicitly specified there and if `keep_self` is not present). The suffix matches
+/// the naming convention of the item: `Sync`/`Async` for types and traits, `_sync`/`_async` for
+/// functions, modules and macros, `_SYNC`/`_ASYNC` for constants and statics.
+///
+///     The bodies of `macro_rules!` definitions are converted token by token: identifiers from the
+/// `idents` list are renamed, and for the `sync` version the `async` and `.await` keywords are
+/// removed.
+///
+///     The items of extern blocks are declared in every version, so with several versions each
+/// function and static of the block must be listed in `idents` (otherwise it is an error). The
+/// renamed items get `#[link_name = "..."]` with the original name, so they are still linked to
+/// the same symbol.
+///
+/// - `blocking`
+///
+///     Defines a blocking version of the code, which is not a converted copy of the async code, but
+/// a thin wrapper around one of the async versions: a struct or enum `Foo` becomes a struct
+/// `FooBlocking` with a single field containing `FooAsync` (and `From<FooAsync>` is implemented
+/// for it), and the methods and functions delegate to the async version, blocking on the returned
+/// futures. `Self` in arguments and return types (including `Option<Self>` and `Result<Self, E>`)
+/// is wrapped and unwrapped automatically, as well as the arguments of the types listed in
+/// `idents` (`Foo`, `&Foo` or `&mut Foo`), which take the `FooBlocking` wrappers. Only structs, enums, inherent impls and functions are
+/// supported. The suffixes `Blocking`/`_blocking` are used by default.
+///
+///     The blocking version has the following additional parameters:
+///
+///     - `executor`
+///
+///         The executor used to block on futures: `"futures"` (`futures::executor::block_on`),
+/// `"tokio"` or `"async-std"` (`async_std::task::block_on`).
+///
+///         For `"tokio"`, all the blocking functions of the crate share a multi-thread runtime with
+/// one worker (the `rt-multi-thread` feature of `tokio` is required), so the connections and other
+/// resources created by one of them can be used by another. The runtime is defined by the
+/// [`blocking_runtime!`] macro, which must be invoked once at the crate root. It is created on the
+/// first call and kept for the rest of the program, so the tasks spawned by the async code keep
+/// running after the call returns. Note that these functions can not be called from inside a
+/// tokio runtime: tokio panics with "Cannot start a runtime from within a runtime".
+///
+///     - `block_on`
+///
+///         The path of a function used to block on futures, instead of `executor`. Use it to run all
+/// the blocking functions on a single runtime of your crate:
+///
+///         ```rust
+///         fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+///             static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+///             RUNTIME
+///                 .get_or_init(|| tokio::runtime::Runtime::new().expect("Failed to build tokio runtime"))
+///                 .block_on(fut)
+///         }
+///         ```
+///
+///     - `target`
+///
+///         The key of the async version to delegate to. If omitted, the first async version will be
+/// used.
+///
+///     ```rust
+///     #[maybe_async_cfg::maybe(
+///         idents(Client),
+///         async(feature="use_async"),
+///         blocking(feature="use_blocking", executor="futures"),
+///     )]
+///     impl Client {
+///         pub async fn connect(url: &str) -> Result<Self, Error> {
+///             todo!()
+///         }
+///         pub async fn get(&self, key: &str) -> Option<String> {
+///             todo!()
+///         }
+///     }
+///     ```
+///     After convertation:
+///     ```rust
+///     #[cfg(feature="use_async")]
+///     impl ClientAsync {
+///         pub async fn connect(url: &str) -> Result<Self, Error> {
+///             todo!()
+///         }
+///         pub async fn get(&self, key: &str) -> Option<String> {
+///             todo!()
+///         }
+///     }
+///     #[cfg(feature="use_blocking")]
+///     impl ClientBlocking {
+///         pub fn connect(url: &str) -> Result<Self, Error> {
+///             ::futures::executor::block_on(<ClientAsync>::connect(url))
+///                 .map(|inner| Self { inner })
+///         }
+///         pub fn get(&self, key: &str) -> Option<String> {
+///             ::futures::executor::block_on(self.inner.get(key))
+///         }
+///     }
+///     ```
 ///
 /// - All other parameters will be passed to all versions (with merging).
 ///
@@ -324,6 +723,45 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 ///         f: usize,
 ///     }
 ///     ```
+///
+///     Several keys can be specified at once: `only_if(foo, bar)` keeps the code in both versions
+/// `foo` and `bar`, `remove_if(foo, bar)` removes it from both.
+///
+/// - `suffix`
+///
+///     Defines the suffix that will be added to the renamed identifiers in this version instead of
+/// the standard `Sync`/`Async`. The suffix is written in camel case; for snake-case identifiers it
+/// will be converted accordingly (`suffix="AsyncStd"` gives `FooAsyncStd`, `foo_async_std` and
+/// `FOO_ASYNC_STD`). Together with `key`, it allows to have any number of versions of the same kind.
+/// Versions with the same suffix and the same conditions (like two `async` versions without
+/// `suffix`) would define the same names, so this is reported as an error.
+///
+///     ```rust
+///     #[maybe_async_cfg::maybe(
+///         idents(Client),
+///         sync(feature="blocking", suffix="Blocking"),
+///         async(feature="tokio", key="tokio", suffix="Tokio"),
+///         async(feature="async-std", key="async_std", suffix="AsyncStd"),
+///     )]
+///     async fn connect() -> Client {
+///         Client::connect().await
+///     }
+///     ```
+///     After convertation:
+///     ```rust
+///     #[cfg(feature="blocking")]
+///     fn connect_blocking() -> ClientBlocking {
+///         ClientBlocking::connect()
+///     }
+///     #[cfg(feature="tokio")]
+///     async fn connect_tokio() -> ClientTokio {
+///         ClientTokio::connect().await
+///     }
+///     #[cfg(feature="async-std")]
+///     async fn connect_async_std() -> ClientAsyncStd {
+///         ClientAsyncStd::connect().await
+///     }
+///     ```
 /// 
 /// - `cfg`
 /// 
@@ -365,6 +803,11 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 /// converted by adding the suffixes `"_sync"`/`"_async"` (otherwise, the suffixes 
 /// `"Sync"`/`"Async"` will be used).
 ///
+///     - `upper`, `const`, `static`
+///
+///         means that this is the upper-case name of the constant or static and it should be
+/// converted by adding the suffixes `"_SYNC"`/`"_ASYNC"`.
+///
 ///     - `use`  
 /// 
 ///         in `use` lists, using this identifier will result in renaming via the `as` expression, 
@@ -374,6 +817,10 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 ///
 ///         this identifier will not be converted anywhere
 ///
+///     - `allow_unused`
+///
+///         do not warn if this identifier is not found in the code (see below)
+///
 ///     - `sync`, `async`
 ///
 ///         specifies the name that will be used in the corresponding version of the code. Overrides
@@ -442,6 +889,76 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 ///     }
 ///     ```
 ///
+///     Instead of listing every identifier, a rule can rename all the identifiers matching a
+/// pattern. A rule is written as `regex = "..."`, `prefix = "..."` or `suffix = "..."` inside
+/// `idents(...)`, optionally followed by `sync = "..."`, `async = "..."` or `<key> = "..."` values
+/// (one rule per `idents(...)` list). The rule is applied to every identifier not listed explicitly.
+///
+///     - `regex = "^(.*)Client$"`
+///
+///         the whole identifier gets the default suffix (`HttpClient` -> `HttpClientSync`); the
+/// values may refer to the captured groups, like `sync = "${1}BlockingClient"`.
+///
+///     - `prefix = "Maybe"`, `suffix = "Async"`
+///
+///         the prefix or the suffix is removed and the default suffix is added to the rest of the
+/// identifier (`MaybeClient` -> `ClientSync`); a value without `$` replaces the prefix or the
+/// suffix (`suffix = "Async", sync = "Blocking"`: `ClientAsync` -> `ClientBlocking`).
+///
+///     The case of the default suffix is inferred from the identifier (`Foo`, `foo` or `FOO`).
+///
+///     ```rust
+///     #[maybe_async_cfg::maybe(
+///         idents(suffix = "Async", sync = "Blocking"),
+///         idents(prefix = "maybe_", sync = "blocking_", async = "async_"),
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///     )]
+///     async fn func(c: ClientAsync) {
+///         maybe_send(&c).await;
+///     }
+///     ```
+///     After convertation:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn func_sync(c: ClientBlocking) {
+///         blocking_send(&c);
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn func_async(c: ClientAsync) {
+///         async_send(&c).await;
+///     }
+///     ```
+///
+///     If an identifier listed in `idents` (but not matched by a rule) is not found anywhere in the
+/// code of the item, a warning is produced: usually it is a misprint or a leftover of a refactoring.
+/// The code removed by `sync_impl!`, `async_impl!` and `version_impl!` counts too, and the
+/// identifiers inherited from `content!` or a profile are not reported. The warning is produced once
+/// for the item, by the first of its versions listing the identifier (the disabled and `blocking`
+/// versions are skipped), so it is not produced if that version is excluded by its `cfg`. There is
+/// no way to emit a warning from a proc macro on stable Rust, so it is reported as a use of the
+/// deprecated constant `unused_ident` and controlled by the `deprecated` lint. It is silenced by the
+/// `allow_unused` parameter of the identifier or by the `allow_unused_idents` parameter.
+///
+/// - `allow_unused_idents`
+///
+///     Do not warn about the identifiers in `idents` which are not found in the code.
+///
+/// - `validate`
+///
+///     After the convertation, the `sync` version is checked for the async code left in it: `async`
+/// closures, `async` blocks and `.await` in the arguments of unknown macros, `Future` and `Stream`
+/// bounds (only the `Future<Output = ...>` generic parameters are converted) and `#[async_trait]`
+/// attributes. Each of them is reported with a hint pointing to the original code, instead of the
+/// compiler errors pointing to the generated one. The items removed from the `sync` version by
+/// `only_if` or `remove_if` are not checked. By default the leftovers are warnings (reported the
+/// same way as the unused identifiers, see `idents`); with `validate` they are errors.
+///
+/// - `allow_async_leftovers`
+///
+///     Do not warn about the async code left in the `sync` version, e.g. if the `sync` version uses
+/// futures deliberately to run them on an executor.
+///
 /// - `keep_self`
 ///
 ///     Do not change name of item to which attribute `maybe` refers.
@@ -450,11 +967,291 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 /// 
 ///     Defines the name that will be assigned to the item in this variant.
 /// 
+/// - `streams`
+///
+///     For the `sync` version, converts streams into iterators: `Stream<Item = XXX>` bounds become
+/// `::core::iter::Iterator<Item = XXX>`, `stream::iter(...)`, `stream::once(...)`, `stream::empty()` and
+/// `stream::repeat(...)` become their `core::iter` counterparts, and `pin_mut!(s)`/`tokio::pin!(s)`
+/// become `let mut s = s;`. Since `.await` is removed anyway, `s.next().await` turns into the
+/// `Iterator::next` call. The `Stream` traits of `futures`, `futures_core` and `tokio_stream` are
+/// converted if written with the path or imported by the item itself, so a trait of your own named
+/// `Stream` is left intact.
+///
+///     The bodies of `async_stream::stream!` and `async_stream::try_stream!` are executed eagerly:
+/// every `yield x` pushes `x` into a `Vec`, and the iterator over this `Vec` is returned. For
+/// `try_stream!`, the first error returned by `?` stops the execution and becomes the last item.
+///
+///     Note that the whole stream is collected before the first item is returned, so infinite
+/// streams (or streams waiting for the consumer) never return in the sync version. Also `return`
+/// can not end the iteration the way it does in the async version, so `return` in the body of
+/// `stream!`/`try_stream!` is reported as an error.
+///
+///     ```rust
+///     #[maybe_async_cfg::maybe(
+///         streams,
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///     )]
+///     fn numbers(n: u32) -> impl futures::Stream<Item = u32> {
+///         async_stream::stream! {
+///             for i in 0..n {
+///                 yield i;
+///             }
+///         }
+///     }
+///     ```
+///     After convertation:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn numbers_sync(n: u32) -> impl ::core::iter::Iterator<Item = u32> {
+///         let mut items = Vec::new();
+///         for i in 0..n {
+///             items.push(i);
+///         }
+///         items.into_iter()
+///     }
+///     #[cfg(feature="use_async")]
+///     fn numbers_async(n: u32) -> impl futures::Stream<Item = u32> {
+///         async_stream::stream! {
+///             for i in 0..n {
+///                 yield i;
+///             }
+///         }
+///     }
+///     ```
+///
+/// - `io`
+///
+///     For the `sync` version, converts the async I/O traits into the `std::io` ones: `AsyncRead`
+/// and `AsyncReadExt` become `Read`, `AsyncWrite` and `AsyncWriteExt` become `Write`, and the same
+/// for `AsyncBufRead` and `AsyncSeek`. The `tokio::io`, `futures::io` (`futures`), `futures_util::io`
+/// and `futures_io` paths are converted into the `std::io` ones, several imports of the same trait
+/// are merged, and the `Unpin` bounds next to these traits are removed. Only the traits imported
+/// from these paths by the item itself (or written with the full path) are converted, so a trait
+/// of your own named `AsyncRead` is left intact. Since `.await` is removed anyway,
+/// `r.read_exact(&mut buf).await` turns into the `Read::read_exact` call.
+///
+///     ```rust
+///     #[maybe_async_cfg::maybe(io, sync(feature="use_sync"), async(feature="use_async"))]
+///     mod codec {
+///         use tokio::io::{AsyncRead, AsyncReadExt};
+///
+///         pub async fn decode<R: AsyncRead + Unpin>(r: &mut R) -> std::io::Result<u32> {
+///             let mut buf = [0u8; 4];
+///             r.read_exact(&mut buf).await?;
+///             Ok(u32::from_be_bytes(buf))
+///         }
+///     }
+///     ```
+///     After convertation:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     mod codec_sync {
+///         use std::io::Read;
+///
+///         pub fn decode<R: Read>(r: &mut R) -> std::io::Result<u32> {
+///             let mut buf = [0u8; 4];
+///             r.read_exact(&mut buf)?;
+///             Ok(u32::from_be_bytes(buf))
+///         }
+///     }
+///     #[cfg(feature="use_async")]
+///     mod codec_async {
+///         use tokio::io::{AsyncRead, AsyncReadExt};
+///
+///         pub async fn decode<R: AsyncRead + Unpin>(r: &mut R) -> std::io::Result<u32> {
+///             let mut buf = [0u8; 4];
+///             r.read_exact(&mut buf).await?;
+///             Ok(u32::from_be_bytes(buf))
+///         }
+///     }
+///     ```
+///
+/// - `convert_doctests`
+///
+///     Convert the code of the doctests in the documentation of the item the same way as the item
+/// itself (see the "Doctests" section of the crate documentation).
+///
+/// - `paths`
+///
+///     Defines a list of paths that should be replaced in this version of the code. Unlike `idents`,
+/// it replaces whole paths (or their leading parts): with `paths(tokio::time = "std::thread")`, the
+/// path `tokio::time::sleep` becomes `std::thread::sleep`. If several paths match, the longest one
+/// wins. Use declarations are converted too, and the imported name is kept via `as` if necessary.
+/// The paths specified for the whole item apply to the `sync` and `blocking` versions only, the
+/// `async` versions are converted by their own `paths`.
+///
+///     ```rust
+///     #[maybe_async_cfg::maybe(
+///         sync(feature="use_sync", paths(crate::client::async_impl = "crate::client::blocking")),
+///         async(feature="use_async"),
+///     )]
+///     async fn fetch() -> u32 {
+///         use crate::client::async_impl::Client;
+///         Client::new().fetch().await
+///     }
+///     ```
+///     After convertation:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn fetch_sync() -> u32 {
+///         use crate::client::blocking::Client;
+///         Client::new().fetch()
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn fetch_async() -> u32 {
+///         use crate::client::async_impl::Client;
+///         Client::new().fetch().await
+///     }
+///     ```
+///
+/// - `runtime`
+///
+///     Specifies the async runtime used by the code: `runtime = "tokio"` or
+/// `runtime = "async-std"`. For the `sync` version, the common fs, io, net, time and sync APIs of
+/// this runtime will be replaced with their `std` counterparts (as if they were specified in
+/// `paths`), e.g. `tokio::fs::read` becomes `std::fs::read`, `tokio::time::sleep` becomes
+/// `std::thread::sleep`, `tokio::io::AsyncReadExt` becomes `std::io::Read` and so on. Paths
+/// specified in `paths` take precedence over the runtime ones.
+///
+///     ```rust
+///     #[maybe_async_cfg::maybe(
+///         runtime = "tokio",
+///         unwrap_locks,
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///     )]
+///     async fn inc(counter: &tokio::sync::Mutex<u32>) {
+///         tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+///         *counter.lock().await += 1;
+///     }
+///     ```
+///     After convertation:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn inc_sync(counter: &std::sync::Mutex<u32>) {
+///         std::thread::sleep(std::time::Duration::from_millis(10));
+///         *counter.lock().unwrap() += 1;
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn inc_async(counter: &tokio::sync::Mutex<u32>) {
+///         tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+///         *counter.lock().await += 1;
+///     }
+///     ```
+///
+/// - `unwrap_locks`
+///
+///     For the `sync` version, `mutex.lock().await` (as well as `rwlock.read().await` and
+/// `rwlock.write().await`) becomes `mutex.lock().unwrap()`, since the locks of `std::sync` return a
+/// `Result`. The type of the receiver is not known to the macro, so every call of `lock()`, `read()`
+/// or `write()` without arguments followed by `.await` is converted; use this parameter only if
+/// these methods of other types are not awaited in the item. See `runtime` for an example.
+///
+/// - `macros`
+///
+///     The arguments of macros are usually not valid Rust code, so they are left untouched by
+/// default. Only the arguments of standard macros (`assert`, `assert_eq`, `assert_ne`,
+/// `debug_assert`, `debug_assert_eq`, `debug_assert_ne`, `dbg`, `eprint`, `eprintln`, `format`,
+/// `format_args`, `matches`, `panic`, `print`, `println`, `todo`, `unimplemented`, `unreachable`,
+/// `vec`, `write` and `writeln`) are converted. `macros` registers additional macros whose
+/// arguments should be converted as well. Path-qualified macros must be specified exactly as they
+/// are called: `macros(tracing::debug)` matches `tracing::debug!(...)`, but not `debug!(...)`.
+///
+///     Arguments are separated by `,` or `;`. Arguments that can not be parsed as expressions
+/// (like `target: "app"` or patterns in `matches!`) are kept as is.
+///
+///     ```rust
+///     #[maybe_async_cfg::maybe(
+///         macros(tracing::info, anyhow::ensure),
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///     )]
+///     async fn run(client: &Client) -> anyhow::Result<()> {
+///         tracing::info!(target: "app", "status: {}", client.status().await);
+///         anyhow::ensure!(client.ready().await, "not ready");
+///         Ok(())
+///     }
+///     ```
+///     After convertation:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn run_sync(client: &Client) -> anyhow::Result<()> {
+///         tracing::info!(target: "app", "status: {}", client.status());
+///         anyhow::ensure!(client.ready(), "not ready");
+///         Ok(())
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn run_async(client: &Client) -> anyhow::Result<()> {
+///         tracing::info!(target: "app", "status: {}", client.status().await);
+///         anyhow::ensure!(client.ready().await, "not ready");
+///         Ok(())
+///     }
+///     ```
+///
 /// - `send`
 ///
 ///     If `send = "Send"` or `send = "true"` is present, the attribute
 /// `#[async_trait::async_trait]` will be added before the async code. If `send = "?Send"` or
 /// `send = "false"` then `#[async_trait::async_trait(?Send)]` will be added.  
+///
+///     Without `trait_backend` the attribute is added to impls only, so the trait itself must be
+/// annotated manually. An attribute already present on the item is not duplicated.
+///
+/// - `trait_backend`
+///
+///     Selects how async traits and their impls are emitted in the async version:
+///
+///     - `trait_backend = "async_trait"`: `#[async_trait::async_trait]` (or
+/// `#[async_trait::async_trait(?Send)]` for `send = "?Send"`) is added to both the trait and
+/// its impls.
+///
+///     - `trait_backend = "native"`: `async fn` in traits and impls is kept as is. With
+/// `send = "Send"` the methods of the trait are desugared into
+/// `fn ...(...) -> impl Future<Output = T> + Send`, so the futures returned by any implementation
+/// are `Send`; default bodies are wrapped in `async move { ... }`.
+///
+///     - `trait_backend = "trait_variant"`: `#[trait_variant::make(Send)]` is added to the trait
+/// (unless `send = "?Send"`), impls are kept as is.
+///
+///     Like `send`, it may be specified for all versions at once or for each version separately.
+///
+///     ```rust, no_run
+///     #[maybe_async_cfg::maybe(
+///         trait_backend = "native",
+///         sync(feature="use_sync"),
+///         async(feature="use_async", send = "Send")
+///     )]
+///     trait Storage {
+///         async fn load(&self, key: u32) -> Option<String>;
+///
+///         async fn exists(&self, key: u32) -> bool {
+///             self.load(key).await.is_some()
+///         }
+///     }
+///     ```
+///     After convertation:
+///     ```rust, no_run
+///     #[cfg(feature="use_sync")]
+///     trait StorageSync {
+///         fn load(&self, key: u32) -> Option<String>;
+///
+///         fn exists(&self, key: u32) -> bool {
+///             self.load(key).is_some()
+///         }
+///     }
+///     #[cfg(feature="use_async")]
+///     trait StorageAsync {
+///         fn load(&self, key: u32)
+///             -> impl ::core::future::Future<Output = Option<String>> + ::core::marker::Send;
+///
+///         fn exists(&self, key: u32)
+///             -> impl ::core::future::Future<Output = bool> + ::core::marker::Send
+///         {
+///             async move { self.load(key).await.is_some() }
+///         }
+///     }
+///     ```
 /// 
 /// - `drop_attrs`
 ///
@@ -492,6 +1289,10 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 ///
 ///     Replace one feature name with another.
 ///
+///     The feature names are replaced in the predicates of `#[cfg(...)]`, `#[cfg_attr(...)]` (the
+/// nested ones too), `#[doc(cfg(...))]` and `cfg!(...)`. The other arguments of `cfg_attr` are
+/// left as they are.
+///
 ///     ```rust
 ///     #[maybe_async_cfg::maybe(
 ///         sync(feature="use_sync", replace_feature("secure", "secure_sync")),
@@ -502,6 +1303,8 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 ///         // In sync version "secure" feature will be replaced with "secure_sync" feature
 ///         #[cfg(feature="secure")]
 ///         field: bool,
+///         #[cfg_attr(feature="secure", allow(unused))]
+///         other: bool,
 ///     }
 ///     ```
 ///     After convertation:
@@ -511,12 +1314,16 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 ///         f: usize,
 ///         #[cfg(feature="secure_sync")]
 ///         field: bool,
+///         #[cfg_attr(feature="secure_sync", allow(unused))]
+///         other: bool,
 ///     }
 ///     #[cfg(feature="use_async")]
 ///     struct StructAsync {
 ///         f: usize,
 ///         #[cfg(feature="secure")]
 ///         field: bool,
+///         #[cfg_attr(feature="secure", allow(unused))]
+///         other: bool,
 ///     }
 ///     ```
 ///
@@ -558,8 +1365,9 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 /// 
 /// - In other cases, the following rules apply:
 ///     
-///     - name-value pairs (`xxx = "yyy"`) with a name other than `key`, `prefix`, `send` and
-/// `feature` will produce an error.
+///     - name-value pairs (`xxx = "yyy"`) with a name other than `key`, `suffix`, `prefix`,
+/// `profile`, `send`, `trait_backend`, `runtime`, `executor`, `block_on`, `target` and `feature`
+/// will produce an error.
 ///     
 ///     - `feature = "..."`, `not(...)`, `all(...)`, `any(...)` will be interpreted as condition for
 /// current version (as wrapped in `cfg(...)`).
@@ -575,9 +1383,22 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 /// > _Parameter_ :\
 /// > &nbsp;&nbsp;&nbsp;&nbsp;`disable`\
 /// > &nbsp;&nbsp;|&nbsp;`keep_self`\
+/// > &nbsp;&nbsp;|&nbsp;`streams`\
+/// > &nbsp;&nbsp;|&nbsp;`io`\
+/// > &nbsp;&nbsp;|&nbsp;`unwrap_locks`\
+/// > &nbsp;&nbsp;|&nbsp;`convert_doctests`\
+/// > &nbsp;&nbsp;|&nbsp;`allow_unused_idents`\
+/// > &nbsp;&nbsp;|&nbsp;`validate`\
+/// > &nbsp;&nbsp;|&nbsp;`allow_async_leftovers`\
 /// > &nbsp;&nbsp;|&nbsp;`prefix` `=` _STRING_LITERAL_\
-/// > &nbsp;&nbsp;|&nbsp;(`sync` | `async`) `(` _VersionParametersList_ `)`\
+/// > &nbsp;&nbsp;|&nbsp;`profile` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`send` `=` (`""` | `"Send"` | `"true"` | `"?Send"` | `"false"`)\
+/// > &nbsp;&nbsp;|&nbsp;`trait_backend` `=` (`"async_trait"` | `"native"` | `"trait_variant"`)\
+/// > &nbsp;&nbsp;|&nbsp;`runtime` `=` (`"tokio"` | `"async-std"`)\
+/// > &nbsp;&nbsp;|&nbsp;(`sync` | `async` | `blocking`) `(` _VersionParametersList_ `)`\
 /// > &nbsp;&nbsp;|&nbsp;`idents` `(` _IdentsList_ `)`\
+/// > &nbsp;&nbsp;|&nbsp;`paths` `(` _PathsList_ `)`\
+/// > &nbsp;&nbsp;|&nbsp;`macros` `(` _MacrosList_ `)`\
 /// >
 /// > _VersionParametersList_ :\
 /// > &nbsp;&nbsp;&nbsp;&nbsp;_VersionParameter_ (`,` _VersionParameter_)<sup>\*</sup>
@@ -585,12 +1406,27 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 /// > _VersionParameter_ :\
 /// > &nbsp;&nbsp;&nbsp;&nbsp;`disable`\
 /// > &nbsp;&nbsp;|&nbsp;`keep_self`\
+/// > &nbsp;&nbsp;|&nbsp;`streams`\
+/// > &nbsp;&nbsp;|&nbsp;`io`\
+/// > &nbsp;&nbsp;|&nbsp;`unwrap_locks`\
+/// > &nbsp;&nbsp;|&nbsp;`convert_doctests`\
+/// > &nbsp;&nbsp;|&nbsp;`allow_unused_idents`\
+/// > &nbsp;&nbsp;|&nbsp;`validate`\
+/// > &nbsp;&nbsp;|&nbsp;`allow_async_leftovers`\
 /// > &nbsp;&nbsp;|&nbsp;`key` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`suffix` `=` _STRING_LITERAL_\
 /// > &nbsp;&nbsp;|&nbsp;`feature` `=` _STRING_LITERAL_\
 /// > &nbsp;&nbsp;|&nbsp;`self` `=` _STRING_LITERAL_\
 /// > &nbsp;&nbsp;|&nbsp;`send` `=` (`""` | `"Send"` | `"true"` | `"?Send"` | `"false"`)\
+/// > &nbsp;&nbsp;|&nbsp;`trait_backend` `=` (`"async_trait"` | `"native"` | `"trait_variant"`)\
+/// > &nbsp;&nbsp;|&nbsp;`runtime` `=` (`"tokio"` | `"async-std"`)\
+/// > &nbsp;&nbsp;|&nbsp;`executor` `=` (`"futures"` | `"tokio"` | `"async-std"`)\
+/// > &nbsp;&nbsp;|&nbsp;`block_on` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`target` `=` _STRING_LITERAL_\
 /// > &nbsp;&nbsp;|&nbsp;(`cfg` | `any` | `all` | `not`) `(` _ANY_CFG_CONDITION_ `)`\
 /// > &nbsp;&nbsp;|&nbsp;`idents` `(` _IdentsList_ `)`\
+/// > &nbsp;&nbsp;|&nbsp;`paths` `(` _PathsList_ `)`\
+/// > &nbsp;&nbsp;|&nbsp;`macros` `(` _MacrosList_ `)`\
 /// > &nbsp;&nbsp;|&nbsp;(`outer` | `inner`) `(` _AttributesList_ `)`\
 /// > &nbsp;&nbsp;|&nbsp;`replace_feature` `(` _STRING_LITERAL_ `,` _STRING_LITERAL_ `)`\
 /// > &nbsp;&nbsp;|&nbsp;`drop_attrs` `(` _IdentifiersList_ `)`\
@@ -599,11 +1435,27 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 /// > _Path_ :\
 /// > &nbsp;&nbsp;&nbsp;&nbsp;_IDENTIFIER_ (`::` _IDENTIFIER_)<sup>\+</sup>
 /// >
+/// > _PathsList_ :\
+/// > &nbsp;&nbsp;&nbsp;&nbsp;_Path_ `=` _STRING_LITERAL_ (`,` _Path_ `=` _STRING_LITERAL_)<sup>\*</sup>
+/// >
+/// > _MacrosList_ :\
+/// > &nbsp;&nbsp;&nbsp;&nbsp;_MacroPath_ (`,` _MacroPath_)<sup>\*</sup>
+/// >
+/// > _MacroPath_ :\
+/// > &nbsp;&nbsp;&nbsp;&nbsp;_IDENTIFIER_ (`::` _IDENTIFIER_)<sup>\*</sup>
+/// >
 /// > _IdentifiersList_ :\
 /// > &nbsp;&nbsp;&nbsp;&nbsp;_IDENTIFIER_ (`,` _IDENTIFIER_)<sup>\*</sup>
 /// >
 /// > _IdentsList_ :\
-/// > &nbsp;&nbsp;&nbsp;&nbsp;_Ident_ (`,` _Ident_)<sup>\*</sup>
+/// > &nbsp;&nbsp;&nbsp;&nbsp;_Ident_ (`,` _Ident_)<sup>\*</sup>\
+/// > &nbsp;&nbsp;|&nbsp;_IdentPattern_ (`,` (_Ident_ | _IdentPatternValue_))<sup>\*</sup>
+/// >
+/// > _IdentPattern_ :\
+/// > &nbsp;&nbsp;&nbsp;&nbsp;(`regex` | `prefix` | `suffix`) `=` _STRING_LITERAL_
+/// >
+/// > _IdentPatternValue_ :\
+/// > &nbsp;&nbsp;&nbsp;&nbsp;(`sync` | `async` | _IDENTIFIER_) `=` _STRING_LITERAL_
 /// >
 /// > _Ident_ :\
 /// > &nbsp;&nbsp;&nbsp;&nbsp;_IDENTIFIER_ (`(` _IdentParametersList_ `)`)<sup>\?</sup>
@@ -613,8 +1465,10 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 /// >
 /// > _IdentParameter_ :\
 /// > &nbsp;&nbsp;&nbsp;&nbsp;`keep`\
+/// > &nbsp;&nbsp;|&nbsp;`allow_unused`\
 /// > &nbsp;&nbsp;|&nbsp;`use`\
 /// > &nbsp;&nbsp;|&nbsp;(`snake` | `fn` | `mod` )\
+/// > &nbsp;&nbsp;|&nbsp;(`upper` | `const` | `static` )\
 /// > &nbsp;&nbsp;|&nbsp;`use`\
 /// > &nbsp;&nbsp;|&nbsp;(`sync` | `async` | _IDENTIFIER_) (`=` _STRING_LITERAL_)<sup>\?</sup>
 /// >
@@ -627,7 +1481,10 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn maybe(args: TokenStream, input: TokenStream) -> TokenStream {
-    macros::maybe(args, input)
+    match maybe_async_cfg_core::maybe(args.into(), input.into()) {
+        Ok(ts) => ts.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
 }
 
 /// Marks conditional content that should only be used in the specified version of the code.
@@ -648,15 +1505,72 @@ pub fn remove_if(_: TokenStream, body: TokenStream) -> TokenStream {
 /// Does nothing (leaves content intact).
 #[proc_macro_error]
 #[proc_macro_attribute]
-pub fn noop(_: TokenStream, body: TokenStream) -> TokenStream {
-    body
+pub fn noop(args: TokenStream, body: TokenStream) -> TokenStream {
+    maybe_async_cfg_core::noop(args.into(), body.into()).into()
 }
 
 /// Removes marked content.
 #[proc_macro_error]
 #[proc_macro_attribute]
-pub fn remove(_: TokenStream, _: TokenStream) -> TokenStream {
-    TokenStream::new()
+pub fn remove(args: TokenStream, body: TokenStream) -> TokenStream {
+    maybe_async_cfg_core::remove(args.into(), body.into()).into()
+}
+
+/// Marks code (an expression or statements) that should only be used in the sync versions.
+///
+/// The macro is processed by the enclosing `maybe` macro and can not be used outside of it.
+#[proc_macro_error]
+#[proc_macro]
+pub fn sync_impl(_: TokenStream) -> TokenStream {
+    abort_call_site!("`sync_impl!` can only be used inside items marked with `maybe`")
+}
+
+/// Marks code (an expression or statements) that should only be used in the async versions.
+///
+/// The macro is processed by the enclosing `maybe` macro and can not be used outside of it.
+#[proc_macro_error]
+#[proc_macro]
+pub fn async_impl(_: TokenStream) -> TokenStream {
+    abort_call_site!("`async_impl!` can only be used inside items marked with `maybe`")
+}
+
+/// Marks code (an expression or statements) that should only be used in the versions with the
+/// specified keys: `version_impl!(key = "tokio", { ... })`.
+///
+/// The macro is processed by the enclosing `maybe` macro and can not be used outside of it.
+#[proc_macro_error]
+#[proc_macro]
+pub fn version_impl(_: TokenStream) -> TokenStream {
+    abort_call_site!("`version_impl!` can only be used inside items marked with `maybe`")
+}
+
+/// Defines the tokio runtime shared by the blocking versions with `executor = "tokio"`.
+///
+/// Invoke it once at the crate root:
+///
+/// ```rust
+/// maybe_async_cfg::blocking_runtime!();
+///
+/// #[maybe_async_cfg::maybe(
+///     idents(answer(fn)),
+///     async(),
+///     blocking(executor = "tokio"),
+/// )]
+/// async fn answer() -> u32 {
+///     42
+/// }
+///
+/// fn main() {
+///     assert_eq!(answer_blocking(), 42);
+/// }
+/// ```
+#[proc_macro_error]
+#[proc_macro]
+pub fn blocking_runtime(input: TokenStream) -> TokenStream {
+    if !input.is_empty() {
+        abort_call_site!("`blocking_runtime!` takes no arguments");
+    }
+    maybe_async_cfg_core::blocking_runtime().into()
 }
 
 /// A wrapper for code with common `maybe` parameters
@@ -705,5 +1619,8 @@ pub fn remove(_: TokenStream, _: TokenStream) -> TokenStream {
 #[proc_macro_error]
 #[proc_macro]
 pub fn content(body: TokenStream) -> TokenStream {
-    macros::content(body)
+    match maybe_async_cfg_core::content(body.into()) {
+        Ok(ts) => ts.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
 }
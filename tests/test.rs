@@ -1,5 +1,11 @@
 #[test]
 fn ui() {
+    // profiles for tests/ui/19-profiles.rs
+    std::env::set_var(
+        "MAYBE_ASYNC_CFG_CONFIG",
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/maybe-async-cfg.toml"),
+    );
+
     let t = trybuild::TestCases::new();
     t.pass("tests/ui/01-maybe-async.rs");
     t.pass("tests/ui/02-must-be-async.rs");
@@ -7,9 +13,40 @@ fn ui() {
     t.pass("tests/ui/04-unit-test-util.rs");
     t.pass("tests/ui/05-replace-future-generic-type-with-output.rs");
     t.pass("tests/ui/06-sync_impl_async_impl.rs");
+    t.pass("tests/ui/07-impl-future-return.rs");
+    t.pass("tests/ui/08-boxed-future.rs");
+    t.pass("tests/ui/09-streams.rs");
+    t.pass("tests/ui/10-paths.rs");
+    t.pass("tests/ui/11-more-items.rs");
+    t.pass("tests/ui/12-named-versions.rs");
+    t.pass("tests/ui/13-blocking.rs");
+    t.pass("tests/ui/14-trait-backend.rs");
+    t.pass("tests/ui/15-macros.rs");
+    t.pass("tests/ui/16-combinators.rs");
+    t.pass("tests/ui/17-version-impl.rs");
+    t.pass("tests/ui/18-ident-patterns.rs");
+    t.pass("tests/ui/19-profiles.rs");
+    t.pass("tests/ui/20-unused-idents.rs");
+    t.pass("tests/ui/21-sync-keeps-future.rs");
+    t.pass("tests/ui/22-future-generics.rs");
+    t.pass("tests/ui/23-future-output-types.rs");
+    t.pass("tests/ui/24-async-closures.rs");
+    t.pass("tests/ui/25-io.rs");
+    t.pass("tests/ui/26-convert-doctests.rs");
+    t.pass("tests/ui/27-doctest-conditions.rs");
+    t.pass("tests/ui/28-doc-prose.rs");
+    t.pass("tests/ui/29-doc-links.rs");
+    t.pass("tests/ui/30-replace-features-cfg.rs");
+    t.pass("tests/ui/31-extern-versions.rs");
+    t.pass("tests/ui/32-allow-unused-idents.rs");
 
     t.compile_fail("tests/ui/test_fail/01-empty-test.rs");
     t.compile_fail("tests/ui/test_fail/02-unknown-path.rs");
     t.compile_fail("tests/ui/test_fail/03-async-gt2.rs");
     t.compile_fail("tests/ui/test_fail/04-bad-sync-cond.rs");
+    t.compile_fail("tests/ui/test_fail/05-select-without-sync-arm.rs");
+    t.compile_fail("tests/ui/test_fail/06-unused-idents.rs");
+    t.compile_fail("tests/ui/test_fail/07-async-leftovers.rs");
+    t.compile_fail("tests/ui/test_fail/08-future-bound-on-type.rs");
+    t.compile_fail("tests/ui/test_fail/09-stream-return.rs");
 }
@@ -0,0 +1,38 @@
+#![allow(dead_code)]
+use std::future::Future;
+
+struct Body(Vec<u8>);
+
+#[derive(Debug)]
+struct Error;
+
+#[maybe_async_cfg::maybe(sync(), async())]
+struct Client;
+
+#[maybe_async_cfg::maybe(idents(Client), sync(), async())]
+impl Client {
+    fn fetch(&self) -> impl Future<Output = Result<Body, Error>> + Send + '_ {
+        async move { Ok(Body(vec![1, 2, 3])) }
+    }
+
+    fn fetch_len<'a>(&'a self) -> impl Future<Output = usize> + 'a {
+        async move {
+            let body = self.fetch().await.unwrap();
+            body.0.len()
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(idents(Client), sync(), async())]
+fn len_of(client: &Client) -> impl Future<Output = usize> + '_ {
+    async move { client.fetch_len().await }
+}
+
+#[tokio::main]
+async fn main() {
+    let client = ClientSync;
+    assert_eq!(len_of_sync(&client), 3);
+
+    let client = ClientAsync;
+    assert_eq!(len_of_async(&client).await, 3);
+}
@@ -0,0 +1,50 @@
+#![allow(dead_code)]
+use std::{future::Future, pin::Pin};
+
+use futures::future::{BoxFuture, FutureExt, LocalBoxFuture};
+
+#[maybe_async_cfg::maybe(idents(fib(fn)), sync(), async())]
+fn fib(n: u64) -> Pin<Box<dyn Future<Output = u64> + Send>> {
+    Box::pin(async move {
+        if n < 2 {
+            n
+        } else {
+            fib(n - 1).await + fib(n - 2).await
+        }
+    })
+}
+
+#[maybe_async_cfg::maybe(idents(fact(fn)), sync(), async())]
+fn fact<'a>(n: &'a u64) -> BoxFuture<'a, u64> {
+    async move {
+        if *n < 2 {
+            1
+        } else {
+            n * fact(&(n - 1)).await
+        }
+    }
+    .boxed()
+}
+
+#[maybe_async_cfg::maybe(sync(), async())]
+trait Source {
+    fn value(&self) -> LocalBoxFuture<'_, u64>;
+}
+
+#[maybe_async_cfg::maybe(keep_self, idents(Source), sync(), async())]
+impl Source for u64 {
+    fn value(&self) -> LocalBoxFuture<'_, u64> {
+        async move { *self }.boxed_local()
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(fib_sync(10), 55);
+    assert_eq!(fact_sync(&5), 120);
+    assert_eq!(SourceSync::value(&7u64), 7);
+
+    assert_eq!(fib_async(10).await, 55);
+    assert_eq!(fact_async(&5).await, 120);
+    assert_eq!(SourceAsync::value(&7u64).await, 7);
+}
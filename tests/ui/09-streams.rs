@@ -0,0 +1,93 @@
+#![allow(dead_code)]
+use futures::{pin_mut, stream, StreamExt};
+
+#[maybe_async_cfg::maybe(idents(numbers(fn)), streams, sync(), async())]
+fn numbers(n: u32) -> impl futures::Stream<Item = u32> {
+    async_stream::stream! {
+        for i in 0..n {
+            yield i;
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(idents(parse_all(fn)), streams, sync(), async())]
+fn parse_all(items: Vec<&'static str>) -> impl futures::Stream<Item = Result<u32, std::num::ParseIntError>> {
+    async_stream::try_stream! {
+        for item in items {
+            let value: u32 = item.parse()?;
+            yield value;
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(idents(sum(fn)), streams, sync(), async())]
+async fn sum<S: futures::stream::Stream<Item = u32>>(s: S) -> u32 {
+    pin_mut!(s);
+    let mut total = 0;
+    while let Some(x) = s.next().await {
+        total += x;
+    }
+    total
+}
+
+#[maybe_async_cfg::maybe(idents(numbers(fn), sum(fn)), streams, sync(), async())]
+async fn total() -> u32 {
+    sum(numbers(5)).await + sum(stream::iter(vec![10, 20])).await
+}
+
+#[maybe_async_cfg::maybe(idents(parse_all(fn)), streams, sync(), async())]
+async fn parsed() -> Vec<Result<u32, std::num::ParseIntError>> {
+    let s = parse_all(vec!["1", "2", "x", "3"]);
+    pin_mut!(s);
+    let mut res = vec![];
+    while let Some(x) = s.next().await {
+        res.push(x);
+    }
+    res
+}
+
+// the bare name is converted if the item imports it
+#[maybe_async_cfg::maybe(streams, sync(), async())]
+mod evens {
+    use futures::Stream;
+
+    pub fn all(n: u32) -> impl Stream<Item = u32> {
+        futures::stream::iter((0..n).map(|i| i * 2))
+    }
+}
+
+// a trait of your own named `Stream` is left intact
+pub trait Stream {
+    fn size(&self) -> usize;
+}
+
+impl Stream for Vec<u32> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+}
+
+#[maybe_async_cfg::maybe(idents(size_of(fn)), streams, sync(), async())]
+async fn size_of<S: Stream>(s: S) -> usize {
+    s.size()
+}
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(total_sync(), 40);
+    assert_eq!(total_async().await, 40);
+
+    let sync = parsed_sync();
+    assert_eq!(sync.len(), 3);
+    assert!(sync[2].is_err());
+
+    let not_sync = parsed_async().await;
+    assert_eq!(not_sync.len(), 3);
+    assert!(not_sync[2].is_err());
+
+    assert_eq!(evens_sync::all(3).collect::<Vec<_>>(), vec![0, 2, 4]);
+    assert_eq!(evens_async::all(3).collect::<Vec<_>>().await, vec![0, 2, 4]);
+
+    assert_eq!(size_of_sync(vec![1, 2]), 2);
+    assert_eq!(size_of_async(vec![1, 2]).await, 2);
+}
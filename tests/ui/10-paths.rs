@@ -0,0 +1,99 @@
+#![allow(dead_code)]
+
+#[maybe_async_cfg::maybe(idents(write_file(fn)), runtime = "tokio", sync(), async())]
+async fn write_file(path: &std::path::Path, data: &[u8]) {
+    use tokio::io::AsyncWriteExt;
+    let mut file = tokio::fs::File::create(path).await.unwrap();
+    file.write_all(data).await.unwrap();
+}
+
+#[maybe_async_cfg::maybe(idents(read_len(fn)), runtime = "tokio", sync(), async())]
+async fn read_len(path: &std::path::Path) -> usize {
+    tokio::fs::read(path).await.unwrap().len()
+}
+
+#[maybe_async_cfg::maybe(idents(pause(fn)), runtime = "tokio", sync(), async())]
+async fn pause() {
+    use tokio::time::{sleep, Duration};
+    sleep(Duration::from_millis(1)).await;
+}
+
+#[maybe_async_cfg::maybe(idents(Counter), runtime = "tokio", sync(), async())]
+struct Counter {
+    value: tokio::sync::Mutex<u32>,
+}
+
+#[maybe_async_cfg::maybe(idents(Counter), runtime = "tokio", unwrap_locks, sync(), async())]
+impl Counter {
+    fn new() -> Self {
+        Self {
+            value: tokio::sync::Mutex::new(0),
+        }
+    }
+
+    async fn inc(&self) -> u32 {
+        let mut value = self.value.lock().await;
+        *value += 1;
+        *value
+    }
+}
+
+mod blocking_api {
+    pub fn fetch() -> u32 {
+        1
+    }
+}
+
+mod async_api {
+    pub async fn fetch() -> u32 {
+        2
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    idents(fetch_twice(fn)),
+    sync(paths(crate::async_api = "crate::blocking_api")),
+    async()
+)]
+async fn fetch_twice() -> u32 {
+    crate::async_api::fetch().await + crate::async_api::fetch().await
+}
+
+// the paths of the item leave the async version intact
+#[maybe_async_cfg::maybe(
+    idents(fetch_once(fn)),
+    paths(crate::async_api = "crate::blocking_api"),
+    sync(),
+    async()
+)]
+async fn fetch_once() -> u32 {
+    crate::async_api::fetch().await
+}
+
+#[tokio::main]
+async fn main() {
+    let path = std::env::temp_dir().join("maybe-async-cfg-10-paths.txt");
+
+    write_file_sync(&path, b"hello");
+    assert_eq!(read_len_sync(&path), 5);
+    write_file_async(&path, b"hello, world").await;
+    assert_eq!(read_len_async(&path).await, 12);
+    let _ = std::fs::remove_file(&path);
+
+    pause_sync();
+    pause_async().await;
+
+    let counter = CounterSync::new();
+    counter.inc();
+    assert_eq!(counter.inc(), 2);
+
+    let counter = CounterAsync::new();
+    counter.inc().await;
+    assert_eq!(counter.inc().await, 2);
+
+    assert_eq!(fetch_twice_sync(), 2);
+    assert_eq!(fetch_twice_async().await, 4);
+
+    assert_eq!(fetch_once_sync(), 1);
+    assert_eq!(fetch_once_async().await, 2);
+}
@@ -0,0 +1,67 @@
+#![allow(dead_code)]
+
+struct ClientSync;
+struct ClientAsync;
+
+impl ClientSync {
+    fn get(&self) -> u32 {
+        1
+    }
+}
+
+impl ClientAsync {
+    async fn get(&self) -> u32 {
+        2
+    }
+}
+
+#[maybe_async_cfg::maybe(idents(Client), sync(), async())]
+type Handle = Client;
+
+#[maybe_async_cfg::maybe(idents(Client), sync(), async())]
+const DEFAULT_CLIENT: Client = Client;
+
+#[maybe_async_cfg::maybe(idents(Client), sync(), async())]
+static GLOBAL_CLIENT: Client = Client;
+
+#[maybe_async_cfg::maybe(sync(), async())]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+#[maybe_async_cfg::maybe(idents(Client, Handle), sync(), async())]
+macro_rules! fetch {
+    ($client:expr) => {{
+        let client: &Handle = $client;
+        async move { client.get().await }.await
+    }};
+}
+
+#[maybe_async_cfg::maybe(sync())]
+extern "C" {
+    fn abs(x: i32) -> i32;
+}
+
+#[maybe_async_cfg::maybe(idents(fetch(fn), DEFAULT_CLIENT(upper), total(fn)), sync(), async())]
+async fn total() -> u32 {
+    let client = &DEFAULT_CLIENT;
+    fetch!(client) + 10
+}
+
+#[tokio::main]
+async fn main() {
+    let _: HandleSync = ClientSync;
+    let _: HandleAsync = ClientAsync;
+
+    assert_eq!(total_sync(), 11);
+    assert_eq!(total_async().await, 12);
+    assert_eq!(GLOBAL_CLIENT_SYNC.get(), 1);
+    assert_eq!(GLOBAL_CLIENT_ASYNC.get().await, 2);
+
+    let bits = BitsSync { int: 0 };
+    assert_eq!(unsafe { bits.float }, 0.0);
+    let _ = BitsAsync { int: 0 };
+
+    assert_eq!(unsafe { abs(-3) }, 3);
+}
@@ -0,0 +1,69 @@
+#![allow(dead_code)]
+
+#[maybe_async_cfg::maybe(
+    sync(suffix = "Blocking", runtime = "tokio"),
+    async(key = "tokio", suffix = "Tokio"),
+    async(key = "async_std", suffix = "AsyncStd")
+)]
+struct Client {
+    counter: tokio::sync::Mutex<u32>,
+}
+
+#[maybe_async_cfg::maybe(
+    idents(Client, MAX_VALUE(upper)),
+    sync(suffix = "Blocking", runtime = "tokio", unwrap_locks),
+    async(key = "tokio", suffix = "Tokio"),
+    async(key = "async_std", suffix = "AsyncStd")
+)]
+impl Client {
+    async fn inc(&self) -> u32 {
+        let mut counter = self.counter.lock().await;
+        *counter += 1;
+        (*counter).min(MAX_VALUE)
+    }
+
+    #[maybe_async_cfg::only_if(tokio, async_std)]
+    fn is_async(&self) -> bool {
+        true
+    }
+
+    #[maybe_async_cfg::remove_if(tokio, async_std)]
+    fn is_async(&self) -> bool {
+        false
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(suffix = "Blocking"),
+    async(key = "tokio", suffix = "Tokio"),
+    async(key = "async_std", suffix = "AsyncStd")
+)]
+const MAX_VALUE: u32 = 10;
+
+#[maybe_async_cfg::maybe(
+    idents(Client),
+    sync(suffix = "Blocking"),
+    async(key = "tokio", suffix = "Tokio"),
+    async(key = "async_std", suffix = "AsyncStd")
+)]
+async fn make_client() -> Client {
+    Client {
+        counter: Default::default(),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let client = make_client_blocking();
+    assert_eq!(client.inc(), 1);
+    assert_eq!(MAX_VALUE_BLOCKING, 10);
+    assert!(!client.is_async());
+
+    let client = make_client_tokio().await;
+    assert_eq!(client.inc().await, 1);
+    assert!(client.is_async());
+
+    let client = make_client_async_std().await;
+    assert_eq!(client.inc().await, 1);
+    assert!(client.is_async());
+}
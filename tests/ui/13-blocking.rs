@@ -0,0 +1,107 @@
+#![allow(dead_code)]
+
+maybe_async_cfg::blocking_runtime!();
+
+#[derive(Debug, PartialEq)]
+pub struct Error;
+
+#[maybe_async_cfg::maybe(
+    idents(Connection),
+    async(),
+    blocking(executor = "futures")
+)]
+pub struct Connection {
+    values: Vec<u32>,
+}
+
+#[maybe_async_cfg::maybe(
+    idents(Connection),
+    async(),
+    blocking(executor = "futures")
+)]
+impl Connection {
+    pub const LIMIT: usize = 3;
+
+    pub async fn open(values: Vec<u32>) -> Result<Self, Error> {
+        if values.len() > Self::LIMIT {
+            return Err(Error);
+        }
+        Ok(Connection { values })
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub async fn push(&mut self, value: u32) {
+        self.values.push(value);
+    }
+
+    pub async fn sum(&self, (from, to): (usize, usize)) -> u32 {
+        self.values[from..to].iter().sum()
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.values.extend(other.values);
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    idents(Connection, total(fn)),
+    async(),
+    blocking(block_on = "futures::executor::block_on")
+)]
+pub async fn total(conn: &Connection) -> u32 {
+    conn.sum((0, conn.len())).await
+}
+
+#[maybe_async_cfg::maybe(
+    idents(answer(fn)),
+    async(key = "tokio", suffix = "Tokio"),
+    blocking(executor = "tokio", target = "tokio")
+)]
+pub fn answer() -> impl std::future::Future<Output = u32> {
+    async { 42 }
+}
+
+#[maybe_async_cfg::maybe(
+    idents(spawn_send(fn)),
+    async(key = "tokio", suffix = "Tokio"),
+    blocking(executor = "tokio", target = "tokio")
+)]
+pub async fn spawn_send(tx: std::sync::mpsc::Sender<std::thread::ThreadId>) {
+    tokio::spawn(async move {
+        tokio::task::yield_now().await;
+        tx.send(std::thread::current().id()).unwrap();
+    });
+}
+
+#[maybe_async_cfg::maybe(
+    idents(worker_thread(fn)),
+    async(key = "tokio", suffix = "Tokio"),
+    blocking(executor = "tokio", target = "tokio")
+)]
+pub async fn worker_thread() -> std::thread::ThreadId {
+    tokio::spawn(async { std::thread::current().id() }).await.unwrap()
+}
+
+fn main() {
+    assert!(ConnectionBlocking::open(vec![1, 2, 3, 4]).is_err());
+
+    let mut conn = ConnectionBlocking::open(vec![1, 2]).unwrap();
+    conn.push(3);
+    conn.merge(ConnectionBlocking::from(ConnectionAsync { values: vec![4] }));
+    assert_eq!(conn.len(), 4);
+    assert_eq!(conn.sum((1, 3)), 5);
+    let other = ConnectionBlocking::open(vec![5, 6]).unwrap();
+    assert_eq!(total_blocking(&other), 11);
+    assert_eq!(ConnectionBlocking::LIMIT, 3);
+
+    assert_eq!(answer_blocking(), 42);
+
+    // the task outlives the call, and all the functions share the runtime (with a single worker)
+    let (tx, rx) = std::sync::mpsc::channel();
+    spawn_send_blocking(tx);
+    let worker = rx.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+    assert_eq!(worker_thread_blocking(), worker);
+}
@@ -0,0 +1,50 @@
+#![allow(dead_code)]
+
+#[maybe_async_cfg::maybe(
+    sync(),
+    async(key = "async_trait", suffix = "AsyncTrait", trait_backend = "async_trait"),
+    async(key = "native", suffix = "Native", trait_backend = "native"),
+    async(key = "native_send", suffix = "NativeSend", trait_backend = "native", send = "Send"),
+    async(key = "variant", suffix = "Variant", trait_backend = "trait_variant")
+)]
+trait Counter: Sync {
+    async fn get(&self) -> u32;
+
+    async fn get_twice(&self) -> u32 {
+        self.get().await * 2
+    }
+}
+
+struct Seven;
+
+#[maybe_async_cfg::maybe(
+    keep_self,
+    idents(Counter),
+    sync(),
+    async(key = "async_trait", suffix = "AsyncTrait", trait_backend = "async_trait"),
+    async(key = "native", suffix = "Native", trait_backend = "native"),
+    async(key = "native_send", suffix = "NativeSend", trait_backend = "native", send = "Send"),
+    async(key = "variant", suffix = "Variant", trait_backend = "trait_variant")
+)]
+impl Counter for Seven {
+    async fn get(&self) -> u32 {
+        7
+    }
+}
+
+fn spawn_native_send<T: CounterNativeSend + Send + 'static>(counter: T) -> tokio::task::JoinHandle<u32> {
+    tokio::spawn(async move { counter.get_twice().await })
+}
+
+fn spawn_variant<T: CounterVariant + Send + 'static>(counter: T) -> tokio::task::JoinHandle<u32> {
+    tokio::spawn(async move { counter.get_twice().await })
+}
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(CounterSync::get_twice(&Seven), 14);
+    assert_eq!(CounterAsyncTrait::get_twice(&Seven).await, 14);
+    assert_eq!(CounterNative::get_twice(&Seven).await, 14);
+    assert_eq!(spawn_native_send(Seven).await.unwrap(), 14);
+    assert_eq!(spawn_variant(Seven).await.unwrap(), 14);
+}
@@ -0,0 +1,46 @@
+#![allow(dead_code)]
+
+mod log {
+    macro_rules! debug {
+        (target: $target:expr, $($arg:tt)*) => {
+            let _ = ($target, format!($($arg)*));
+        };
+    }
+    pub(crate) use debug;
+}
+
+macro_rules! check {
+    ($cond:expr, $msg:expr) => {
+        assert!($cond, "{}", $msg)
+    };
+}
+
+#[maybe_async_cfg::maybe(sync(), async())]
+async fn value() -> u32 {
+    42
+}
+
+#[maybe_async_cfg::maybe(idents(value(fn)), macros(log::debug, check), sync(), async())]
+async fn run() -> Vec<String> {
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    write!(out, "{}", value().await).unwrap();
+
+    log::debug!(target: "app", "value = {}", value().await);
+    check!(value().await == 42, format!("got {}", value().await));
+
+    debug_assert!(matches!(value().await, 40..=50 | 0));
+    assert!(matches!(Some(value().await), Some(x) if x > 0));
+
+    let pairs = vec![(|a: u32, b: u32| a + b)(value().await, 1); 2];
+    assert_eq!(pairs, std::vec![43, 43]);
+
+    vec![out, std::format!("{}", value().await)]
+}
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(run_sync(), vec!["42".to_string(), "42".to_string()]);
+    assert_eq!(run_async().await, vec!["42".to_string(), "42".to_string()]);
+}
@@ -0,0 +1,51 @@
+#![allow(dead_code)]
+
+#[maybe_async_cfg::maybe(sync(), async())]
+async fn double(x: u32) -> u32 {
+    x * 2
+}
+
+#[maybe_async_cfg::maybe(sync(), async())]
+async fn checked(x: u32) -> Result<u32, String> {
+    if x < 10 {
+        Ok(x)
+    } else {
+        Err(format!("{} is too big", x))
+    }
+}
+
+#[maybe_async_cfg::maybe(idents(double(fn), checked(fn)), sync(), async())]
+async fn run(values: Vec<u32>) -> Result<u32, String> {
+    let (a, b) = tokio::join!(double(1), double(2));
+    let (c,) = futures::join!(double(3));
+    let (d, e) = tokio::try_join!(checked(4), checked(5))?;
+    let (f, g) = futures::future::try_join(checked(6), checked(7)).await?;
+
+    let doubled = futures::future::join_all(values.iter().map(|v| double(*v))).await;
+    let checked_all = futures::future::try_join_all(values.into_iter().map(checked)).await?;
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<u32>();
+    tx.send(100).unwrap();
+
+    let h = tokio::select! {
+        #[sync]
+        v = double(50) => v,
+        Some(v) = rx.recv() => v,
+    };
+
+    tokio::select! {
+        _ = double(0) => {}
+        #[sync]
+        else => {}
+    }
+
+    Ok(a + b + c + d + e + f + g + h + doubled.iter().sum::<u32>() + checked_all.iter().sum::<u32>())
+}
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(run_sync(vec![1, 2]), Ok(2 + 4 + 6 + 4 + 5 + 6 + 7 + 100 + 6 + 3));
+    assert!(run_async(vec![1, 2]).await.is_ok());
+    assert_eq!(run_sync(vec![1, 20]), Err("20 is too big".to_string()));
+    assert_eq!(run_async(vec![1, 20]).await, Err("20 is too big".to_string()));
+}
@@ -0,0 +1,37 @@
+#![allow(dead_code)]
+
+#[maybe_async_cfg::maybe(sync(), async(key = "tokio"), async(key = "std", suffix = "AsyncStd"))]
+async fn sleep_ms(ms: u64) -> &'static str {
+    maybe_async_cfg::sync_impl! {
+        std::thread::sleep(std::time::Duration::from_millis(ms));
+        "sync"
+    }
+    maybe_async_cfg::version_impl! { key = "tokio", {
+        tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+        "tokio"
+    }}
+    maybe_async_cfg::version_impl!(key = "std", {
+        async_std::task::sleep(std::time::Duration::from_millis(ms)).await;
+        "async-std"
+    })
+}
+
+#[maybe_async_cfg::maybe(sync(), async(key = "tokio"), async(key = "std", suffix = "AsyncStd"))]
+async fn kinds() -> Vec<&'static str> {
+    let mut found = vec![];
+    maybe_async_cfg::async_impl! { found.push("async"); }
+    let _ = maybe_async_cfg::sync_impl!(found.push("sync"));
+    maybe_async_cfg::version_impl!(tokio, std, { found.push("runtime") });
+    found
+}
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(sleep_ms_sync(1), "sync");
+    assert_eq!(sleep_ms_async(1).await, "tokio");
+    assert_eq!(sleep_ms_async_std(1).await, "async-std");
+
+    assert_eq!(kinds_sync(), vec!["sync"]);
+    assert_eq!(kinds_async().await, vec!["async", "runtime"]);
+    assert_eq!(kinds_async_std().await, vec!["async", "runtime"]);
+}
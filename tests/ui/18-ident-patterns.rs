@@ -0,0 +1,57 @@
+#![allow(dead_code)]
+
+mod clients {
+    pub struct HttpClientSync;
+    pub struct HttpClientAsync;
+
+    pub struct ConnBlocking;
+    pub struct ConnAsync;
+
+    pub fn open_sync() -> &'static str {
+        "sync"
+    }
+    pub async fn open_async() -> &'static str {
+        "async"
+    }
+
+    pub fn read_blocking() -> &'static str {
+        "blocking"
+    }
+    pub async fn read_async() -> &'static str {
+        "async"
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    idents(regex = "^(.*)Client$"),
+    idents(suffix = "Async", sync = "Blocking"),
+    idents(prefix = "maybe_"),
+    idents(regex = "^read_(.*)$", sync = "read_blocking", async = "read_async"),
+    sync(),
+    async()
+)]
+async fn run(_client: HttpClient, _conn: ConnAsync) -> (&'static str, &'static str) {
+    use self::clients::{maybe_open, read_any};
+
+    (maybe_open().await, read_any().await)
+}
+
+#[maybe_async_cfg::maybe(
+    idents(prefix = "Maybe"),
+    sync(self = "types_sync"),
+    async(self = "types_async")
+)]
+mod types {
+    pub struct MaybeConn;
+}
+
+use clients::*;
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(run_sync(HttpClientSync, ConnBlocking), ("sync", "blocking"));
+    assert_eq!(run_async(HttpClientAsync, ConnAsync).await, ("async", "async"));
+
+    let _ = types_sync::ConnSync;
+    let _ = types_async::ConnAsync;
+}
@@ -0,0 +1,43 @@
+#![allow(dead_code)]
+
+struct ClientSync;
+struct ClientAsync;
+
+struct ConnectionTokio;
+
+#[maybe_async_cfg::maybe(profile = "client")]
+impl Client {
+    async fn connect(&self) -> &'static str {
+        "connected"
+    }
+}
+
+#[maybe_async_cfg::maybe(profile = "client")]
+async fn open(client: &Client) -> &'static str {
+    client.connect().await
+}
+
+#[maybe_async_cfg::maybe(profile = "client", async(key = "only_async"))]
+async fn only_async(client: &Client) -> &'static str {
+    client.connect().await
+}
+
+#[maybe_async_cfg::maybe(profile = "named", keep_self)]
+async fn open_tokio(_conn: Connection) -> &'static str {
+    "tokio"
+}
+
+// the entry specified in place wins
+#[maybe_async_cfg::maybe(profile = "conflicting", idents(Client))]
+async fn conflicting(client: &Client) -> &'static str {
+    client.connect().await
+}
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(open_sync(&ClientSync), "connected");
+    assert_eq!(open_async(&ClientAsync).await, "connected");
+    assert_eq!(only_async_async(&ClientAsync).await, "connected");
+    assert_eq!(open_tokio(ConnectionTokio).await, "tokio");
+    assert_eq!(conflicting_sync(&ClientSync), "connected");
+}
@@ -0,0 +1,36 @@
+#![deny(deprecated)]
+#![allow(dead_code)]
+
+pub struct Conn;
+pub struct ConnSync;
+pub struct ConnAsync;
+
+// `Stale` is allowed explicitly
+#[maybe_async_cfg::maybe(idents(Conn, Stale(allow_unused)), sync(), async())]
+async fn open() -> Conn {
+    maybe_async_cfg::async_impl! {
+        let _: ConnAsync = ConnAsync;
+    }
+    Conn
+}
+
+#[maybe_async_cfg::maybe(idents(Conn, Stale), allow_unused_idents, sync(), async())]
+async fn reopen(c: Conn) -> Conn {
+    c
+}
+
+maybe_async_cfg::content! {
+    #![maybe_async_cfg::default(idents(Conn, Stale))]
+
+    #[maybe_async_cfg::maybe(sync(), async())]
+    async fn close(_: Conn) {}
+}
+
+#[tokio::main]
+async fn main() {
+    let _: ConnSync = open_sync();
+    let _: ConnAsync = open_async().await;
+    let _: ConnSync = reopen_sync(ConnSync);
+    close_sync(ConnSync);
+    close_async(ConnAsync).await;
+}
@@ -0,0 +1,26 @@
+#![allow(dead_code)]
+
+use std::future::Future;
+
+pub struct Task<F>(F);
+
+// the sync version keeps the future and runs it on the executor
+#[maybe_async_cfg::maybe(keep_self, idents(run(fn)), allow_async_leftovers, sync(), async())]
+impl<F: Future> Task<F> {
+    async fn run(self) -> F::Output {
+        let n;
+        maybe_async_cfg::sync_impl! {
+            n = futures::executor::block_on(self.0);
+        }
+        maybe_async_cfg::async_impl! {
+            n = self.0.await;
+        }
+        n
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(Task(async { 1 }).run_sync(), 1);
+    assert_eq!(Task(async { 2 }).run_async().await, 2);
+}
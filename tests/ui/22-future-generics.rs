@@ -0,0 +1,66 @@
+#![allow(dead_code, async_fn_in_trait)]
+
+use std::future::Future;
+
+#[maybe_async_cfg::maybe(sync(), async())]
+pub struct Task<F: Future<Output = u32>> {
+    fut: F,
+    retries: u32,
+}
+
+#[maybe_async_cfg::maybe(sync(), async())]
+impl<F> Task<F>
+where
+    F: Future<Output = u32>,
+{
+    pub fn new(fut: F) -> Self {
+        Self { fut, retries: 0 }
+    }
+
+    pub async fn run(self) -> u32 {
+        self.fut.await + self.retries
+    }
+
+    pub async fn then<G: Future<Output = u32>>(self, next: G) -> u32 {
+        self.run().await + next.await
+    }
+}
+
+#[maybe_async_cfg::maybe(sync(), async())]
+pub enum Job<F: Future<Output = u32>> {
+    Ready(u32),
+    Pending(F),
+}
+
+#[maybe_async_cfg::maybe(sync(), async())]
+pub trait Runner {
+    async fn run_job<F: Future<Output = u32>>(&self, job: F) -> u32;
+}
+
+#[maybe_async_cfg::maybe(idents(Runner), sync(), async())]
+impl Runner for () {
+    async fn run_job<F: Future<Output = u32>>(&self, job: F) -> u32 {
+        job.await
+    }
+}
+
+#[maybe_async_cfg::maybe(sync(), async())]
+async fn twice(fut: impl Future<Output = u32>) -> u32 {
+    fut.await * 2
+}
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(TaskSync::new(1).run(), 1);
+    assert_eq!(TaskSync::new(1).then(2), 3);
+    assert_eq!(TaskAsync::new(async { 1 }).then(async { 2 }).await, 3);
+
+    let _: JobSync = JobSync::Pending(1);
+    let _: JobAsync<std::future::Ready<u32>> = JobAsync::Pending(std::future::ready(1));
+
+    assert_eq!(RunnerSync::run_job(&(), 5), 5);
+    assert_eq!(RunnerAsync::run_job(&(), async { 5 }).await, 5);
+
+    assert_eq!(twice_sync(4), 8);
+    assert_eq!(twice_async(async { 4 }).await, 8);
+}
@@ -0,0 +1,37 @@
+#![allow(dead_code)]
+
+use std::{future::Future, io};
+
+#[maybe_async_cfg::maybe(sync(), async())]
+async fn read_all<F>(fut: F) -> Result<Vec<u8>, io::Error>
+where
+    F: Future<Output = Result<Vec<u8>, io::Error>>,
+{
+    fut.await
+}
+
+#[maybe_async_cfg::maybe(sync(), async())]
+async fn pair<'a, F: Future<Output = (&'a str, u32)>>(fut: F) -> (&'a str, u32) {
+    fut.await
+}
+
+#[maybe_async_cfg::maybe(sync(), async())]
+async fn unit<F: Future<Output = ()>, T>(fut: F, items: Vec<T>) -> usize
+where
+    Vec<T>: Clone,
+{
+    let () = fut.await;
+    items.clone().len()
+}
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(read_all_sync(Ok(vec![1, 2])).unwrap(), vec![1, 2]);
+    assert_eq!(read_all_async(async { Ok(vec![1, 2]) }).await.unwrap(), vec![1, 2]);
+
+    assert_eq!(pair_sync(("a", 1)), ("a", 1));
+    assert_eq!(pair_async(async { ("a", 1) }).await, ("a", 1));
+
+    assert_eq!(unit_sync((), vec![1, 2, 3]), 3);
+    assert_eq!(unit_async(async {}, vec![1, 2, 3]).await, 3);
+}
@@ -0,0 +1,59 @@
+#![allow(dead_code)]
+
+use std::future::Future;
+
+#[maybe_async_cfg::maybe(sync(), async())]
+async fn retry<F, Fut>(mut attempts: u32, mut op: F) -> Result<u32, ()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<u32, ()>>,
+{
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempts == 0 => return Err(err),
+            Err(_) => attempts -= 1,
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(sync(), async())]
+async fn apply(f: impl AsyncFn(u32) -> u32, value: u32) -> u32 {
+    f(value).await
+}
+
+#[maybe_async_cfg::maybe(idents(retry(fn), apply(fn)), sync(), async())]
+async fn run(base: u32) -> u32 {
+    let mut calls = 0;
+    let value = retry(3, || {
+        calls += 1;
+        let failed = calls < 3;
+        async move {
+            if failed { Err(()) } else { Ok(base) }
+        }
+    })
+    .await
+    .unwrap();
+
+    let name = String::from("total");
+    let total = apply(async move |x| {
+        let _ = &name;
+        x + 1
+    }, value)
+    .await;
+
+    let single = retry(0, || async { Ok(total) }).await.unwrap();
+    let unit = (|| async {
+        let _unused = 1;
+    })()
+    .await;
+    let () = unit;
+
+    single
+}
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(run_sync(1), 2);
+    assert_eq!(run_async(1).await, 2);
+}
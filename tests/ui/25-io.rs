@@ -0,0 +1,54 @@
+#![allow(dead_code)]
+
+#[maybe_async_cfg::maybe(io, sync(), async())]
+mod codec {
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+    pub async fn decode<R: AsyncRead + Unpin>(r: &mut R) -> std::io::Result<u32> {
+        let mut buf = [0u8; 4];
+        r.read_exact(&mut buf).await?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    pub async fn encode<W>(w: &mut W, value: u32) -> std::io::Result<()>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        w.write_all(&value.to_be_bytes()).await?;
+        w.flush().await
+    }
+
+    pub async fn copy(r: &mut (impl AsyncRead + Unpin), w: &mut (impl AsyncWrite + Unpin)) -> std::io::Result<()> {
+        let value = decode(r).await?;
+        encode(w, value).await
+    }
+}
+
+#[maybe_async_cfg::maybe(io, sync(), async())]
+mod lines {
+    use futures::io::{AsyncBufRead, AsyncBufReadExt};
+
+    pub async fn first_line<R: AsyncBufRead + Unpin>(r: &mut R) -> String {
+        let mut line = String::new();
+        r.read_line(&mut line).await.unwrap();
+        line
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut out = vec![];
+    codec_sync::encode(&mut out, 42).unwrap();
+    assert_eq!(codec_sync::decode(&mut out.as_slice()).unwrap(), 42);
+
+    let mut copied = vec![];
+    codec_sync::copy(&mut out.as_slice(), &mut copied).unwrap();
+    assert_eq!(copied, out);
+
+    let mut out = vec![];
+    codec_async::encode(&mut out, 7).await.unwrap();
+    assert_eq!(codec_async::decode(&mut out.as_slice()).await.unwrap(), 7);
+
+    assert_eq!(lines_sync::first_line(&mut &b"one\ntwo"[..]), "one\n");
+    assert_eq!(lines_async::first_line(&mut &b"one\ntwo"[..]).await, "one\n");
+}
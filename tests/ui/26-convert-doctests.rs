@@ -0,0 +1,56 @@
+#![allow(dead_code)]
+
+pub struct Foo;
+
+impl Foo {
+    pub fn new() -> Self {
+        Foo
+    }
+}
+
+pub type FooSync = Foo;
+pub type FooAsync = Foo;
+
+#[maybe_async_cfg::maybe(convert_doctests, idents(Foo), sync(), async())]
+/// A structure.
+///
+/// ```rust
+/// # async fn example(s: Struct) {
+/// let f = Foo::new(); // the `Foo` in the comment stays unchanged
+/// let n = s
+///     .get()
+///     .await;
+/// let c = async move { '"' };
+/// let s = "Foo.await";
+/// # }
+/// ```
+///
+/// ```text
+/// Foo::new().await
+/// ```
+///
+/// ```rust, only_if(async)
+/// let f: Foo;
+/// ```
+pub struct Struct {
+    f: Foo,
+}
+
+#[maybe_async_cfg::maybe(convert_doctests, idents(Struct, get(fn)), sync(), async())]
+impl Struct {
+    /// Returns one.
+    ///
+    /// ```
+    /// # async fn example(s: Struct) {
+    /// assert_eq!(s.get().await, 1);
+    /// # }
+    /// ```
+    pub async fn get(&self) -> u32 {
+        1
+    }
+}
+
+fn main() {
+    let s = StructSync { f: Foo::new() };
+    assert_eq!(s.get_sync(), 1);
+}
@@ -0,0 +1,36 @@
+#![allow(dead_code)]
+
+#[maybe_async_cfg::maybe(
+    convert_doctests,
+    idents(Client),
+    sync(key = "sync"),
+    sync(key = "blocking", suffix = "Blocking"),
+    async(key = "async")
+)]
+/// A client.
+///
+/// ```rust, only_if(sync, blocking)
+/// let c: Client;
+/// ```
+///
+/// ```rust, remove_if(blocking)
+/// let c: Client;
+/// ```
+///
+/// ```rust, only_if("async"), flags_if(async, no_run, edition2021)
+/// # async fn example(c: Client) {
+/// c.get().await;
+/// # }
+/// ```
+///
+/// - In a list:
+///   ```{.rust .remove_if(sync)}
+///   let c: Client;
+///   ```
+pub struct Client;
+
+fn main() {
+    let _ = ClientSync;
+    let _ = ClientAsync;
+    let _ = ClientBlocking;
+}
@@ -0,0 +1,29 @@
+#![allow(dead_code)]
+
+#[maybe_async_cfg::maybe(idents(Client), sync(), async())]
+/// The client, see [`{Self}::get`].
+///
+/// {{sync: Blocks the thread | async: Does not block the thread}} while waiting for the response.
+///
+/// <!-- only_if(async) -->
+/// Requires a runtime.
+/// <!-- end -->
+/// Dropping the [`{Self}`] closes the connection<!-- remove_if(sync) -->, even when a request is
+/// in progress<!-- end -->.
+///
+/// ```rust, ignore
+/// let s = "{Self}";
+/// ```
+pub struct Client;
+
+#[maybe_async_cfg::maybe(idents(Client), sync(), async())]
+impl Client {
+    /// Returns the value {{async, blocking: of the future | _: now}}, see [`{Self}`].
+    pub async fn get(&self) -> u32 {
+        1
+    }
+}
+
+fn main() {
+    assert_eq!(ClientSync.get(), 1);
+}
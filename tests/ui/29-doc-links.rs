@@ -0,0 +1,28 @@
+#![allow(dead_code)]
+
+#[maybe_async_cfg::maybe(idents(Client, fetch(fn)), sync(), async())]
+/// See [`Client::get`], [Client], [`fetch()`], [the client](Client "title"),
+/// [`Client`](crate::Client), [struct](struct@Client), [`Client`][client-ref], [`Client`][],
+/// [Self::get] and [web](https://example.com/Client).
+///
+/// [client-ref]: crate::Client
+/// [`Client`]: Client
+pub struct Client;
+
+#[maybe_async_cfg::maybe(idents(Client, fetch(fn)), sync(), async())]
+impl Client {
+    /// Returns one, see [`fetch`].
+    pub async fn get(&self) -> u32 {
+        1
+    }
+}
+
+#[maybe_async_cfg::maybe(idents(Client, fetch(fn)), sync(), async())]
+/// Fetches with [`Client`].
+pub async fn fetch(c: &Client) -> u32 {
+    c.get().await
+}
+
+fn main() {
+    assert_eq!(fetch_sync(&ClientSync), 1);
+}
@@ -0,0 +1,26 @@
+#![allow(dead_code, unexpected_cfgs)]
+
+#[maybe_async_cfg::maybe(
+    sync(replace_feature("secure", "secure_sync")),
+    async()
+)]
+#[cfg_attr(feature = "secure", derive(Debug))]
+#[cfg_attr(docsrs, cfg_attr(feature = "secure", doc(cfg(feature = "secure"))))]
+pub struct Config {
+    #[cfg_attr(all(feature = "secure", not(test)), allow(unused))]
+    pub port: u16,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(replace_feature("secure", "secure_sync")),
+    async()
+)]
+impl Config {
+    pub async fn is_secure(&self) -> bool {
+        cfg!(feature = "secure") || cfg!(all(unix, feature = "secure"))
+    }
+}
+
+fn main() {
+    assert!(!ConfigSync { port: 80 }.is_secure());
+}
@@ -0,0 +1,11 @@
+#[maybe_async_cfg::maybe(idents(abs(fn), labs(fn)), sync(), async())]
+extern "C" {
+    fn abs(x: i32) -> i32;
+    fn labs(x: std::os::raw::c_long) -> std::os::raw::c_long;
+}
+
+fn main() {
+    assert_eq!(unsafe { abs_sync(-3) }, 3);
+    assert_eq!(unsafe { abs_async(-4) }, 4);
+    assert_eq!(unsafe { labs_sync(-5) }, 5);
+}
@@ -0,0 +1,54 @@
+#![deny(deprecated)]
+#![allow(dead_code)]
+
+pub struct ClientSync;
+pub struct ClientAsync;
+
+impl ClientSync {
+    fn get(&self) -> u32 {
+        1
+    }
+}
+
+impl ClientAsync {
+    async fn get(&self) -> u32 {
+        2
+    }
+}
+
+pub struct PoolAsync;
+
+// the macro does not use `Client`, but shares the list of idents with the other items
+#[maybe_async_cfg::maybe(idents(Client, Handle), allow_unused_idents, sync(), async())]
+macro_rules! fetch {
+    ($client:expr) => {{
+        let client: &Handle = $client;
+        async move { client.get().await }.await
+    }};
+}
+
+type HandleSync = ClientSync;
+type HandleAsync = ClientAsync;
+
+#[maybe_async_cfg::maybe(idents(fetch(fn), Client), sync(), async())]
+async fn total(client: &Client) -> u32 {
+    fetch!(client) + 10
+}
+
+// `Pool` is used by the async version only, so it is not reported
+#[maybe_async_cfg::maybe(idents(Pool), sync(), async())]
+async fn pooled() -> bool {
+    maybe_async_cfg::async_impl! {
+        let _: Pool = Pool;
+        return true;
+    }
+    false
+}
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(total_sync(&ClientSync), 11);
+    assert_eq!(total_async(&ClientAsync).await, 12);
+    assert!(!pooled_sync());
+    assert!(pooled_async().await);
+}
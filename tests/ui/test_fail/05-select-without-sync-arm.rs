@@ -0,0 +1,9 @@
+#[maybe_async_cfg::maybe(sync(), async())]
+async fn first(a: tokio::sync::oneshot::Receiver<u32>, b: tokio::sync::oneshot::Receiver<u32>) -> u32 {
+    tokio::select! {
+        Ok(v) = a => v,
+        Ok(v) = b => v,
+    }
+}
+
+fn main() {}
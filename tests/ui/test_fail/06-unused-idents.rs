@@ -0,0 +1,11 @@
+#![deny(deprecated)]
+
+pub struct ConnSync;
+pub struct ConnAsync;
+
+#[maybe_async_cfg::maybe(idents(Conn, Cnn), sync(), async())]
+async fn open(c: Conn) -> Conn {
+    c
+}
+
+fn main() {}
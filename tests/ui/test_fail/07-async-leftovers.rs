@@ -0,0 +1,31 @@
+use std::future::Future;
+
+macro_rules! run {
+    ($e:expr) => {
+        $e
+    };
+}
+
+pub struct Runner<F>(F);
+
+#[maybe_async_cfg::maybe(validate, sync(), async())]
+impl<F: Future> Runner<F> {
+    async fn run(self) -> F::Output {
+        self.0.await
+    }
+}
+
+#[maybe_async_cfg::maybe(validate, sync(), async())]
+pub trait Service {
+    async fn call(&self) -> u32;
+}
+
+#[maybe_async_cfg::maybe(validate, sync(), async())]
+#[async_trait::async_trait]
+impl Service for u32 {
+    async fn call(&self) -> u32 {
+        run!(async { *self }.await)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use std::future::Future;
+
+pub trait Source {
+    type Fut: Future<Output = u32>;
+    fn get(&self) -> Self::Fut;
+}
+
+#[maybe_async_cfg::maybe(sync(), async())]
+async fn fetch<S: Source>(source: S) -> u32
+where
+    S::Fut: Future<Output = u32>,
+{
+    source.get().await
+}
+
+fn main() {}
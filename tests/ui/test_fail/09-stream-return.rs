@@ -0,0 +1,13 @@
+#[maybe_async_cfg::maybe(streams, sync(), async())]
+fn numbers(n: u32) -> impl futures::Stream<Item = u32> {
+    async_stream::stream! {
+        for i in 0..n {
+            if i == 10 {
+                return;
+            }
+            yield i;
+        }
+    }
+}
+
+fn main() {}